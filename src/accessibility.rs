@@ -0,0 +1,56 @@
+use crate::store::SettingsFile;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Reflect, Debug, Clone, PartialEq, Resource, Serialize, Deserialize)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    /// Turns off camera shake, banking and other fast movement for players sensitive to motion.
+    pub reduce_motion: bool,
+    /// Shows where the player's leader is headed so the momentum is easier to get used to.
+    pub path_preview: bool,
+    /// Draws a ring where boids start turning back into the arena.
+    pub boundary_ring: bool,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            reduce_motion: false,
+            path_preview: false,
+            // New players don't know where the edge is until they hit it.
+            boundary_ring: true,
+        }
+    }
+}
+
+impl SettingsFile for AccessibilitySettings {
+    const FILE: &'static str = "accessibility.ron";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{from_ron, to_ron};
+
+    #[test]
+    fn test_round_trip() {
+        let settings = AccessibilitySettings {
+            reduce_motion: true,
+            path_preview: true,
+            boundary_ring: false,
+        };
+        assert_eq!(
+            from_ron::<AccessibilitySettings>(&to_ron(&settings).unwrap()).unwrap(),
+            settings
+        );
+        assert_eq!(
+            from_ron::<AccessibilitySettings>("(reduce_motion: true)").unwrap(),
+            AccessibilitySettings {
+                reduce_motion: true,
+                ..default()
+            }
+        );
+    }
+}
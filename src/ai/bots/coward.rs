@@ -1,13 +1,25 @@
+use crate::ai::bots::{Bot, BotDebugControls};
 use crate::math::direction_to_turn_away_from_target;
 use crate::{BoidAveragedInputs, Leader};
 use bevy::prelude::*;
 use std::fmt::Formatter;
 
-const RUN_AWAY_RANGE_SQUARED: f32 = 300.0f32 * 300.0f32;
+/// How close another leader has to be before a scaredy cat runs, before any jitter
+pub const RUN_AWAY_RANGE: f32 = 300.0;
 
 /// A bot that always boosts
-#[derive(Default, Component)]
-pub struct ScaredyCat {}
+#[derive(Component)]
+pub struct ScaredyCat {
+    pub run_away_range: f32,
+}
+
+impl Default for ScaredyCat {
+    fn default() -> Self {
+        Self {
+            run_away_range: RUN_AWAY_RANGE,
+        }
+    }
+}
 
 impl std::fmt::Display for ScaredyCat {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -17,21 +29,22 @@ impl std::fmt::Display for ScaredyCat {
 
 #[allow(clippy::type_complexity)]
 pub fn update(
-    mut query: Query<
-        (Entity, &Transform, &mut BoidAveragedInputs),
-        (With<ScaredyCat>, With<Leader>),
-    >,
+    mut query: Query<(Entity, &Transform, &mut BoidAveragedInputs, &ScaredyCat), With<Leader>>,
     leaders: Query<(Entity, &Transform), With<Leader>>,
+    debug_controls: Res<BotDebugControls>,
 ) {
+    if debug_controls.is_paused(Bot::ScaredyCat) {
+        return;
+    }
     let leaders: Vec<_> = leaders.iter().map(|(e, t)| (e, *t)).collect();
-    for (entity, transform, mut inputs) in query.iter_mut() {
+    for (entity, transform, mut inputs, scaredy_cat) in query.iter_mut() {
         if let Some(closest_leader) = leaders
             .iter()
             .filter(|(e, _)| *e != entity)
             .map(|(_, t)| (t.translation.distance_squared(transform.translation), t))
             .min_by(|(a, _), (b, _)| a.total_cmp(b))
         {
-            if closest_leader.0 < RUN_AWAY_RANGE_SQUARED {
+            if closest_leader.0 < scaredy_cat.run_away_range.powi(2) {
                 inputs.add_turn(direction_to_turn_away_from_target(
                     transform,
                     closest_leader.1.translation.truncate(),
@@ -1,14 +1,31 @@
+use crate::ai::bots::{Bot, BotDebugControls};
 use crate::math::direction_to_turn_towards_target;
-use crate::{BoidAveragedInputs, BoidColor, Leader};
+use crate::{BoidAveragedInputs, BoidColor, BoidSettings, Leader, RoundSettings};
 use bevy::prelude::*;
 use bevy::utils::HashMap;
+use bevy_prototype_debug_lines::DebugLines;
+use std::f32::consts::TAU;
 use std::fmt::Formatter;
 
-const SIGHT_RANGE_SQUARED: f32 = 500.0 * 500.0;
+/// How far a hunter can see targets before any jitter
+pub const SIGHT_RANGE: f32 = 500.0;
 
 /// A bot that always boosts
-#[derive(Default, Component)]
-pub struct Hunter {}
+#[derive(Component)]
+pub struct Hunter {
+    pub sight_range: f32,
+    /// The leader we're chasing, kept until it gets away or a much weaker one shows up.
+    pub target: Option<Entity>,
+}
+
+impl Default for Hunter {
+    fn default() -> Self {
+        Self {
+            sight_range: SIGHT_RANGE,
+            target: None,
+        }
+    }
+}
 
 impl std::fmt::Display for Hunter {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -19,37 +36,63 @@ impl std::fmt::Display for Hunter {
 #[allow(clippy::type_complexity)]
 pub fn update(
     mut query: Query<
-        (Entity, &Transform, &mut BoidAveragedInputs, &BoidColor),
-        (With<Hunter>, With<Leader>),
+        (
+            Entity,
+            &Transform,
+            &mut BoidAveragedInputs,
+            &BoidColor,
+            &mut Hunter,
+        ),
+        With<Leader>,
     >,
     leaders: Query<(Entity, &Transform, &BoidColor), With<Leader>>,
     boid_colors: Query<&BoidColor>,
+    mut lines: ResMut<DebugLines>,
+    boid_settings: Res<BoidSettings>,
+    round_settings: Res<RoundSettings>,
+    debug_controls: Res<BotDebugControls>,
 ) {
+    if debug_controls.is_paused(Bot::Hunter) {
+        return;
+    }
     let mut color_counts: HashMap<BoidColor, usize> = HashMap::new();
     for other_color in boid_colors.iter() {
         let count = color_counts.entry(*other_color).or_insert(0);
         *count += 1;
     }
-    let leaders: Vec<_> = leaders.iter().map(|(e, t, c)| (e, *t, c)).collect();
-    for (entity, transform, mut inputs, color) in query.iter_mut() {
-        if let Some(closest_leader) = leaders
-            .iter()
-            // Don't consider self as a target
-            .filter(|(e, _, _)| *e != entity)
-            // Don't consider targets that have more followers than us
-            .filter(|(_, _, c)| {
-                color_counts.get(*c).cloned().unwrap_or_default()
-                    < color_counts.get(color).cloned().unwrap_or_default()
-            })
-            .map(|(_, t, c)| (t.translation.distance_squared(transform.translation), t, c))
-            // limit sight range
-            .filter(|(d, _, _)| *d < SIGHT_RANGE_SQUARED)
-            // find the leader with the least followers
-            .min_by(|(_, _, a), (_, _, b)| color_counts[a].cmp(&color_counts[b]))
-        {
+    let leaders: Vec<_> = leaders.iter().map(|(e, t, c)| (e, *t, *c)).collect();
+    for (entity, transform, mut inputs, color, mut hunter) in query.iter_mut() {
+        let target = choose_target(
+            entity,
+            transform,
+            *color,
+            hunter.sight_range,
+            hunter.target,
+            round_settings.hunter_switch_margin,
+            &leaders,
+            &color_counts,
+        );
+        hunter.target = target.map(|(e, _)| e);
+        let target = target.map(|(_, t)| t);
+        if boid_settings.debug_lines {
+            let debug_color = match target {
+                Some(_) => Color::LIME_GREEN,
+                None => Color::ORANGE_RED,
+            };
+            draw_ring(
+                &mut lines,
+                transform.translation,
+                hunter.sight_range,
+                debug_color,
+            );
+            if let Some(target) = target {
+                lines.line_colored(transform.translation, target.translation, 0.0, debug_color);
+            }
+        }
+        if let Some(target) = target {
             inputs.add_turn(direction_to_turn_towards_target(
                 transform,
-                closest_leader.1.translation.truncate(),
+                target.translation.truncate(),
             ));
             inputs.add_speed(1.0);
         } else {
@@ -57,3 +100,146 @@ pub fn update(
         }
     }
 }
+
+/// Picks the leader in sight range with the fewest followers, as long as it has fewer
+/// followers than us. Sticks with the `current` target unless another one has `switch_margin`
+/// fewer followers than it, so similar targets don't make us flip-flop between them.
+#[allow(clippy::too_many_arguments)]
+fn choose_target<'a>(
+    entity: Entity,
+    transform: &Transform,
+    color: BoidColor,
+    sight_range: f32,
+    current: Option<Entity>,
+    switch_margin: f32,
+    leaders: &'a [(Entity, Transform, BoidColor)],
+    color_counts: &HashMap<BoidColor, usize>,
+) -> Option<(Entity, &'a Transform)> {
+    let count = |c: &BoidColor| color_counts.get(c).cloned().unwrap_or_default();
+    let candidates: Vec<&(Entity, Transform, BoidColor)> = leaders
+        .iter()
+        // Don't consider self as a target
+        .filter(|(e, _, _)| *e != entity)
+        // Don't consider targets that have more followers than us
+        .filter(|(_, _, c)| count(c) < count(&color))
+        // limit sight range
+        .filter(|(_, t, _)| {
+            t.translation.distance_squared(transform.translation) < sight_range * sight_range
+        })
+        .collect();
+    // find the leader with the least followers
+    let best = candidates.iter().min_by_key(|(_, _, c)| count(c)).copied();
+    let current =
+        current.and_then(|current| candidates.iter().find(|(e, _, _)| *e == current).copied());
+    let target = match (current, best) {
+        (Some(current), Some(best))
+            if count(&best.2) as f32 >= count(&current.2) as f32 * (1.0 - switch_margin) =>
+        {
+            Some(current)
+        }
+        (_, best) => best,
+    };
+    target.map(|(e, t, _)| (*e, t))
+}
+
+fn draw_ring(lines: &mut DebugLines, center: Vec3, radius: f32, color: Color) {
+    const SEGMENTS: usize = 32;
+    let point = |i: usize| {
+        let angle = TAU * i as f32 / SEGMENTS as f32;
+        center + Vec3::new(angle.cos(), angle.sin(), 0.0) * radius
+    };
+    for i in 0..SEGMENTS {
+        lines.line_colored(point(i), point(i + 1), 0.0, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose_target() {
+        let leader = |id: u32, x: f32, color: BoidColor| {
+            (
+                Entity::from_raw(id),
+                Transform::from_xyz(x, 0.0, 0.0),
+                color,
+            )
+        };
+        let leaders = [
+            leader(0, 0.0, BoidColor::Red),
+            leader(1, 100.0, BoidColor::Blue),
+            leader(2, 200.0, BoidColor::Green),
+            leader(3, 1000.0, BoidColor::Yellow),
+            leader(4, 50.0, BoidColor::Purple),
+        ];
+        let color_counts: HashMap<BoidColor, usize> = [
+            (BoidColor::Red, 10),
+            (BoidColor::Blue, 5),
+            (BoidColor::Green, 3),
+            (BoidColor::Yellow, 1),
+            (BoidColor::Purple, 20),
+        ]
+        .into_iter()
+        .collect();
+        let target = choose_target(
+            Entity::from_raw(0),
+            &leaders[0].1,
+            BoidColor::Red,
+            SIGHT_RANGE,
+            None,
+            0.0,
+            &leaders,
+            &color_counts,
+        );
+        assert_eq!(target, Some((leaders[2].0, &leaders[2].1)));
+    }
+
+    #[test]
+    fn test_hunter_commits_to_its_target() {
+        let leaders = [
+            (Entity::from_raw(0), Transform::default(), BoidColor::Red),
+            (
+                Entity::from_raw(1),
+                Transform::from_xyz(100.0, 0.0, 0.0),
+                BoidColor::Blue,
+            ),
+            (
+                Entity::from_raw(2),
+                Transform::from_xyz(-100.0, 0.0, 0.0),
+                BoidColor::Green,
+            ),
+        ];
+        let target = |current: Option<Entity>, blue: usize, green: usize| {
+            let color_counts: HashMap<BoidColor, usize> = [
+                (BoidColor::Red, 50),
+                (BoidColor::Blue, blue),
+                (BoidColor::Green, green),
+            ]
+            .into_iter()
+            .collect();
+            choose_target(
+                leaders[0].0,
+                &leaders[0].1,
+                BoidColor::Red,
+                SIGHT_RANGE,
+                current,
+                0.25,
+                &leaders,
+                &color_counts,
+            )
+            .map(|(e, _)| e)
+        };
+        let blue = Some(leaders[1].0);
+        let green = Some(leaders[2].0);
+        // Blue and green take turns being slightly weaker, we keep chasing blue.
+        let mut current = target(None, 9, 10);
+        assert_eq!(current, blue);
+        for (blue_count, green_count) in [(10, 9), (9, 10), (10, 9)] {
+            current = target(current, blue_count, green_count);
+            assert_eq!(current, blue);
+        }
+        // Green gets much weaker so it's worth switching.
+        assert_eq!(target(current, 10, 3), green);
+    }
+}
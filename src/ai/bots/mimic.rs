@@ -0,0 +1,155 @@
+use crate::ai::bots::{Bot, BotDebugControls};
+use crate::math::direction_to_turn_towards_target;
+use crate::{BoidAveragedInputs, Leader, PlayerActions, Velocity};
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use std::collections::VecDeque;
+use std::fmt::Formatter;
+
+/// How many of the target's recent positions are remembered to work out where it's heading.
+const HISTORY_LENGTH: usize = 15;
+/// Never aims further ahead than this many seconds before any jitter, the target will have turned
+/// by then.
+pub const MAX_LEAD_SECONDS: f32 = 2.0;
+/// Used instead of our speed when working out how long we'll take to get there, so a slow
+/// start doesn't make us aim miles ahead.
+const MIN_INTERCEPT_SPEED: f32 = 50.0;
+
+/// A bot that watches the nearest human and cuts them off where they're going.
+#[derive(Component)]
+pub struct Mimic {
+    target: Option<Entity>,
+    /// The target's recent positions and when we saw them, oldest first.
+    history: VecDeque<(f32, Vec2)>,
+    pub max_lead_seconds: f32,
+}
+
+impl Default for Mimic {
+    fn default() -> Self {
+        Self::new(MAX_LEAD_SECONDS)
+    }
+}
+
+impl std::fmt::Display for Mimic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Mimic")
+    }
+}
+
+impl Mimic {
+    pub fn new(max_lead_seconds: f32) -> Self {
+        Self {
+            target: None,
+            history: VecDeque::new(),
+            max_lead_seconds,
+        }
+    }
+
+    /// Remembers where the target is, forgetting the old history if the target changed.
+    pub fn record(&mut self, target: Entity, seconds: f32, position: Vec2) {
+        if self.target != Some(target) {
+            self.target = Some(target);
+            self.history.clear();
+        }
+        self.history.push_back((seconds, position));
+        while self.history.len() > HISTORY_LENGTH {
+            self.history.pop_front();
+        }
+    }
+
+    /// The target's average velocity over the remembered history.
+    pub fn target_velocity(&self) -> Vec2 {
+        match (self.history.front(), self.history.back()) {
+            (Some((start, from)), Some((end, to))) if end > start => (*to - *from) / (end - start),
+            _ => Vec2::ZERO,
+        }
+    }
+
+    /// Where to aim from `position` at `speed` to meet the target, assuming it keeps going in
+    /// a straight line.
+    pub fn intercept_point(&self, position: Vec2, speed: f32) -> Option<Vec2> {
+        let (_, target) = self.history.back()?;
+        let lead_seconds =
+            (target.distance(position) / speed.max(MIN_INTERCEPT_SPEED)).min(self.max_lead_seconds);
+        Some(*target + self.target_velocity() * lead_seconds)
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn update(
+    mut query: Query<
+        (
+            Entity,
+            &Transform,
+            &Velocity,
+            &mut BoidAveragedInputs,
+            &mut Mimic,
+        ),
+        With<Leader>,
+    >,
+    leaders: Query<(Entity, &Transform, Option<&InputMap<PlayerActions>>), With<Leader>>,
+    time: Res<Time>,
+    debug_controls: Res<BotDebugControls>,
+) {
+    if debug_controls.is_paused(Bot::Mimic) {
+        return;
+    }
+    let leaders: Vec<_> = leaders
+        .iter()
+        .map(|(e, t, input_map)| (e, t.translation.truncate(), input_map.is_some()))
+        .collect();
+    let any_humans = leaders.iter().any(|(_, _, human)| *human);
+    for (entity, transform, velocity, mut inputs, mut mimic) in query.iter_mut() {
+        let position = transform.translation.truncate();
+        // Go after the nearest human, or the nearest anyone when only bots are playing.
+        let target = leaders
+            .iter()
+            .filter(|(e, _, human)| *e != entity && (*human || !any_humans))
+            .min_by(|(_, a, _), (_, b, _)| {
+                a.distance_squared(position)
+                    .total_cmp(&b.distance_squared(position))
+            });
+        let (target, target_position, _) = match target {
+            Some(target) => target,
+            None => {
+                inputs.add_speed(-1.0);
+                continue;
+            }
+        };
+        mimic.record(*target, time.elapsed_seconds(), *target_position);
+        if let Some(intercept) = mimic.intercept_point(position, velocity.forward) {
+            inputs.add_turn(direction_to_turn_towards_target(transform, intercept));
+            inputs.add_speed(1.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aims_ahead_of_straight_moving_target() {
+        let mut mimic = Mimic::default();
+        let target = Entity::from_raw(1);
+        // The target flies right along y = 200 at 100 units per second.
+        for frame in 0..10 {
+            let seconds = frame as f32 * 0.1;
+            mimic.record(target, seconds, Vec2::new(seconds * 100.0, 200.0));
+        }
+        let current = Vec2::new(90.0, 200.0);
+        assert!(mimic
+            .target_velocity()
+            .abs_diff_eq(Vec2::new(100.0, 0.0), 0.001));
+
+        let intercept = mimic.intercept_point(Vec2::ZERO, 100.0).unwrap();
+        assert!(intercept.x > current.x);
+        assert!((intercept.y - 200.0).abs() < 0.001);
+
+        // Facing straight at the target, we still turn right to cut it off.
+        let transform =
+            Transform::from_rotation(Quat::from_rotation_z(-current.x.atan2(current.y)));
+        assert!(direction_to_turn_towards_target(&transform, current).abs() < 0.001);
+        assert!(direction_to_turn_towards_target(&transform, intercept) > 0.0);
+    }
+}
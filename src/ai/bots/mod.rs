@@ -1,19 +1,38 @@
 use bevy::ecs::system::EntityCommands;
-use bevy::prelude::{FromReflect, Reflect};
+use bevy::prelude::{Component, FromReflect, Reflect, Resource};
+use bevy::utils::HashSet;
+use serde::{Deserialize, Serialize};
 use std::fmt::Formatter;
+use turborand::prelude::*;
 
 pub mod bonehead;
 pub mod coward;
 pub mod hunter;
+pub mod mimic;
 pub mod speedy;
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Reflect, FromReflect)]
+/// Also added to bot leaders so we know which bot to put back after a player drops out.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Hash,
+    Default,
+    Reflect,
+    FromReflect,
+    Component,
+    Serialize,
+    Deserialize,
+)]
 pub enum Bot {
     #[default]
     BoneHead,
     Speedy,
     ScaredyCat,
     Hunter,
+    Mimic,
 }
 
 impl std::fmt::Display for Bot {
@@ -23,12 +42,28 @@ impl std::fmt::Display for Bot {
             Bot::Speedy => write!(f, "{}", speedy::Speedy::default()),
             Bot::ScaredyCat => write!(f, "{}", coward::ScaredyCat::default()),
             Bot::Hunter => write!(f, "{}", hunter::Hunter::default()),
+            Bot::Mimic => write!(f, "{}", mimic::Mimic::default()),
         }
     }
 }
 
 impl Bot {
+    /// The bots with an update system that can be paused, BoneHead only wanders.
+    pub const PAUSABLE: [Self; 4] = [Self::Speedy, Self::ScaredyCat, Self::Hunter, Self::Mimic];
+
     pub fn insert(&self, commands: &mut EntityCommands) {
+        self.insert_with_jitter(commands, 0.0, &Rng::with_seed(0));
+    }
+
+    /// Inserts the bot with its settings nudged up or down by up to `jitter` (0.1 is ±10%), so
+    /// bots of the same type don't all behave exactly the same.
+    pub fn insert_with_jitter(
+        &self,
+        commands: &mut EntityCommands,
+        jitter: f32,
+        rand: &impl TurboRand,
+    ) {
+        commands.insert(*self);
         match self {
             Bot::BoneHead => {
                 commands.insert(bonehead::BoneHead::default());
@@ -37,11 +72,123 @@ impl Bot {
                 commands.insert(speedy::Speedy::default());
             }
             Bot::ScaredyCat => {
-                commands.insert(coward::ScaredyCat::default());
+                commands.insert(coward::ScaredyCat {
+                    run_away_range: jittered(coward::RUN_AWAY_RANGE, jitter, rand),
+                });
             }
             Bot::Hunter => {
-                commands.insert(hunter::Hunter::default());
+                commands.insert(hunter::Hunter {
+                    sight_range: jittered(hunter::SIGHT_RANGE, jitter, rand),
+                    ..Default::default()
+                });
+            }
+            Bot::Mimic => {
+                commands.insert(mimic::Mimic::new(jittered(
+                    mimic::MAX_LEAD_SECONDS,
+                    jitter,
+                    rand,
+                )));
+            }
+        }
+    }
+
+    /// Removes whichever bot is controlling the entity.
+    pub fn remove(commands: &mut EntityCommands) {
+        commands.remove::<(
+            Bot,
+            bonehead::BoneHead,
+            speedy::Speedy,
+            coward::ScaredyCat,
+            hunter::Hunter,
+            mimic::Mimic,
+        )>();
+    }
+}
+
+/// Returns `value` randomly moved up or down by up to `jitter` of itself.
+pub fn jittered(value: f32, jitter: f32, rand: &impl TurboRand) -> f32 {
+    value * (1.0 + jitter * (rand.f32() * 2.0 - 1.0))
+}
+
+/// Lets developers freeze some kinds of bots to watch the others.
+#[derive(Resource, Debug, Default)]
+pub struct BotDebugControls {
+    pub paused: HashSet<Bot>,
+}
+
+impl BotDebugControls {
+    pub fn is_paused(&self, bot: Bot) -> bool {
+        self.paused.contains(&bot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoidAveragedInputs, BoidColor, BoidSettings, Leader, RoundSettings};
+    use bevy::ecs::system::SystemState;
+    use bevy::prelude::*;
+    use bevy_prototype_debug_lines::DebugLines;
+
+    #[test]
+    fn test_jitter_varies_bots_of_the_same_type() {
+        let sight_ranges = |jitter: f32| {
+            let mut world = World::new();
+            let rand = Rng::with_seed(5);
+            let mut system_state: SystemState<Commands> = SystemState::new(&mut world);
+            let mut commands = system_state.get_mut(&mut world);
+            for _ in 0..2 {
+                Bot::Hunter.insert_with_jitter(&mut commands.spawn_empty(), jitter, &rand);
             }
+            system_state.apply(&mut world);
+            world
+                .query::<&hunter::Hunter>()
+                .iter(&world)
+                .map(|hunter| hunter.sight_range)
+                .collect::<Vec<f32>>()
+        };
+        let jittered = sight_ranges(0.1);
+        assert_ne!(jittered[0], jittered[1]);
+        for sight_range in jittered {
+            assert!((sight_range - hunter::SIGHT_RANGE).abs() <= hunter::SIGHT_RANGE * 0.1);
         }
+        assert_eq!(sight_ranges(0.0), vec![hunter::SIGHT_RANGE; 2]);
+    }
+
+    #[test]
+    fn test_paused_bots_add_no_inputs() {
+        let mut world = World::new();
+        world.init_resource::<DebugLines>();
+        world.init_resource::<BoidSettings>();
+        world.init_resource::<RoundSettings>();
+        world.insert_resource(BotDebugControls {
+            paused: [Bot::Hunter].into_iter().collect(),
+        });
+        let hunter = world
+            .spawn((
+                hunter::Hunter::default(),
+                Leader,
+                BoidColor::Red,
+                Transform::default(),
+                BoidAveragedInputs::default(),
+            ))
+            .id();
+        let coward = world
+            .spawn((
+                coward::ScaredyCat::default(),
+                Leader,
+                BoidColor::Blue,
+                Transform::from_xyz(10.0, 0.0, 0.0),
+                BoidAveragedInputs::default(),
+            ))
+            .id();
+        let mut schedule = Schedule::new();
+        schedule.add_systems((hunter::update, coward::update));
+        schedule.run(&mut world);
+
+        let inputs = |entity| world.get::<BoidAveragedInputs>(entity).unwrap();
+        assert_eq!(inputs(hunter).speed_average(), 0.0);
+        assert_eq!(inputs(hunter).turn_average(), 0.0);
+        assert_ne!(inputs(coward).speed_average(), 0.0);
     }
 }
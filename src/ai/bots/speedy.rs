@@ -1,3 +1,4 @@
+use crate::ai::bots::{Bot, BotDebugControls};
 use crate::{BoidAveragedInputs, Leader};
 use bevy::prelude::*;
 use std::fmt::Formatter;
@@ -12,7 +13,13 @@ impl std::fmt::Display for Speedy {
     }
 }
 
-pub fn update(mut query: Query<&mut BoidAveragedInputs, (With<Speedy>, With<Leader>)>) {
+pub fn update(
+    mut query: Query<&mut BoidAveragedInputs, (With<Speedy>, With<Leader>)>,
+    debug_controls: Res<BotDebugControls>,
+) {
+    if debug_controls.is_paused(Bot::Speedy) {
+        return;
+    }
     for mut inputs in query.iter_mut() {
         inputs.add_speed(1.0);
     }
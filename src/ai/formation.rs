@@ -0,0 +1,202 @@
+use crate::math::direction_to_turn_towards_target;
+use crate::offscreen::SteeringThrottle;
+use crate::{Boid, BoidAveragedInputs, BoidColor, BoidSettings, Leader, PlayerActions};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+/// The shape a flock holds around its leader while in formation
+#[derive(
+    Debug, Copy, Clone, Eq, PartialEq, Default, Reflect, FromReflect, Serialize, Deserialize,
+)]
+pub enum Formation {
+    #[default]
+    V,
+    Circle,
+    Line,
+}
+
+/// Add to a leader to make its flock hold a formation instead of loosely following.
+#[derive(Component, Debug)]
+pub struct InFormation;
+
+/// Returns where the boid in `slot` out of `slot_count` should be relative to the leader,
+/// where +y is the direction the leader is facing.
+pub fn formation_offset(
+    formation: Formation,
+    slot: usize,
+    slot_count: usize,
+    spacing: f32,
+) -> Vec2 {
+    match formation {
+        Formation::V => {
+            let row = (slot / 2 + 1) as f32;
+            let side = match slot.is_multiple_of(2) {
+                true => 1.0,
+                false => -1.0,
+            };
+            Vec2::new(side * row * spacing, -row * spacing)
+        }
+        Formation::Circle => {
+            // Make the circle big enough that the boids are `spacing` apart around it.
+            let radius = (spacing * slot_count as f32 / TAU).max(spacing);
+            let angle = TAU * slot as f32 / slot_count.max(1) as f32;
+            Vec2::new(angle.cos(), angle.sin()) * radius
+        }
+        Formation::Line => Vec2::new(0.0, -((slot + 1) as f32) * spacing),
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn toggle_formation(
+    mut commands: Commands,
+    query: Query<(Entity, &ActionState<PlayerActions>, Option<&InFormation>), With<Leader>>,
+) {
+    for (entity, action_state, in_formation) in query.iter() {
+        if action_state.just_pressed(PlayerActions::Formation) {
+            match in_formation {
+                Some(_) => commands.entity(entity).remove::<InFormation>(),
+                None => commands.entity(entity).insert(InFormation),
+            };
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn calculate_formation_inputs(
+    mut query: Query<
+        (Entity, &Transform, &mut BoidAveragedInputs, &BoidColor),
+        (With<Boid>, Without<Leader>),
+    >,
+    leader_query: Query<(&Transform, &BoidColor), (With<Leader>, With<InFormation>)>,
+    boid_settings: Res<BoidSettings>,
+    throttle: SteeringThrottle,
+) {
+    if leader_query.is_empty() {
+        return;
+    }
+
+    // Sort each flock by entity so every boid keeps the same slot from frame to frame.
+    let mut flocks: HashMap<BoidColor, Vec<Entity>> = HashMap::new();
+    for (entity, _, _, color) in query.iter() {
+        if leader_query.iter().any(|(_, c)| c == color) {
+            flocks.entry(*color).or_default().push(entity);
+        }
+    }
+    for flock in flocks.values_mut() {
+        flock.sort();
+    }
+
+    let spacing = boid_settings.formation_spacing;
+    for (entity, transform, mut inputs, color) in query.iter_mut() {
+        if throttle.skips(entity) {
+            continue;
+        }
+        if let (Some((leader_transform, _)), Some(flock)) = (
+            leader_query.iter().find(|(_, c)| *c == color),
+            flocks.get(color),
+        ) {
+            let slot = flock.binary_search(&entity).unwrap_or_default();
+            let offset = formation_offset(boid_settings.formation, slot, flock.len(), spacing);
+            let target = leader_transform.translation.truncate()
+                + (leader_transform.rotation * offset.extend(0.0)).truncate();
+            inputs.add_turn(direction_to_turn_towards_target(transform, target));
+
+            // Speed up if the slot is ahead of us and slow down if we passed it.
+            let ahead = transform
+                .up()
+                .truncate()
+                .dot(target - transform.translation.truncate());
+            inputs.add_speed(match ahead {
+                x if x > spacing * 0.5 => 1.0,
+                x if x < -spacing * 0.5 => -1.0,
+                _ => 0.0,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offscreen::SteeringFrame;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_circle_formation_is_evenly_spaced() {
+        let offsets: Vec<Vec2> = (0..12)
+            .map(|i| formation_offset(Formation::Circle, i, 12, 20.0))
+            .collect();
+        let radius = offsets[0].length();
+        let gap = offsets[0].distance(offsets[1]);
+        for (i, offset) in offsets.iter().enumerate() {
+            assert_relative_eq!(offset.length(), radius, max_relative = 0.001);
+            let next = offsets[(i + 1) % offsets.len()];
+            assert_relative_eq!(offset.distance(next), gap, max_relative = 0.001);
+        }
+    }
+
+    #[test]
+    fn test_v_formation_alternates_sides_behind_leader() {
+        let first = formation_offset(Formation::V, 0, 4, 10.0);
+        let second = formation_offset(Formation::V, 1, 4, 10.0);
+        assert!(first.x > 0.0 && second.x < 0.0);
+        assert!(first.y < 0.0 && second.y < 0.0);
+    }
+
+    #[test]
+    fn test_flock_turns_towards_circle_slots() {
+        let mut world = World::new();
+        world.init_resource::<SteeringFrame>();
+        world.insert_resource(BoidSettings {
+            formation: Formation::Circle,
+            ..default()
+        });
+        world.spawn((
+            Boid::default(),
+            Leader,
+            InFormation,
+            BoidColor::Red,
+            Transform::default(),
+        ));
+        // Everyone starts on the leader facing the same way, so each slot is clearly to the
+        // left or right of its boid.
+        let mut spawn_boid = |color: BoidColor| {
+            world
+                .spawn((
+                    Boid::default(),
+                    color,
+                    Transform::default(),
+                    BoidAveragedInputs::default(),
+                ))
+                .id()
+        };
+        let flock: Vec<Entity> = (0..6).map(|_| spawn_boid(BoidColor::Red)).collect();
+        let stray = spawn_boid(BoidColor::Blue);
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(calculate_formation_inputs);
+        schedule.run(&mut world);
+
+        let spacing = BoidSettings::default().formation_spacing;
+        for (slot, boid) in flock.iter().enumerate() {
+            let target = formation_offset(Formation::Circle, slot, flock.len(), spacing);
+            let turn = world
+                .get::<BoidAveragedInputs>(*boid)
+                .unwrap()
+                .turn_average();
+            // Positive turns are to the right.
+            assert_eq!(turn.signum(), target.x.signum(), "slot {slot}");
+        }
+        // Flocks whose leader isn't in formation steer as usual.
+        assert_eq!(
+            world
+                .get::<BoidAveragedInputs>(stray)
+                .unwrap()
+                .turn_average(),
+            0.0
+        );
+    }
+}
@@ -1,30 +1,50 @@
 pub mod bots;
+pub mod formation;
+pub mod scent;
 mod systems;
 
+use crate::attract::simulating;
+use crate::step::not_frozen;
 use crate::AppState;
+use bevy::ecs::schedule::SystemConfigs;
 use bevy::prelude::*;
 use systems::*;
 
 pub struct AiAppPlugin;
 
+/// The systems that add up each boid's steering inputs, in the order they run.
+pub fn steering_systems() -> SystemConfigs {
+    (
+        calculate_cohesion_inputs,
+        calculate_alignment_inputs.after(calculate_separation_inputs),
+        calculate_separation_inputs.after(calculate_cohesion_inputs),
+        calculate_wander_inputs.after(calculate_alignment_inputs),
+        formation::calculate_formation_inputs.after(calculate_wander_inputs),
+        scent::calculate_scent_inputs.after(formation::calculate_formation_inputs),
+        calculate_focus_clearance_inputs.after(scent::calculate_scent_inputs),
+    )
+        .into_configs()
+}
+
 impl Plugin for AiAppPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            (
-                calculate_cohesion_inputs,
-                calculate_alignment_inputs.after(calculate_separation_inputs),
-                calculate_separation_inputs.after(calculate_cohesion_inputs),
+        app.add_systems(steering_systems().in_base_set(CoreSet::PreUpdate))
+            .add_system(formation::toggle_formation.in_set(OnUpdate(AppState::Playing)))
+            .add_system(
+                scent::update_scent_field
+                    .run_if(not_frozen)
+                    .in_set(OnUpdate(AppState::Playing)),
             )
-                .in_base_set(CoreSet::PreUpdate),
-        )
-        .add_systems(
-            (
-                bots::speedy::update,
-                bots::coward::update,
-                bots::hunter::update,
+            .add_systems(
+                (
+                    bots::speedy::update,
+                    bots::coward::update,
+                    bots::hunter::update,
+                    bots::mimic::update,
+                )
+                    .in_base_set(CoreSet::PreUpdate)
+                    .distributive_run_if(simulating),
             )
-                .in_base_set(CoreSet::PreUpdate)
-                .distributive_run_if(in_state(AppState::Playing)),
-        );
+            .init_resource::<bots::BotDebugControls>();
     }
 }
@@ -0,0 +1,155 @@
+use crate::math::direction_to_turn_towards_target;
+use crate::offscreen::SteeringThrottle;
+use crate::{Boid, BoidAveragedInputs, BoidColor, BoidSettings, Leader};
+use bevy::prelude::*;
+
+/// How wide each square of the scent grid is. Bigger cells are cheaper but blurrier.
+pub const SCENT_CELL_SIZE: f32 = 100.0;
+
+/// A coarse grid over the arena where boids leave a fading trail of their color.
+/// Boids are drawn towards their own color's scent and away from everyone else's.
+#[derive(Resource, Debug)]
+pub struct ScentField {
+    /// The bottom left corner of the grid
+    origin: Vec2,
+    width: usize,
+    cells: Vec<[f32; BoidColor::ALL.len()]>,
+}
+
+impl ScentField {
+    /// Makes a grid big enough to cover an arena of `radius`.
+    pub fn new(radius: f32) -> Self {
+        let width = ((radius * 2.0) / SCENT_CELL_SIZE).ceil().max(1.0) as usize;
+        Self {
+            origin: Vec2::splat(-radius),
+            width,
+            cells: vec![[0.0; BoidColor::ALL.len()]; width * width],
+        }
+    }
+
+    fn cell_index(&self, position: Vec2) -> Option<usize> {
+        let cell = ((position - self.origin) / SCENT_CELL_SIZE).floor();
+        if cell.x < 0.0 || cell.y < 0.0 {
+            return None;
+        }
+        let (x, y) = (cell.x as usize, cell.y as usize);
+        match x < self.width && y < self.width {
+            true => Some(y * self.width + x),
+            false => None,
+        }
+    }
+
+    pub fn deposit(&mut self, position: Vec2, color: BoidColor, amount: f32) {
+        if let Some(i) = self.cell_index(position) {
+            self.cells[i][color.index()] += amount;
+        }
+    }
+
+    /// Fades every cell, losing `decay_per_second` of the scent each second.
+    pub fn decay(&mut self, decay_per_second: f32, delta_seconds: f32) {
+        let remaining = (1.0 - decay_per_second).max(0.0).powf(delta_seconds);
+        for cell in self.cells.iter_mut() {
+            for scent in cell.iter_mut() {
+                *scent *= remaining;
+            }
+        }
+    }
+
+    /// How much a boid of `color` wants to be at `position`, negative if it wants to avoid it.
+    pub fn attraction(&self, position: Vec2, color: BoidColor) -> f32 {
+        match self.cell_index(position) {
+            Some(i) => {
+                let ours = self.cells[i][color.index()];
+                let total: f32 = self.cells[i].iter().sum();
+                ours - (total - ours)
+            }
+            None => 0.0,
+        }
+    }
+}
+
+pub fn update_scent_field(
+    query: Query<(&Transform, &BoidColor), With<Boid>>,
+    scent_field: Option<ResMut<ScentField>>,
+    boid_settings: Res<BoidSettings>,
+    time: Res<Time>,
+) {
+    let mut scent_field = match scent_field {
+        Some(scent_field) if boid_settings.scent_deposit > 0.0 => scent_field,
+        _ => return,
+    };
+    scent_field.decay(boid_settings.scent_decay_per_second, time.delta_seconds());
+    for (transform, color) in query.iter() {
+        scent_field.deposit(
+            transform.translation.truncate(),
+            *color,
+            boid_settings.scent_deposit * time.delta_seconds(),
+        );
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn calculate_scent_inputs(
+    mut query: Query<
+        (Entity, &Transform, &mut BoidAveragedInputs, &BoidColor),
+        (With<Boid>, Without<Leader>),
+    >,
+    scent_field: Option<Res<ScentField>>,
+    boid_settings: Res<BoidSettings>,
+    throttle: SteeringThrottle,
+) {
+    let scent_field = match scent_field {
+        Some(scent_field) if boid_settings.scent_strength > 0.0 => scent_field,
+        _ => return,
+    };
+    for (entity, transform, mut inputs, color) in query.iter_mut() {
+        if throttle.skips(entity) {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        // Point towards the neighboring cells we like and away from the ones we don't.
+        let mut pull = Vec2::ZERO;
+        for x in -1..=1 {
+            for y in -1..=1 {
+                let offset = Vec2::new(x as f32, y as f32);
+                pull +=
+                    offset * scent_field.attraction(position + offset * SCENT_CELL_SIZE, *color);
+            }
+        }
+        if pull.length_squared() > 0.0 {
+            inputs.add_turn(
+                direction_to_turn_towards_target(transform, position + pull)
+                    * boid_settings.scent_strength,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scent_builds_up_and_fades() {
+        let mut scent_field = ScentField::new(500.0);
+        let position = Vec2::new(10.0, 10.0);
+        for _ in 0..10 {
+            scent_field.deposit(position + Vec2::new(5.0, -5.0), BoidColor::Red, 1.0);
+        }
+        assert_eq!(scent_field.attraction(position, BoidColor::Red), 10.0);
+        assert_eq!(scent_field.attraction(position, BoidColor::Blue), -10.0);
+
+        // The boids left, so it fades away.
+        for _ in 0..100 {
+            scent_field.decay(0.5, 0.1);
+        }
+        assert!(scent_field.attraction(position, BoidColor::Red) < 0.01);
+
+        // Off the edge of the grid is ignored.
+        scent_field.deposit(Vec2::splat(10000.0), BoidColor::Red, 1.0);
+        assert_eq!(
+            scent_field.attraction(Vec2::splat(10000.0), BoidColor::Red),
+            0.0
+        );
+    }
+}
@@ -1,26 +1,58 @@
-use crate::math::direction_to_turn_away_from_target;
+use crate::ai::formation::InFormation;
+use crate::camera::{Camera2dFollow, CameraFollowTarget};
+use crate::math::{direction_to_turn_away_from_target, smooth_noise};
+use crate::offscreen::SteeringThrottle;
 use crate::{
     how_much_right_or_left, Boid, BoidAveragedInputs, BoidColor, BoidNeighborsSeparation,
-    BoidSettings, Leader, Velocity,
+    BoidSettings, Leader, RoundSeed, Velocity,
 };
 use bevy::prelude::*;
 use bevy_prototype_debug_lines::DebugLines;
 
+/// How much faster than a boid its leader has to be, as a fraction of the max speed, for the boid
+/// to speed up at full throttle.
+const SPEED_MATCH_CATCH_UP: f32 = 1.0;
+/// How much slower than a boid its leader has to be, as a fraction of the max speed, for the
+/// boid to slow down.
+const SPEED_MATCH_SLOW_DOWN: f32 = 0.025;
+
+/// Returns the speed input for a boid to match its leader's speed.
+/// `speed_difference` is the leader's speed minus the boid's.
+pub fn speed_match_input(speed_difference: f32, max_speed: f32) -> f32 {
+    match speed_difference {
+        x if x > SPEED_MATCH_CATCH_UP * max_speed => 1.0,
+        x if x > 0.0 => 0.5,
+        x if x < -SPEED_MATCH_SLOW_DOWN * max_speed => -1.0,
+        _ => 0.0,
+    }
+}
+
 #[allow(clippy::type_complexity)]
 pub fn calculate_cohesion_inputs(
     mut query: Query<
-        (&Transform, &mut BoidAveragedInputs, &BoidColor, &Velocity),
+        (
+            Entity,
+            &Transform,
+            &mut BoidAveragedInputs,
+            &BoidColor,
+            &Velocity,
+        ),
         (With<Boid>, Without<Leader>),
     >,
-    leader_query: Query<(&Transform, &BoidColor, &Velocity), With<Leader>>,
+    // Flocks in formation steer towards their slots instead.
+    leader_query: Query<(&Transform, &BoidColor, &Velocity), (With<Leader>, Without<InFormation>)>,
     mut lines: ResMut<DebugLines>,
     boid_settings: Res<BoidSettings>,
+    throttle: SteeringThrottle,
 ) {
     if !boid_settings.cohesion_enabled {
         return;
     }
     // Turn and move towards the leader's position if they have one.
-    for (transform, mut inputs, color, velocity) in query.iter_mut() {
+    for (entity, transform, mut inputs, color, velocity) in query.iter_mut() {
+        if throttle.skips(entity) {
+            continue;
+        }
         if let Some((leader_transform, _, leader_velocity)) =
             leader_query.iter().find(|(_, c, _)| *c == color)
         {
@@ -33,12 +65,6 @@ pub fn calculate_cohesion_inputs(
                 transform,
                 transform.translation.truncate() + direction_to_target,
             );
-            let speed_up_down = match leader_velocity.forward - velocity.forward {
-                x if x > 120.0 => 1.0,
-                x if x > 0.0 => 0.5,
-                x if x < -3.0 => -1.0,
-                _ => 0.0,
-            };
             if boid_settings.debug_lines {
                 lines.line_gradient(
                     transform.translation,
@@ -49,8 +75,15 @@ pub fn calculate_cohesion_inputs(
                 );
             }
 
-            inputs.add_turn(turn_towards_leader_direction);
-            inputs.add_speed(speed_up_down);
+            if boid_settings.cohesion_turn {
+                inputs.add_turn(boid_settings.gained_turn(turn_towards_leader_direction));
+            }
+            if boid_settings.cohesion_speed_match {
+                inputs.add_speed(speed_match_input(
+                    leader_velocity.forward - velocity.forward,
+                    boid_settings.max_speed,
+                ));
+            }
         }
     }
 }
@@ -59,6 +92,7 @@ pub fn calculate_cohesion_inputs(
 pub fn calculate_separation_inputs(
     mut query: Query<
         (
+            Entity,
             &Transform,
             &BoidNeighborsSeparation,
             &mut BoidAveragedInputs,
@@ -68,52 +102,136 @@ pub fn calculate_separation_inputs(
     transforms: Query<&Transform>,
     mut lines: ResMut<DebugLines>,
     boid_settings: Res<BoidSettings>,
+    throttle: SteeringThrottle,
 ) {
     if !boid_settings.separation_enabled {
         return;
     }
-    for (transform, neighbors, mut inputs) in query.iter_mut() {
-        transforms
-            .iter_many(&neighbors.entities)
-            .for_each(|target| {
-                let direction =
-                    (direction_to_turn_away_from_target(transform, target.translation.truncate())
-                        * 2.0)
-                        .clamp(-1.0, 1.0);
-                // Turn away from neighbors within separation distance
-                inputs.add_turn(direction);
-
-                // Draw a line from the current entity to the target that is affecting the separation
-                // Fades out farther from the current entity so it's easy to tell if both
-                // entities are being affected.
-                if boid_settings.debug_lines {
-                    lines.line_gradient(
-                        transform.translation,
-                        ((target.translation - transform.translation) * 0.5)
-                            + transform.translation,
-                        0.0,
-                        Color::rgba(1.0, 0.0, 0.0, (direction + 1.0) / 2.0),
-                        Color::rgba(1.0, 0.0, 0.0, 0.2),
-                    );
-                }
-            });
+    for (entity, transform, neighbors, mut inputs) in query.iter_mut() {
+        if throttle.skips(entity) {
+            continue;
+        }
+        // Only the closest neighbors count so crowded boids don't over-steer.
+        let mut targets: Vec<&Transform> = transforms.iter_many(&neighbors.entities).collect();
+        targets.sort_by(|a, b| {
+            a.translation
+                .distance_squared(transform.translation)
+                .total_cmp(&b.translation.distance_squared(transform.translation))
+        });
+        targets.truncate(boid_settings.max_separation_neighbors);
+        for target in targets {
+            let direction = boid_settings.gained_turn(
+                direction_to_turn_away_from_target(transform, target.translation.truncate()) * 2.0,
+            );
+            // Turn away from neighbors within separation distance
+            inputs.add_turn(direction);
+
+            // Draw a line from the current entity to the target that is affecting the separation
+            // Fades out farther from the current entity so it's easy to tell if both
+            // entities are being affected.
+            if boid_settings.debug_lines {
+                lines.line_gradient(
+                    transform.translation,
+                    ((target.translation - transform.translation) * 0.5) + transform.translation,
+                    0.0,
+                    Color::rgba(1.0, 0.0, 0.0, (direction + 1.0) / 2.0),
+                    Color::rgba(1.0, 0.0, 0.0, 0.2),
+                );
+            }
+        }
+    }
+}
+
+/// Adds a bit of random turning so flocks circling a still leader don't look frozen.
+/// The round's seed shifts the curves so the same seed wanders the same way.
+#[allow(clippy::type_complexity)]
+pub fn calculate_wander_inputs(
+    mut query: Query<(Entity, &mut BoidAveragedInputs), (With<Boid>, Without<Leader>)>,
+    time: Res<Time>,
+    boid_settings: Res<BoidSettings>,
+    round_seed: Res<RoundSeed>,
+    throttle: SteeringThrottle,
+) {
+    if boid_settings.wander_amplitude <= 0.0 {
+        return;
+    }
+    // Kept small so the noise doesn't lose precision.
+    let phase = (round_seed.0 % 1024) as f32;
+    for (entity, mut inputs) in query.iter_mut() {
+        if throttle.skips(entity) {
+            continue;
+        }
+        inputs.add_turn(
+            smooth_noise(time.elapsed_seconds(), entity.index() as f32 + phase)
+                * boid_settings.wander_amplitude,
+        );
+    }
+}
+
+/// Returns how hard a boid `distance` away from a followed leader turns away from it, fading out
+/// towards the edge of the clearance radius so it stays subtle.
+pub fn focus_clearance_weight(distance: f32, radius: f32) -> f32 {
+    match radius > 0.0 {
+        true => (1.0 - distance / radius).clamp(0.0, 1.0),
+        false => 0.0,
+    }
+}
+
+/// Makes room around the leaders the cameras are following so they stay clearly in frame.
+#[allow(clippy::type_complexity)]
+pub fn calculate_focus_clearance_inputs(
+    mut query: Query<(Entity, &Transform, &mut BoidAveragedInputs), (With<Boid>, Without<Leader>)>,
+    leaders: Query<(Entity, &Transform, Option<&CameraFollowTarget>), With<Leader>>,
+    cameras: Query<&Camera2dFollow>,
+    boid_settings: Res<BoidSettings>,
+    throttle: SteeringThrottle,
+) {
+    let radius = boid_settings.focus_clearance_radius;
+    if radius <= 0.0 {
+        return;
+    }
+    let followed: Vec<Vec2> = leaders
+        .iter()
+        .filter(|(entity, _, follow_target)| {
+            follow_target.is_some() || cameras.iter().any(|camera| camera.target == *entity)
+        })
+        .map(|(_, transform, _)| transform.translation.truncate())
+        .collect();
+    if followed.is_empty() {
+        return;
+    }
+    for (entity, transform, mut inputs) in query.iter_mut() {
+        if throttle.skips(entity) {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        for leader in followed.iter() {
+            let weight = focus_clearance_weight(position.distance(*leader), radius);
+            if weight > 0.0 {
+                inputs.add_turn(direction_to_turn_away_from_target(transform, *leader) * weight);
+            }
+        }
     }
 }
 
 #[allow(clippy::type_complexity)]
 pub fn calculate_alignment_inputs(
     mut query: Query<
-        (&Transform, &mut BoidAveragedInputs, &BoidColor),
+        (Entity, &Transform, &mut BoidAveragedInputs, &BoidColor),
         (With<Boid>, Without<Leader>),
     >,
     leader_query: Query<(&Transform, &BoidColor), With<Leader>>,
     mut lines: ResMut<DebugLines>,
     boid_settings: Res<BoidSettings>,
+    throttle: SteeringThrottle,
 ) {
     if !boid_settings.alignment_enabled {
         return;
     }
-    for (transform, mut inputs, color) in query.iter_mut() {
+    for (entity, transform, mut inputs, color) in query.iter_mut() {
+        if throttle.skips(entity) {
+            continue;
+        }
         if let Some((leader_transform, _)) = leader_query.iter().find(|(_, c)| *c == color) {
             let average = leader_transform.up().truncate();
             if boid_settings.debug_lines {
@@ -124,10 +242,163 @@ pub fn calculate_alignment_inputs(
                     Color::VIOLET,
                 );
             }
-            inputs.add_turn(how_much_right_or_left(
+            inputs.add_turn(boid_settings.gained_turn(how_much_right_or_left(
                 &Transform::from_rotation(transform.rotation),
                 average,
-            ));
+            )));
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offscreen::SteeringFrame;
+
+    #[test]
+    fn test_speed_match_scales_with_max_speed() {
+        assert_eq!(speed_match_input(150.0, 120.0), 1.0);
+        assert_eq!(speed_match_input(150.0, 300.0), 0.5);
+        assert_eq!(speed_match_input(-5.0, 120.0), -1.0);
+        assert_eq!(speed_match_input(-5.0, 300.0), 0.0);
+    }
+
+    #[test]
+    fn test_turn_gain_sharpens_flocking_turns() {
+        // The leader is a little to the right of straight ahead.
+        let turn = how_much_right_or_left(&Transform::IDENTITY, Vec2::new(0.2, 1.0));
+        let gentle = BoidSettings::default();
+        let sharp = BoidSettings {
+            turn_gain: 3.0,
+            ..default()
+        };
+        let twitchy = BoidSettings {
+            turn_gain: 10.0,
+            ..default()
+        };
+        assert_eq!(gentle.gained_turn(turn), turn);
+        assert!(sharp.gained_turn(turn) > gentle.gained_turn(turn));
+        assert_eq!(twitchy.gained_turn(turn), 1.0);
+        assert_eq!(twitchy.gained_turn(-turn), -1.0);
+    }
+
+    #[test]
+    fn test_cohesion_without_speed_match_only_turns() {
+        let mut world = World::new();
+        world.init_resource::<DebugLines>();
+        world.init_resource::<SteeringFrame>();
+        world.insert_resource(BoidSettings {
+            cohesion_speed_match: false,
+            ..default()
+        });
+        world.spawn((
+            Boid::default(),
+            Leader,
+            BoidColor::Red,
+            Transform::from_xyz(100.0, 0.0, 0.0),
+            Velocity {
+                forward: 500.0,
+                ..default()
+            },
+        ));
+        let boid = world
+            .spawn((
+                Boid::default(),
+                BoidColor::Red,
+                Transform::default(),
+                BoidAveragedInputs::default(),
+                Velocity::default(),
+            ))
+            .id();
+        let mut schedule = Schedule::new();
+        schedule.add_system(calculate_cohesion_inputs);
+        schedule.run(&mut world);
+
+        let inputs = world.get::<BoidAveragedInputs>(boid).unwrap();
+        assert_ne!(inputs.turn_average(), 0.0);
+        assert_eq!(inputs.speed_average(), 0.0);
+    }
+
+    #[test]
+    fn test_boids_make_room_around_followed_leader() {
+        let focus_turns = |radius: f32| {
+            let mut world = World::new();
+            world.init_resource::<SteeringFrame>();
+            world.insert_resource(BoidSettings {
+                focus_clearance_radius: radius,
+                ..default()
+            });
+            world.spawn((Leader, CameraFollowTarget, Transform::default()));
+            let mut boid = |x: f32| {
+                world
+                    .spawn((
+                        Boid::default(),
+                        Transform::from_xyz(x, 0.0, 0.0),
+                        BoidAveragedInputs::default(),
+                    ))
+                    .id()
+            };
+            let (near, far) = (boid(30.0), boid(500.0));
+            let mut schedule = Schedule::new();
+            schedule.add_system(calculate_focus_clearance_inputs);
+            schedule.run(&mut world);
+            let turn = |entity| {
+                world
+                    .get::<BoidAveragedInputs>(entity)
+                    .unwrap()
+                    .turn_average()
+            };
+            (turn(near), turn(far))
+        };
+
+        // Facing up with the leader on its left, the near boid turns right, away from it.
+        let (near, far) = focus_turns(100.0);
+        assert!(near > 0.0);
+        assert_eq!(far, 0.0);
+        assert_eq!(focus_turns(0.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_separation_only_uses_closest_neighbors() {
+        let separation_turn = |neighbors: &[Vec3]| {
+            let mut world = World::new();
+            world.init_resource::<DebugLines>();
+            world.init_resource::<SteeringFrame>();
+            world.insert_resource(BoidSettings {
+                max_separation_neighbors: 3,
+                ..default()
+            });
+            let entities = neighbors
+                .iter()
+                .map(|position| world.spawn(Transform::from_translation(*position)).id())
+                .collect();
+            let boid = world
+                .spawn((
+                    Boid::default(),
+                    Transform::default(),
+                    BoidNeighborsSeparation { entities },
+                    BoidAveragedInputs::default(),
+                ))
+                .id();
+            let mut schedule = Schedule::new();
+            schedule.add_system(calculate_separation_inputs);
+            schedule.run(&mut world);
+            world
+                .get::<BoidAveragedInputs>(boid)
+                .unwrap()
+                .turn_average()
+        };
+
+        // Three close neighbors ahead on the left and seven farther ones ahead on the right.
+        let closest = [
+            Vec3::new(-2.0, 3.0, 0.0),
+            Vec3::new(-3.0, 3.0, 0.0),
+            Vec3::new(-2.0, 4.0, 0.0),
+        ];
+        let mut neighbors = closest.to_vec();
+        neighbors.extend((0..7).map(|i| Vec3::new(8.0 + i as f32, 6.0, 0.0)));
+
+        assert_eq!(separation_turn(&neighbors), separation_turn(&closest));
+        assert_ne!(separation_turn(&neighbors[3..]), separation_turn(&closest));
+    }
+}
@@ -0,0 +1,306 @@
+use crate::ai::bots::Bot;
+use crate::boids::{separate_leaders, spawn_positions, BoidSettings};
+use crate::camera::{Camera2dFollowMany, CameraFollowTarget};
+use crate::step::StepMode;
+use crate::ui::Logo;
+use crate::{
+    spawn_boid_facing, AppState, BoidColor, GameEnded, Leader, RoundSeed, RoundSettings,
+    ARENA_PADDING, LOGO_OFFSET, LOGO_SCALE, SCENE_HEIGHT,
+};
+use bevy::input::mouse::MouseMotion;
+use bevy::prelude::*;
+use bevy::render::camera::ScalingMode;
+use std::f32::consts::TAU;
+use turborand::prelude::*;
+
+/// How long the title screen has to sit without any input before the demo starts.
+const ATTRACT_IDLE_SECONDS: f32 = 30.0;
+const ATTRACT_BOID_COUNT: usize = 200;
+const ATTRACT_BOTS: [Bot; 4] = [Bot::BoneHead, Bot::ScaredyCat, Bot::Speedy, Bot::Hunter];
+
+/// Plays an all bot match behind the title menu when nobody has touched anything for a while.
+#[derive(Resource, Debug, Default)]
+pub struct AttractMode {
+    idle_seconds: f32,
+    pub active: bool,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AttractChange {
+    Start,
+    Stop,
+}
+
+impl AttractMode {
+    /// Counts up the idle time and returns whether the demo should start or stop.
+    pub fn update(&mut self, delta_seconds: f32, had_input: bool) -> Option<AttractChange> {
+        if had_input {
+            self.idle_seconds = 0.0;
+            return match self.active {
+                true => {
+                    self.active = false;
+                    Some(AttractChange::Stop)
+                }
+                false => None,
+            };
+        }
+        self.idle_seconds += delta_seconds;
+        match !self.active && self.idle_seconds >= ATTRACT_IDLE_SECONDS {
+            true => {
+                self.active = true;
+                Some(AttractChange::Start)
+            }
+            false => None,
+        }
+    }
+}
+
+/// Everything spawned for the demo is a child of this so it can be cleaned up in one go.
+#[derive(Component, Debug)]
+pub struct AttractRoot;
+
+/// The camera that shows the title screen, it follows the demo's leaders while it plays.
+#[derive(Component, Debug)]
+pub struct TitleCamera;
+
+/// Run condition for the systems that move boids around, true in a round or during the demo.
+/// In step mode they only run on the frames that were stepped to.
+pub fn simulating(
+    state: Res<State<AppState>>,
+    attract_mode: Res<AttractMode>,
+    step_mode: Res<StepMode>,
+) -> bool {
+    let running = state.0 == AppState::Playing || attract_mode.active;
+    match step_mode.enabled {
+        true => step_mode.step_requested && (running || state.0 == AppState::Paused),
+        false => running,
+    }
+}
+
+/// Spawns an all bot flock using the round's arena and returns the root entity.
+pub fn spawn_attract_demo(
+    commands: &mut Commands,
+    texture: Handle<Image>,
+    round_settings: &RoundSettings,
+    boid_settings: &BoidSettings,
+    rand: &impl TurboRand,
+) -> Entity {
+    let root = commands
+        .spawn((
+            AttractRoot,
+            SpatialBundle::default(),
+            Name::new("Attract Mode"),
+        ))
+        .id();
+    let colors = BoidColor::spread(ATTRACT_BOTS.len());
+    let mut positions = spawn_positions(
+        ATTRACT_BOID_COUNT,
+        round_settings.arena_radius - ARENA_PADDING,
+        round_settings.spawn_min_distance,
+        rand,
+    );
+    separate_leaders(
+        &mut positions,
+        ATTRACT_BOTS.len(),
+        round_settings.arena_radius - ARENA_PADDING,
+        round_settings.min_leader_separation,
+        boid_settings.scaled_capture_range(round_settings.arena_radius),
+        rand,
+    );
+    for (i, position) in positions.into_iter().enumerate() {
+        let entity = spawn_boid_facing(commands, texture.clone(), position, rand.f32() * TAU);
+        if let (Some(bot), Some(color)) = (ATTRACT_BOTS.get(i), colors.get(i)) {
+            let mut entity_commands = commands.entity(entity);
+            entity_commands.insert((*color, Leader, CameraFollowTarget));
+            bot.insert_with_jitter(&mut entity_commands, round_settings.bot_jitter, rand);
+        }
+        commands.entity(root).add_child(entity);
+    }
+    root
+}
+
+/// Removes the demo and puts the title camera back where the title screen expects it.
+pub fn despawn_attract_demo(
+    commands: &mut Commands,
+    roots: &Query<Entity, With<AttractRoot>>,
+    cameras: &mut Query<(Entity, &mut Transform, &mut OrthographicProjection), With<TitleCamera>>,
+) {
+    for root in roots.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+    for (entity, mut transform, mut projection) in cameras.iter_mut() {
+        commands.entity(entity).remove::<Camera2dFollowMany>();
+        transform.translation = Vec3::new(0.0, 0.0, transform.translation.z);
+        projection.scaling_mode = ScalingMode::FixedVertical(SCENE_HEIGHT);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+pub fn update_attract_mode(
+    mut commands: Commands,
+    mut attract_mode: ResMut<AttractMode>,
+    mut game_ended: ResMut<GameEnded>,
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    asset_server: Res<AssetServer>,
+    round_settings: Res<RoundSettings>,
+    boid_settings: Res<BoidSettings>,
+    roots: Query<Entity, With<AttractRoot>>,
+    mut cameras: Query<(Entity, &mut Transform, &mut OrthographicProjection), With<TitleCamera>>,
+) {
+    let had_input = keys.get_just_pressed().next().is_some()
+        || mouse_buttons.get_just_pressed().next().is_some()
+        || gamepad_buttons.get_just_pressed().next().is_some()
+        || mouse_motion.iter().count() > 0;
+    let change = match attract_mode.update(time.delta_seconds(), had_input) {
+        // Start the next demo when the bots have finished one.
+        None if attract_mode.active && game_ended.0 => {
+            despawn_attract_demo(&mut commands, &roots, &mut cameras);
+            Some(AttractChange::Start)
+        }
+        change => change,
+    };
+    match change {
+        Some(AttractChange::Start) => {
+            info!("Starting attract mode");
+            *game_ended = GameEnded::default();
+            let seed = Rng::new().gen_u64();
+            commands.insert_resource(RoundSeed(seed));
+            spawn_attract_demo(
+                &mut commands,
+                asset_server.load("bird.png"),
+                &round_settings,
+                &boid_settings,
+                &Rng::with_seed(seed),
+            );
+            for (entity, ..) in cameras.iter() {
+                commands
+                    .entity(entity)
+                    .insert(Camera2dFollowMany::default());
+            }
+        }
+        Some(AttractChange::Stop) => {
+            info!("Stopping attract mode");
+            despawn_attract_demo(&mut commands, &roots, &mut cameras);
+        }
+        None => {}
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn stop_attract_mode(
+    mut commands: Commands,
+    mut attract_mode: ResMut<AttractMode>,
+    roots: Query<Entity, With<AttractRoot>>,
+    mut cameras: Query<(Entity, &mut Transform, &mut OrthographicProjection), With<TitleCamera>>,
+) {
+    *attract_mode = AttractMode::default();
+    despawn_attract_demo(&mut commands, &roots, &mut cameras);
+}
+
+/// Moves the logo along with the title camera so it stays in the same spot on screen.
+#[allow(clippy::type_complexity)]
+pub fn keep_logo_in_view(
+    attract_mode: Res<AttractMode>,
+    cameras: Query<(&Transform, &OrthographicProjection), (With<TitleCamera>, Without<Logo>)>,
+    mut logos: Query<&mut Transform, With<Logo>>,
+) {
+    let (camera_transform, projection) = match cameras.get_single() {
+        Ok(camera) => camera,
+        Err(_) => return,
+    };
+    let zoom = match (attract_mode.active, projection.scaling_mode.clone()) {
+        (true, ScalingMode::FixedVertical(height)) => height / SCENE_HEIGHT,
+        _ => 1.0,
+    };
+    let center = match attract_mode.active {
+        true => camera_transform.translation.truncate(),
+        false => Vec2::ZERO,
+    };
+    for mut transform in logos.iter_mut() {
+        transform.translation = (center + LOGO_OFFSET * zoom).extend(transform.translation.z);
+        transform.scale = Vec3::splat(LOGO_SCALE * zoom);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Boid;
+    use bevy::ecs::system::SystemState;
+
+    #[test]
+    fn test_idle_starts_and_input_stops_attract_mode() {
+        let mut attract_mode = AttractMode::default();
+        assert_eq!(attract_mode.update(ATTRACT_IDLE_SECONDS * 0.5, false), None);
+        // Input before the demo starts only resets the idle time.
+        assert_eq!(attract_mode.update(0.1, true), None);
+        assert_eq!(attract_mode.update(ATTRACT_IDLE_SECONDS * 0.5, false), None);
+        assert_eq!(
+            attract_mode.update(ATTRACT_IDLE_SECONDS * 0.5, false),
+            Some(AttractChange::Start)
+        );
+        assert!(attract_mode.active);
+        assert_eq!(attract_mode.update(0.1, false), None);
+        assert_eq!(attract_mode.update(0.1, true), Some(AttractChange::Stop));
+        assert!(!attract_mode.active);
+    }
+
+    #[test]
+    #[allow(clippy::type_complexity)]
+    fn test_attract_demo_spawns_and_tears_down() {
+        let mut world = World::new();
+        let camera = world
+            .spawn((
+                TitleCamera,
+                Camera2dFollowMany::default(),
+                Transform::from_xyz(300.0, -200.0, 999.0),
+                OrthographicProjection {
+                    scaling_mode: ScalingMode::FixedVertical(SCENE_HEIGHT * 3.0),
+                    ..default()
+                },
+            ))
+            .id();
+
+        let mut system_state: SystemState<Commands> = SystemState::new(&mut world);
+        let mut commands = system_state.get_mut(&mut world);
+        spawn_attract_demo(
+            &mut commands,
+            Handle::default(),
+            &RoundSettings::default(),
+            &BoidSettings::default(),
+            &Rng::with_seed(7),
+        );
+        system_state.apply(&mut world);
+
+        assert_eq!(
+            world.query::<&Boid>().iter(&world).count(),
+            ATTRACT_BOID_COUNT
+        );
+        let leaders: Vec<&Bot> = world
+            .query_filtered::<&Bot, With<Leader>>()
+            .iter(&world)
+            .collect();
+        assert_eq!(leaders.len(), ATTRACT_BOTS.len());
+
+        let mut system_state: SystemState<(
+            Commands,
+            Query<Entity, With<AttractRoot>>,
+            Query<(Entity, &mut Transform, &mut OrthographicProjection), With<TitleCamera>>,
+        )> = SystemState::new(&mut world);
+        let (mut commands, roots, mut cameras) = system_state.get_mut(&mut world);
+        despawn_attract_demo(&mut commands, &roots, &mut cameras);
+        system_state.apply(&mut world);
+
+        assert_eq!(world.query::<&Boid>().iter(&world).count(), 0);
+        assert!(world.get::<Camera2dFollowMany>(camera).is_none());
+        assert_eq!(
+            world.get::<Transform>(camera).unwrap().translation,
+            Vec3::new(0.0, 0.0, 999.0)
+        );
+    }
+}
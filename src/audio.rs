@@ -0,0 +1,89 @@
+use crate::stats::BoidCensus;
+use crate::GameEvent;
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use std::path::Path;
+
+/// Played when a leader is captured. The game stays quiet if it's missing.
+const CAPTURE_SOUND: &str = "sounds/capture.ogg";
+/// The playback speed of the capture sound for the smallest and biggest flocks, lower is deeper.
+const CAPTURE_SPEED_RANGE: (f32, f32) = (1.3, 0.7);
+/// The volume of the capture sound for the smallest and biggest flocks
+const CAPTURE_VOLUME_RANGE: (f32, f32) = (0.5, 1.0);
+/// Flocks this big or bigger get the deepest, loudest capture sound.
+const BIG_FLOCK_SIZE: f32 = 200.0;
+
+#[derive(Resource, Debug)]
+pub struct CaptureSound(pub Handle<AudioSource>);
+
+/// Returns how to play the capture sound when a flock of `flock_size` captures a leader, so
+/// bigger flocks sound deeper and punchier.
+pub fn capture_playback(flock_size: usize) -> PlaybackSettings {
+    let t = (flock_size as f32 / BIG_FLOCK_SIZE).clamp(0.0, 1.0);
+    let lerp = |(min, max): (f32, f32)| min + (max - min) * t;
+    PlaybackSettings {
+        repeat: false,
+        volume: lerp(CAPTURE_VOLUME_RANGE),
+        speed: lerp(CAPTURE_SPEED_RANGE),
+    }
+}
+
+/// Only loads the sounds that are there, loading a missing file logs an error every launch.
+pub fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
+    if asset_server.asset_io().is_file(Path::new(CAPTURE_SOUND)) {
+        commands.insert_resource(CaptureSound(asset_server.load(CAPTURE_SOUND)));
+    }
+}
+
+pub fn play_capture_sounds(
+    mut events: EventReader<GameEvent>,
+    census: Res<BoidCensus>,
+    audio: Res<Audio>,
+    capture_sound: Option<Res<CaptureSound>>,
+    asset_server: Res<AssetServer>,
+) {
+    let capture_sound = match capture_sound {
+        Some(capture_sound) => capture_sound,
+        None => return,
+    };
+    if asset_server.get_load_state(&capture_sound.0) != LoadState::Loaded {
+        return;
+    }
+    for event in events.iter() {
+        if let GameEvent::LeaderCaptured(_, captured_by) = event {
+            audio.play_with_settings(
+                capture_sound.0.clone(),
+                capture_playback(census.count(captured_by)),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_sound_isnt_loaded() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_startup_system(load_sounds);
+        app.update();
+        assert_eq!(
+            app.world.contains_resource::<CaptureSound>(),
+            Path::new("assets").join(CAPTURE_SOUND).exists()
+        );
+    }
+
+    #[test]
+    fn test_bigger_flocks_sound_deeper() {
+        let small = capture_playback(5);
+        let big = capture_playback(150);
+        assert!(big.speed < small.speed);
+        assert!(big.volume > small.volume);
+        for playback in [small, big, capture_playback(0), capture_playback(5000)] {
+            assert!((CAPTURE_SPEED_RANGE.1..=CAPTURE_SPEED_RANGE.0).contains(&playback.speed));
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::BoidColor;
+use bevy::prelude::*;
+use bevy::sprite::Mesh2dHandle;
+use std::f32::consts::PI;
+
+/// How big the badges are in the leader's local space, the leader sprite is 752 by 984.
+const BADGE_RADIUS: f32 = 200.0;
+
+/// A shape drawn on each leader so teams can be told apart without relying on color.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BadgeShape {
+    Circle,
+    Triangle,
+    Square,
+    Diamond,
+    Pentagon,
+    Hexagon,
+    InvertedTriangle,
+    Octagon,
+}
+
+impl BadgeShape {
+    pub fn for_color(color: BoidColor) -> Self {
+        match color {
+            BoidColor::Red => Self::Circle,
+            BoidColor::Green => Self::Triangle,
+            BoidColor::Blue => Self::Square,
+            BoidColor::Yellow => Self::Diamond,
+            BoidColor::Purple => Self::Pentagon,
+            BoidColor::Orange => Self::Hexagon,
+            BoidColor::Pink => Self::InvertedTriangle,
+            BoidColor::Cyan => Self::Octagon,
+        }
+    }
+
+    pub fn mesh(&self) -> Mesh {
+        match self {
+            BadgeShape::Circle => shape::Circle::new(BADGE_RADIUS).into(),
+            BadgeShape::Triangle | BadgeShape::InvertedTriangle => {
+                shape::RegularPolygon::new(BADGE_RADIUS, 3).into()
+            }
+            BadgeShape::Square | BadgeShape::Diamond => {
+                shape::RegularPolygon::new(BADGE_RADIUS, 4).into()
+            }
+            BadgeShape::Pentagon => shape::RegularPolygon::new(BADGE_RADIUS, 5).into(),
+            BadgeShape::Hexagon => shape::RegularPolygon::new(BADGE_RADIUS, 6).into(),
+            BadgeShape::Octagon => shape::RegularPolygon::new(BADGE_RADIUS, 8).into(),
+        }
+    }
+
+    /// Some shapes reuse the same mesh turned a different way.
+    pub fn rotation(&self) -> Quat {
+        match self {
+            BadgeShape::Square => Quat::from_rotation_z(PI / 4.0),
+            BadgeShape::InvertedTriangle => Quat::from_rotation_z(PI),
+            _ => Quat::IDENTITY,
+        }
+    }
+}
+
+/// Added as a child of leaders, the mesh is added by `add_badge_meshes`.
+#[derive(Component, Debug)]
+pub struct TeamBadge(pub BadgeShape);
+
+/// Spawns the badge for a leader of `color`.
+pub fn spawn_team_badge(commands: &mut Commands, leader: Entity, color: BoidColor) {
+    let shape = BadgeShape::for_color(color);
+    let badge = commands
+        .spawn(SpatialBundle {
+            // Just in front of the leader's sprite
+            transform: Transform::from_xyz(0.0, 0.0, 10.0).with_rotation(shape.rotation()),
+            ..default()
+        })
+        .insert(TeamBadge(shape))
+        .insert(Name::new("Team Badge"))
+        .id();
+    commands.entity(leader).add_child(badge);
+}
+
+pub fn add_badge_meshes(
+    mut commands: Commands,
+    query: Query<(Entity, &TeamBadge), Added<TeamBadge>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (entity, badge) in query.iter() {
+        commands.entity(entity).insert((
+            Mesh2dHandle(meshes.add(badge.0.mesh())),
+            materials.add(ColorMaterial::from(Color::WHITE)),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boids::{leader_added, leader_removed, DeathAnim};
+    use crate::Leader;
+
+    #[test]
+    fn test_leaders_get_badges_that_are_removed_on_capture() {
+        let mut world = World::new();
+        let mut schedule = Schedule::new();
+        schedule.add_systems((leader_added, leader_removed));
+
+        let leader = world
+            .spawn((Leader, BoidColor::Blue, Transform::default()))
+            .id();
+        schedule.run(&mut world);
+        let badge = world.get::<Children>(leader).unwrap()[0];
+        assert_eq!(world.get::<TeamBadge>(badge).unwrap().0, BadgeShape::Square);
+
+        world.entity_mut(leader).remove::<Leader>();
+        schedule.run(&mut world);
+        // The badge shrinks away instead of disappearing at once.
+        assert!(world.get::<TeamBadge>(badge).is_none());
+        assert!(world.get::<DeathAnim>(badge).is_some());
+    }
+}
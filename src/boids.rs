@@ -1,6 +1,15 @@
+use crate::accessibility::AccessibilitySettings;
+use crate::ai::bots::Bot;
+use crate::ai::formation::Formation;
+use crate::attract::AttractMode;
+use crate::badges::{spawn_team_badge, TeamBadge};
+use crate::math::how_much_right_or_left;
+use crate::offscreen::SteeringThrottle;
 use crate::quadtree::{Bounds, QuadTree};
+use crate::round::{ring_points, DEFAULT_ARENA_RADIUS};
+use crate::streaks::CaptureStreaks;
 use crate::{
-    AppState, PlayerActions, RoundSettings, Winner, ARENA_PADDING, BOID_SCALE, LEADER_SCALE,
+    AppState, PlayerActions, RoundSettings, Winner, BOID_SCALE, BOID_Z, LEADER_SCALE, LEADER_Z,
 };
 use bevy::prelude::*;
 use bevy_inspector_egui::InspectorOptions;
@@ -10,16 +19,43 @@ use leafwing_input_manager::action_state::ActionData;
 use leafwing_input_manager::axislike::DualAxisData;
 use leafwing_input_manager::orientation::{Orientation, Rotation};
 use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
 use std::collections::HashMap;
-use std::f32::consts::FRAC_PI_2;
+use std::f32::consts::{FRAC_PI_2, TAU};
 use std::mem;
+use turborand::prelude::TurboRand;
 
 const MAX_BOIDS_PER_NODE: usize = 10;
+/// The most boids to draw neighbor links for so it stays fast with lots of boids
+const MAX_NEIGHBOR_LINK_BOIDS: usize = 20;
+const SEPARATION_LINK_COLOR: Color = Color::rgba(1.0, 0.27, 0.0, 0.4);
+const CAPTURE_LINK_COLOR: Color = Color::rgba(0.2, 0.8, 0.2, 0.4);
+/// How many neighbors of neighbors deep the capture search goes
+const CAPTURE_SEARCH_DEPTH: usize = 10;
+const CONTESTED_OUTLINE_RADIUS: f32 = 10.0;
+const CONTESTED_OUTLINE_SEGMENTS: usize = 8;
+const CONTESTED_PULSES_PER_SECOND: f32 = 2.0;
+/// How long it takes a boid to grow to full size when the round starts
+const SPAWN_IN_SECONDS: f32 = 0.3;
+/// How long things take to shrink away before they're despawned
+const DEATH_ANIM_SECONDS: f32 = 0.4;
+/// How far ahead the path preview looks
+const PATH_PREVIEW_SECONDS: f32 = 0.5;
+const PATH_PREVIEW_STEPS: usize = 10;
+const PATH_PREVIEW_ALPHA: f32 = 0.4;
+/// How much of its capture range a leader keeps with none of its flock around it
+const LONE_LEADER_CAPTURE_SCALE: f32 = 0.25;
 
-#[derive(Reflect, Debug, Resource, InspectorOptions)]
+#[derive(Reflect, Debug, Clone, PartialEq, Resource, InspectorOptions, Serialize, Deserialize)]
 #[reflect(Resource)]
+#[serde(default)]
 pub struct BoidSettings {
     pub cohesion_enabled: bool,
+    /// Boids turn towards their leader as part of cohesion
+    pub cohesion_turn: bool,
+    /// Boids speed up or slow down to match their leader as part of cohesion
+    pub cohesion_speed_match: bool,
     pub separation_enabled: bool,
     pub alignment_enabled: bool,
     /// The maximum speed the boid is allowed to go in units per second
@@ -28,6 +64,12 @@ pub struct BoidSettings {
     /// The minimum speed the boid is allowed to go in units per second
     #[inspector(min = 0.0, max = 9999.0)]
     pub min_speed: f32,
+    /// Lets leaders slow down below the min speed with negative throttle or the brake button.
+    /// Off keeps the always moving feel.
+    pub brake_enabled: bool,
+    /// The slowest a braking leader can go in units per second
+    #[inspector(min = 0.0, max = 9999.0)]
+    pub brake_min_speed: f32,
     /// The amount the boid's speed changes by in units per second
     #[inspector(min = 0.0, max = 9999.0)]
     pub acceleration: f32,
@@ -36,32 +78,226 @@ pub struct BoidSettings {
     pub drag: f32,
     #[inspector(min = 0.0, max = 3600.0)]
     pub max_turn_rate_per_second: f32,
+    /// Multiplies the turn rate of leaders only, higher makes them more maneuverable than
+    /// their flock.
+    #[inspector(min = 0.0, max = 10.0)]
+    pub leader_turn_rate_multiplier: f32,
+    /// Multiplies how hard boids turn to follow, line up with and avoid each other, higher makes
+    /// flocks react to their leader faster.
+    #[inspector(min = 0.0, max = 10.0)]
+    pub turn_gain: f32,
     #[inspector(min = 0.0, max = 1000.0)]
     pub separation_distance: f32,
+    /// Only the closest this many neighbors within the separation distance are turned away from,
+    /// so boids in dense crowds don't steer erratically.
+    #[inspector(min = 1, max = 100)]
+    pub max_separation_neighbors: usize,
     #[inspector(min = 0.0, max = 1000.0)]
     pub capture_range: f32,
+    /// Boids are only converted by a color that has one of its boids this close, the rest of the
+    /// flock in capture range still counts. Has no effect above the capture range.
+    #[inspector(min = 0.0, max = 1000.0)]
+    pub recruitment_range: f32,
+    /// Grows and shrinks the separation distance, capture range and leader capture radius along
+    /// with the arena so big and small arenas play the same. Off uses them as they are.
+    pub scale_ranges_with_arena: bool,
+    /// Boids can only be captured by a color within this distance of that color's leader, so
+    /// flocks that stray too far from their leader stop capturing. 0 is off.
+    #[inspector(min = 0.0, max = 10000.0)]
+    pub leader_capture_radius: f32,
+    /// Colorless boids need more than one neighbor of a color to join it, like colored boids do.
+    /// Off lets a single boid recruit them straight away.
+    pub strict_neutral_capture: bool,
+    /// How long a leader can't be captured after a player takes it over, so they aren't
+    /// captured before they get their bearings.
+    #[inspector(min = 0.0, max = 60.0)]
+    pub respawn_invulnerability_seconds: f32,
     #[inspector(min = 0.0, max = 1000.0)]
     pub vision_range: f32,
+    /// Tilts boid sprites into their turns. Purely cosmetic.
+    pub banking_enabled: bool,
+    /// The most a boid sprite will tilt into a turn in degrees
+    #[inspector(min = 0.0, max = 90.0)]
+    pub max_bank_angle: f32,
+    /// Pushes boids apart when they are closer than the separation distance instead of only
+    /// turning them away from each other.
+    pub separation_push_enabled: bool,
+    /// The fastest two overlapping boids will be pushed apart in units per second
+    #[inspector(min = 0.0, max = 9999.0)]
+    pub separation_push_speed: f32,
+    /// Stops counting a color's connected boids once it reaches this many when deciding captures.
+    /// Counts above the cap are approximate, which only matters when two huge flocks meet.
+    #[inspector(min = 1)]
+    pub max_captures_evaluated: usize,
+    /// How fast boids drift towards the center of the arena in units per second. 0 is off.
+    #[inspector(min = 0.0, max = 9999.0)]
+    pub gravity_well_strength: f32,
+    /// How much boids randomly drift side to side so still flocks don't look frozen. 0 is off.
+    #[inspector(min = 0.0, max = 1.0)]
+    pub wander_amplitude: f32,
+    /// Boids within this distance of a leader a camera is following gently steer away from it so
+    /// it isn't buried in the crowd. For screenshots and videos, keep it off for competitive
+    /// play. 0 is off.
+    #[inspector(min = 0.0, max = 1000.0)]
+    pub focus_clearance_radius: f32,
+    /// How many frames to wait between finding each boid's neighbors. Higher values are faster
+    /// with lots of boids but the boids react to each other a little later.
+    #[inspector(min = 1, max = 60)]
+    pub neighbor_update_interval: u32,
+    /// How much of last frame's turn and speed is kept each frame, so boids steer smoothly
+    /// instead of snapping to sudden inputs. 0 is off.
+    #[inspector(min = 0.0, max = 0.99)]
+    pub input_smoothing: f32,
+    /// How many frames boids that none of the players' own cameras can see wait between steering.
+    /// They still move and capture every frame. 1 steers everyone every frame.
+    #[inspector(min = 1, max = 60)]
+    pub offscreen_steering_interval: u32,
+    /// The shape flocks hold when their leader turns on formation mode
+    pub formation: Formation,
+    /// How far apart boids are in a formation
+    #[inspector(min = 0.0, max = 1000.0)]
+    pub formation_spacing: f32,
+    /// The most pieces a fast boid's movement is split into each frame so it doesn't skip past
+    /// boids it should have hit. 1 turns substepping off, it's off by default because sweeping
+    /// for neighbors along the path makes the capture range longer for fast boids.
+    #[inspector(min = 1, max = 16)]
+    pub max_substeps: u32,
+    /// How much scent each boid leaves behind per second. 0 turns scent off.
+    #[inspector(min = 0.0, max = 100.0)]
+    pub scent_deposit: f32,
+    /// How much of the scent fades away each second from 0.0 to 1.0
+    #[inspector(min = 0.0, max = 1.0)]
+    pub scent_decay_per_second: f32,
+    /// How strongly boids follow their own scent and avoid others'
+    #[inspector(min = 0.0, max = 1.0)]
+    pub scent_strength: f32,
+    /// Draws a translucent shape around each flock to show the area it covers. Purely cosmetic.
+    pub territory_enabled: bool,
+    /// How many frames to wait between redrawing the territories
+    #[inspector(min = 1, max = 60)]
+    pub territory_update_interval: u32,
     pub debug_lines: bool,
+    /// Draws lines from some of the boids to the neighbors they found
+    pub show_neighbor_links: bool,
+    /// Outlines boids where the two strongest colors around them are within this many boids of
+    /// each other, showing where flocks are fighting over them. 0 is off.
+    #[inspector(min = 0, max = 100)]
+    pub contested_margin: usize,
+    /// Draws the links between the boids that count towards capturing for the boid clicked on
+    /// while the inspector is open.
+    pub show_capture_cluster: bool,
 }
 
 impl Default for BoidSettings {
     fn default() -> Self {
         Self {
             cohesion_enabled: true,
+            cohesion_turn: true,
+            cohesion_speed_match: true,
             separation_enabled: true,
             alignment_enabled: true,
             max_speed: 120.0,
             min_speed: 60.0,
+            brake_enabled: false,
+            brake_min_speed: 20.0,
             acceleration: 300.0,
             drag: 100.0,
             max_turn_rate_per_second: 520.0,
+            leader_turn_rate_multiplier: 1.0,
+            turn_gain: 1.0,
             separation_distance: 15.0,
+            max_separation_neighbors: 6,
             capture_range: 20.0,
+            recruitment_range: 20.0,
+            scale_ranges_with_arena: false,
+            leader_capture_radius: 0.0,
+            strict_neutral_capture: false,
+            respawn_invulnerability_seconds: 2.0,
             vision_range: 500.0,
+            banking_enabled: false,
+            max_bank_angle: 15.0,
+            separation_push_enabled: false,
+            separation_push_speed: 40.0,
+            max_captures_evaluated: 100,
+            gravity_well_strength: 0.0,
+            wander_amplitude: 0.0,
+            focus_clearance_radius: 0.0,
+            neighbor_update_interval: 1,
+            input_smoothing: 0.0,
+            offscreen_steering_interval: 1,
+            formation: Formation::default(),
+            formation_spacing: 20.0,
+            max_substeps: 1,
+            scent_deposit: 0.0,
+            scent_decay_per_second: 0.5,
+            scent_strength: 0.3,
+            territory_enabled: false,
+            territory_update_interval: 10,
             debug_lines: false,
+            show_neighbor_links: false,
+            contested_margin: 0,
+            show_capture_cluster: false,
+        }
+    }
+}
+
+impl BoidSettings {
+    /// How much the distance settings are multiplied by in an arena of `arena_radius`.
+    pub fn range_scale(&self, arena_radius: f32) -> f32 {
+        match self.scale_ranges_with_arena {
+            true => arena_radius / DEFAULT_ARENA_RADIUS,
+            false => 1.0,
+        }
+    }
+
+    pub fn scaled_capture_range(&self, arena_radius: f32) -> f32 {
+        self.capture_range * self.range_scale(arena_radius)
+    }
+
+    pub fn scaled_recruitment_range(&self, arena_radius: f32) -> f32 {
+        self.recruitment_range * self.range_scale(arena_radius)
+    }
+
+    pub fn scaled_separation_distance(&self, arena_radius: f32) -> f32 {
+        self.separation_distance * self.range_scale(arena_radius)
+    }
+
+    pub fn scaled_leader_capture_radius(&self, arena_radius: f32) -> f32 {
+        self.leader_capture_radius * self.range_scale(arena_radius)
+    }
+
+    /// The most a boid can turn in degrees per second
+    pub fn turn_rate(&self, is_leader: bool) -> f32 {
+        match is_leader {
+            true => self.max_turn_rate_per_second * self.leader_turn_rate_multiplier,
+            false => self.max_turn_rate_per_second,
         }
     }
+
+    /// Scales a flocking turn input by the turn gain, keeping it between -1.0 and 1.0.
+    pub fn gained_turn(&self, turn: f32) -> f32 {
+        (turn * self.turn_gain).clamp(-1.0, 1.0)
+    }
+
+    /// Sets the speed and handling settings from a single 0.0 to 1.0 "agility" value, where
+    /// higher is faster and more responsive. 0.5 gives the defaults.
+    pub fn set_agility(&mut self, agility: f32) {
+        let agility = agility.clamp(0.0, 1.0);
+        let lerp = |min: f32, max: f32| min + (max - min) * agility;
+        self.acceleration = lerp(150.0, 450.0);
+        self.drag = lerp(60.0, 140.0);
+        self.max_turn_rate_per_second = lerp(320.0, 720.0);
+        self.max_speed = lerp(90.0, 150.0);
+    }
+
+    /// Describes any settings that don't make sense together, like a min speed above the max.
+    pub fn warnings(&self) -> Vec<&'static str> {
+        let mut warnings = Vec::new();
+        if self.min_speed > self.max_speed {
+            warnings.push("min_speed is higher than max_speed, boids will always go max_speed");
+        }
+        warnings
+    }
 }
 
 #[derive(Component, Default)]
@@ -71,12 +307,14 @@ pub struct Boid {}
 #[reflect(Component)]
 pub struct Velocity {
     pub forward: f32,
+    /// How far the boid moved last frame
+    pub travelled: Vec2,
 }
 
 #[derive(Component, Default, Reflect)]
 #[reflect(Component)]
 pub struct BoidNeighborsCaptureRange {
-    entities: Vec<Entity>,
+    pub entities: Vec<Entity>,
 }
 
 #[derive(Component, Default, Reflect)]
@@ -95,6 +333,9 @@ pub struct BoidAveragedInputs {
     turn_count: u32,
     speed_average: f32,
     speed_count: u32,
+    /// The turn and speed that were actually applied last frame, kept across resets for smoothing
+    applied_turn: f32,
+    applied_speed: f32,
 }
 
 impl BoidAveragedInputs {
@@ -128,6 +369,28 @@ impl BoidAveragedInputs {
         self.speed_average
     }
 
+    /// Blends this frame's averages into the ones applied last frame and returns the result.
+    /// `smoothing` is how much of last frame's input is kept, 0 applies the averages as they are.
+    pub fn smoothed(&mut self, smoothing: f32) -> (f32, f32) {
+        let smoothing = smoothing.clamp(0.0, 1.0);
+        self.applied_turn = self.applied_turn * smoothing + self.turn_average() * (1.0 - smoothing);
+        self.applied_speed =
+            self.applied_speed * smoothing + self.speed_average() * (1.0 - smoothing);
+        (self.applied_turn, self.applied_speed)
+    }
+
+    /// The turn that was applied last frame after smoothing
+    pub fn applied_turn(&self) -> f32 {
+        self.applied_turn
+    }
+
+    /// Records a turn that was made without the inputs, like turning back into the arena, so the
+    /// banking and smoothing carry on from it.
+    pub fn set_applied_turn(&mut self, turn: f32) {
+        self.applied_turn = turn.clamp(-1.0, 1.0);
+    }
+
+    /// Clears the inputs collected this frame, the applied inputs are kept for smoothing.
     pub fn reset(&mut self) {
         self.turn_average = 0.0;
         self.speed_average = 0.0;
@@ -136,6 +399,105 @@ impl BoidAveragedInputs {
     }
 }
 
+/// Returns true if `frame` is one that the neighbors should be updated on.
+pub fn is_neighbor_update_frame(frame: u32, interval: u32) -> bool {
+    frame.is_multiple_of(interval.max(1))
+}
+
+/// Run condition for rebuilding the quad tree every `neighbor_update_interval` frames.
+pub fn neighbor_update_due(mut frame: Local<u32>, boid_settings: Res<BoidSettings>) -> bool {
+    let due = is_neighbor_update_frame(*frame, boid_settings.neighbor_update_interval);
+    *frame = frame.wrapping_add(1);
+    due
+}
+
+/// How many random spots to try for each boid before giving up on keeping it spaced out
+const SPAWN_ATTEMPTS: usize = 30;
+
+/// Picks `count` random spawn points inside a circle of `radius`, keeping them at least
+/// `min_distance` apart. If there isn't room for them all, the rest are placed anywhere.
+pub fn spawn_positions(
+    count: usize,
+    radius: f32,
+    min_distance: f32,
+    rand: &impl TurboRand,
+) -> Vec<Vec2> {
+    let random_position = || {
+        let r = radius * rand.f32();
+        let theta = rand.f32() * TAU;
+        Vec2::new(r * theta.cos(), r * theta.sin())
+    };
+    let mut tree = QuadTree::<(), MAX_BOIDS_PER_NODE>::new(Bounds {
+        x_min: -radius,
+        x_max: radius,
+        y_min: -radius,
+        y_max: radius,
+    });
+    let mut out_of_room = false;
+    (0..count)
+        .map(|_| {
+            let position = (0..SPAWN_ATTEMPTS)
+                .map(|_| random_position())
+                .find(|p| min_distance <= 0.0 || tree.query_distance(*p, min_distance).is_empty())
+                .unwrap_or_else(|| {
+                    if !out_of_room {
+                        out_of_room = true;
+                        warn!("Not enough room to spawn all boids {min_distance} apart");
+                    }
+                    random_position()
+                });
+            tree.insert(position, ());
+            position
+        })
+        .collect()
+}
+
+/// Moves the first `leader_count` positions to random spots inside a circle of `radius` until
+/// every leader is at least `min_separation` from the others and from the boids the others will
+/// pick up at the start (the regular boids within `flock_range` of them), so nobody starts next
+/// to someone they could capture straight away. Leaders that are already far enough apart stay put.
+pub fn separate_leaders(
+    positions: &mut [Vec2],
+    leader_count: usize,
+    radius: f32,
+    min_separation: f32,
+    flock_range: f32,
+    rand: &impl TurboRand,
+) {
+    if min_separation <= 0.0 {
+        return;
+    }
+    let leader_count = leader_count.min(positions.len());
+    let (leaders, boids) = positions.split_at_mut(leader_count);
+    let mut taken: Vec<Vec2> = Vec::new();
+    for leader in leaders.iter_mut() {
+        let is_clear = |p: Vec2| {
+            taken
+                .iter()
+                .all(|other| other.distance(p) >= min_separation)
+        };
+        if !is_clear(*leader) {
+            let found = (0..SPAWN_ATTEMPTS * 10)
+                .map(|_| {
+                    let r = radius * rand.f32();
+                    let theta = rand.f32() * TAU;
+                    Vec2::new(r * theta.cos(), r * theta.sin())
+                })
+                .find(|p| is_clear(*p));
+            match found {
+                Some(position) => *leader = position,
+                None => warn!("Not enough room to spawn all leaders {min_separation} apart"),
+            }
+        }
+        taken.push(*leader);
+        taken.extend(
+            boids
+                .iter()
+                .filter(|boid| boid.distance(*leader) <= flock_range),
+        );
+    }
+}
+
 #[derive(Resource)]
 pub struct BoidTree {
     tree: QuadTree<Entity, MAX_BOIDS_PER_NODE>,
@@ -143,6 +505,64 @@ pub struct BoidTree {
 
 #[derive(Component, Debug)]
 pub struct Leader;
+
+/// Added to boids that are close to being captured by more than one color.
+#[derive(Component, Debug)]
+pub struct Contested;
+
+/// Grows a boid from nothing to its full size at the start of a round.
+#[derive(Component, Debug)]
+pub struct SpawnIn {
+    pub timer: Timer,
+}
+
+impl SpawnIn {
+    /// `delay` is how long to wait before starting to grow, used to stagger the boids.
+    pub fn new(delay: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(delay + SPAWN_IN_SECONDS, TimerMode::Once),
+        }
+    }
+
+    /// How far through growing the boid is from 0.0 to 1.0
+    pub fn progress(&self) -> f32 {
+        (1.0 - (self.timer.remaining_secs() / SPAWN_IN_SECONDS)).clamp(0.0, 1.0)
+    }
+}
+
+/// Leaders with this can't be captured until the timer runs out, they flash until then.
+#[derive(Component, Debug)]
+pub struct Invulnerable {
+    pub timer: Timer,
+}
+
+impl Invulnerable {
+    pub fn new(seconds: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+        }
+    }
+}
+
+/// Shrinks an entity away and then despawns it.
+/// Only add this to things that no longer count for anything in the round, like the badge of a
+/// captured leader, so the round doesn't wait on the animation.
+#[derive(Component, Debug)]
+pub struct DeathAnim {
+    pub timer: Timer,
+    /// The scale to shrink down from
+    pub scale: Vec3,
+}
+
+impl DeathAnim {
+    pub fn new(scale: Vec3) -> Self {
+        Self {
+            timer: Timer::from_seconds(DEATH_ANIM_SECONDS, TimerMode::Once),
+            scale,
+        }
+    }
+}
+
 pub fn update_quad_tree(
     mut commands: Commands,
     query: Query<(Entity, &Transform), With<Boid>>,
@@ -160,47 +580,251 @@ pub fn update_quad_tree(
     commands.insert_resource(BoidTree { tree });
 }
 
+/// Returns how many steps to split a move of `distance` into so no step is longer than
+/// `step_length`, up to `max_substeps`.
+pub fn substep_count(distance: f32, step_length: f32, max_substeps: u32) -> u32 {
+    if step_length <= 0.0 {
+        return 1;
+    }
+    ((distance / step_length).ceil() as u32).clamp(1, max_substeps.max(1))
+}
+
+/// Finds everything within `distance` of the path a boid took to get to `position` last frame
+/// so fast boids still find the neighbors they passed by.
+pub fn query_swept<const N: usize>(
+    tree: &QuadTree<Entity, N>,
+    position: Vec2,
+    travelled: Vec2,
+    distance: f32,
+    max_substeps: u32,
+) -> Vec<Entity> {
+    let steps = substep_count(travelled.length(), distance, max_substeps);
+    if steps <= 1 {
+        return tree
+            .query_distance(position, distance)
+            .into_iter()
+            .map(|(_, e)| *e)
+            .collect();
+    }
+    let start = position - travelled;
+    let mut results: Vec<Entity> = (0..=steps)
+        .flat_map(|step| {
+            tree.query_distance(start + travelled * (step as f32 / steps as f32), distance)
+        })
+        .map(|(_, e)| *e)
+        .collect();
+    results.sort();
+    results.dedup();
+    results
+}
+
+/// Returns a leader's capture range when it has `escort` of its own boids within range, shrinking
+/// towards `LONE_LEADER_CAPTURE_SCALE` of the full range as its flock leaves it.
+pub fn leader_capture_range(capture_range: f32, escort: usize, escort_size: f32) -> f32 {
+    if escort_size <= 0.0 {
+        return capture_range;
+    }
+    let escorted = (escort as f32 / escort_size).min(1.0);
+    capture_range * (LONE_LEADER_CAPTURE_SCALE + (1.0 - LONE_LEADER_CAPTURE_SCALE) * escorted)
+}
+
 #[allow(clippy::type_complexity)]
 pub fn update_boid_neighbors(
     mut neighbors_components: Query<(
         Entity,
         &Transform,
+        Option<&Velocity>,
         &mut BoidNeighborsCaptureRange,
         &mut BoidNeighborsSeparation,
     )>,
     boid_settings: Res<BoidSettings>,
+    round_settings: Res<RoundSettings>,
     boid_tree: Option<Res<BoidTree>>,
 ) {
+    let capture_range = boid_settings.scaled_capture_range(round_settings.arena_radius);
+    let separation_distance = boid_settings.scaled_separation_distance(round_settings.arena_radius);
     if let Some(quad_tree) = &boid_tree {
-        for (entity, transform, mut capture_neighbors, mut separation_neighbors) in
+        for (entity, transform, velocity, mut capture_neighbors, mut separation_neighbors) in
             neighbors_components.iter_mut()
         {
             let quad_tree = &quad_tree.tree;
-            let c = quad_tree
-                .query_distance(
-                    transform.translation.truncate(),
-                    boid_settings.capture_range,
-                )
-                .into_iter()
-                .map(|(_, e)| *e)
-                .filter(|e| *e != entity)
-                .collect();
-            let s = quad_tree
-                .query_distance(
-                    transform.translation.truncate(),
-                    boid_settings.separation_distance,
-                )
-                .into_iter()
-                .map(|(_, e)| *e)
-                .filter(|e| *e != entity)
-                .collect();
+            let travelled = velocity.map(|v| v.travelled).unwrap_or_default();
+            let c = query_swept(
+                quad_tree,
+                transform.translation.truncate(),
+                travelled,
+                capture_range,
+                boid_settings.max_substeps,
+            )
+            .into_iter()
+            .filter(|e| *e != entity)
+            .collect();
+            let s = query_swept(
+                quad_tree,
+                transform.translation.truncate(),
+                travelled,
+                separation_distance,
+                boid_settings.max_substeps,
+            )
+            .into_iter()
+            .filter(|e| *e != entity)
+            .collect();
             capture_neighbors.entities = c;
             separation_neighbors.entities = s;
         }
     }
 }
 
-#[derive(Component, Eq, PartialEq, Copy, Clone, Debug, Hash, Reflect, FromReflect)]
+/// Returns which neighbors to draw a link to and what color, separation neighbors first.
+pub fn neighbor_links(
+    separation: &BoidNeighborsSeparation,
+    capture: &BoidNeighborsCaptureRange,
+) -> Vec<(Entity, Color)> {
+    separation
+        .entities
+        .iter()
+        .map(|e| (*e, SEPARATION_LINK_COLOR))
+        .chain(capture.entities.iter().map(|e| (*e, CAPTURE_LINK_COLOR)))
+        .collect()
+}
+
+/// Predicts the path of a boid that keeps going at `speed` while turning by `turn` from -1.0
+/// (left) to 1.0 (right) for `seconds`. Returns `steps` points along the way after the start.
+pub fn project_path(
+    transform: &Transform,
+    speed: f32,
+    turn: f32,
+    turn_rate: f32,
+    seconds: f32,
+    steps: usize,
+) -> Vec<Vec2> {
+    let delta_seconds = seconds / steps.max(1) as f32;
+    let mut transform = *transform;
+    (0..steps)
+        .map(|_| {
+            transform.rotate_z(-turn * turn_rate.to_radians() * delta_seconds);
+            transform.translation += transform.up() * speed * delta_seconds;
+            transform.translation.truncate()
+        })
+        .collect()
+}
+
+/// Draws a faint line ahead of each player's leader showing where their input is taking them.
+#[allow(clippy::type_complexity)]
+pub fn draw_path_previews(
+    query: Query<
+        (&Transform, &Velocity, &ActionState<PlayerActions>),
+        (With<Leader>, Without<Bot>),
+    >,
+    mut lines: ResMut<DebugLines>,
+    boid_settings: Res<BoidSettings>,
+    accessibility: Res<AccessibilitySettings>,
+) {
+    if !accessibility.path_preview {
+        return;
+    }
+    for (transform, velocity, action_state) in query.iter() {
+        let turn = match action_state.clamped_axis_pair(PlayerActions::Direction) {
+            Some(axis_data) if axis_data.length_squared() > 0.01 => {
+                how_much_right_or_left(transform, transform.translation.truncate() + axis_data.xy())
+            }
+            _ => action_state
+                .clamped_axis_pair(PlayerActions::Rotate)
+                .map(|axis_data| axis_data.x())
+                .unwrap_or_default(),
+        };
+        let path = project_path(
+            transform,
+            velocity.forward,
+            turn,
+            boid_settings.turn_rate(true),
+            PATH_PREVIEW_SECONDS,
+            PATH_PREVIEW_STEPS,
+        );
+        // Fade out towards the end of the path.
+        let color = |i: usize| {
+            Color::rgba(
+                1.0,
+                1.0,
+                1.0,
+                PATH_PREVIEW_ALPHA * (1.0 - i as f32 / PATH_PREVIEW_STEPS as f32),
+            )
+        };
+        let mut start = transform.translation;
+        for (i, point) in path.into_iter().enumerate() {
+            let end = point.extend(transform.translation.z);
+            lines.line_gradient(start, end, 0.0, color(i), color(i + 1));
+            start = end;
+        }
+    }
+}
+
+/// Draws what the neighbor detection found for the first few boids, the leaders are spawned
+/// first so they're always included.
+pub fn draw_neighbor_links(
+    query: Query<(
+        Entity,
+        &Transform,
+        &BoidNeighborsSeparation,
+        &BoidNeighborsCaptureRange,
+    )>,
+    transforms: Query<&Transform, With<Boid>>,
+    mut lines: ResMut<DebugLines>,
+    boid_settings: Res<BoidSettings>,
+) {
+    if !boid_settings.show_neighbor_links {
+        return;
+    }
+    let sample = query
+        .iter()
+        .sorted_by_key(|(entity, ..)| *entity)
+        .take(MAX_NEIGHBOR_LINK_BOIDS);
+    for (_, transform, separation, capture) in sample {
+        for (neighbor, color) in neighbor_links(separation, capture) {
+            if let Ok(neighbor_transform) = transforms.get(neighbor) {
+                lines.line_colored(
+                    transform.translation,
+                    neighbor_transform.translation,
+                    0.0,
+                    color,
+                );
+            }
+        }
+    }
+}
+
+/// Draws a pulsing outline around contested boids to show the front lines between flocks.
+pub fn draw_contested_boids(
+    query: Query<&Transform, With<Contested>>,
+    mut lines: ResMut<DebugLines>,
+    boid_settings: Res<BoidSettings>,
+    accessibility: Res<AccessibilitySettings>,
+    time: Res<Time>,
+) {
+    if boid_settings.contested_margin == 0 {
+        return;
+    }
+    let alpha = match accessibility.reduce_motion {
+        true => 0.8,
+        false => 0.6 + 0.4 * (time.elapsed_seconds() * CONTESTED_PULSES_PER_SECOND * TAU).sin(),
+    };
+    let color = Color::rgba(1.0, 1.0, 1.0, alpha);
+    let outline = ring_points(CONTESTED_OUTLINE_RADIUS, CONTESTED_OUTLINE_SEGMENTS);
+    for transform in query.iter() {
+        for segment in outline.windows(2) {
+            lines.line_colored(
+                transform.translation + segment[0].extend(0.0),
+                transform.translation + segment[1].extend(0.0),
+                0.0,
+                color,
+            );
+        }
+    }
+}
+
+#[derive(
+    Component, Eq, PartialEq, Copy, Clone, Debug, Hash, Reflect, FromReflect, Serialize, Deserialize,
+)]
 pub enum BoidColor {
     Red,
     Green,
@@ -213,17 +837,17 @@ pub enum BoidColor {
 }
 
 impl BoidColor {
-    pub fn from_index(i: usize) -> Option<Self> {
-        match i {
-            0 => Some(Self::Red),
-            1 => Some(Self::Green),
-            2 => Some(Self::Blue),
-            3 => Some(Self::Yellow),
-            4 => Some(Self::Purple),
-            5 => Some(Self::Orange),
-            6 => Some(Self::Pink),
-            7 => Some(Self::Cyan),
-            _ => None,
+    /// The position of this color in `BoidColor::ALL`
+    pub fn index(&self) -> usize {
+        match self {
+            BoidColor::Red => 0,
+            BoidColor::Green => 1,
+            BoidColor::Blue => 2,
+            BoidColor::Yellow => 3,
+            BoidColor::Purple => 4,
+            BoidColor::Orange => 5,
+            BoidColor::Pink => 6,
+            BoidColor::Cyan => 7,
         }
     }
 
@@ -250,15 +874,93 @@ impl BoidColor {
             BoidColor::Cyan => Color::CYAN,
         }
     }
+
+    /// The hue of the color in degrees
+    pub fn hue(&self) -> f32 {
+        match self.color().as_hsla() {
+            Color::Hsla { hue, .. } => hue,
+            _ => 0.0,
+        }
+    }
+
+    /// How far apart two colors are on the color wheel in degrees
+    pub fn hue_distance(&self, other: &Self) -> f32 {
+        let difference = (self.hue() - other.hue()).abs() % 360.0;
+        difference.min(360.0 - difference)
+    }
+
+    /// Picks `count` colors that are as far apart in hue as possible so they're easy to tell
+    /// apart. Always gives the same colors for the same count.
+    pub fn spread(count: usize) -> Vec<Self> {
+        let mut best: Option<(f32, Vec<Self>)> = None;
+        for colors in Self::ALL
+            .iter()
+            .copied()
+            .combinations(count.min(Self::ALL.len()))
+        {
+            let closest = colors
+                .iter()
+                .tuple_combinations()
+                .map(|(a, b)| a.hue_distance(b))
+                .fold(f32::MAX, f32::min);
+            if best.as_ref().map(|(d, _)| closest > *d).unwrap_or(true) {
+                best = Some((closest, colors));
+            }
+        }
+        best.map(|(_, colors)| colors).unwrap_or_default()
+    }
+
+    /// Picks an unused color that is easy to tell apart from the colors already in `used`.
+    pub fn next_distinct(used: &[Self]) -> Option<Self> {
+        // Stick to the best spread for one more player if the others haven't been changed.
+        let spread = Self::spread(used.len() + 1);
+        if used.iter().all(|c| spread.contains(c)) {
+            return spread.into_iter().find(|c| !used.contains(c));
+        }
+        let mut best: Option<(f32, Self)> = None;
+        for color in Self::ALL.iter().filter(|c| !used.contains(c)) {
+            let closest = used
+                .iter()
+                .map(|c| c.hue_distance(color))
+                .fold(f32::MAX, f32::min);
+            if best.map(|(d, _)| closest > d).unwrap_or(true) {
+                best = Some((closest, *color));
+            }
+        }
+        best.map(|(_, color)| color)
+    }
+
+    /// The name of the color as it should be shown to players
+    pub fn name(&self) -> &'static str {
+        match self {
+            BoidColor::Red => "Red",
+            BoidColor::Green => "Green",
+            BoidColor::Blue => "Blue",
+            BoidColor::Yellow => "Yellow",
+            BoidColor::Purple => "Purple",
+            BoidColor::Orange => "Orange",
+            BoidColor::Pink => "Pink",
+            BoidColor::Cyan => "Cyan",
+        }
+    }
+}
+
+impl std::fmt::Display for BoidColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn update_boid_transforms(
     mut boid_query: Query<
         (
             &mut Transform,
             &mut ActionState<PlayerActions>,
-            &BoidAveragedInputs,
+            &mut BoidAveragedInputs,
             &mut Velocity,
+            Option<&BoidColor>,
+            Option<&Leader>,
         ),
         With<Boid>,
     >,
@@ -266,9 +968,13 @@ pub fn update_boid_transforms(
     mut lines: ResMut<DebugLines>,
     boid_settings: Res<BoidSettings>,
     round_settings: Res<RoundSettings>,
+    streaks: Res<CaptureStreaks>,
 ) {
-    let active_arena_radius_squared = (round_settings.arena_radius - ARENA_PADDING).powf(2.);
-    for (mut transform, mut action_state, inputs, mut velocity) in boid_query.iter_mut() {
+    let active_arena_radius_squared = round_settings.turn_back_radius().powf(2.);
+    for (mut transform, mut action_state, mut inputs, mut velocity, color, leader) in
+        boid_query.iter_mut()
+    {
+        let turn_rate = boid_settings.turn_rate(leader.is_some());
         if boid_settings.debug_lines {
             lines.line_colored(
                 transform.translation,
@@ -286,28 +992,39 @@ pub fn update_boid_transforms(
         if direction.length_squared() > active_arena_radius_squared {
             let angle = direction.y.atan2(direction.x) - FRAC_PI_2;
 
+            let up = transform.up().truncate();
             transform.rotation.rotate_towards(
                 Quat::from_axis_angle(Vec3::Z, angle),
                 Some(Rotation::from_radians(FRAC_PI_2 * time.delta_seconds())),
             );
+            // Counter-clockwise is a negative turn input.
+            let max_turn = turn_rate.to_radians() * time.delta_seconds();
+            if max_turn > 0.0 {
+                let turned = up.angle_between(transform.up().truncate());
+                inputs.set_applied_turn(-turned / max_turn);
+            }
         } else {
+            let (turn, speed) = inputs.smoothed(boid_settings.input_smoothing);
             add_axis_input(
                 &mut action_state,
                 PlayerActions::Rotate,
-                DualAxisData::new(inputs.turn_average(), 0.0),
+                DualAxisData::new(turn, 0.0),
             );
             add_axis_input(
                 &mut action_state,
                 PlayerActions::Throttle,
-                DualAxisData::new(0.0, inputs.speed_average()),
+                DualAxisData::new(0.0, speed),
             );
+            if action_state.pressed(PlayerActions::Brake) {
+                add_axis_input(
+                    &mut action_state,
+                    PlayerActions::Throttle,
+                    DualAxisData::new(0.0, -1.0),
+                );
+            }
 
             if let Some(axis_data) = action_state.clamped_axis_pair(PlayerActions::Rotate) {
-                transform.rotate_z(
-                    -axis_data.x()
-                        * boid_settings.max_turn_rate_per_second.to_radians()
-                        * time.delta_seconds(),
-                );
+                transform.rotate_z(-axis_data.x() * turn_rate.to_radians() * time.delta_seconds());
             }
 
             if let Some(axis_data) = action_state.clamped_axis_pair(PlayerActions::Throttle) {
@@ -320,9 +1037,7 @@ pub fn update_boid_transforms(
                 if axis_data.length_squared() > 0.01 {
                     transform.rotation.rotate_towards(
                         Quat::from_rotation_z((-axis_data.x()).atan2(axis_data.y())),
-                        Some(Rotation::from_degrees(
-                            boid_settings.max_turn_rate_per_second * time.delta_seconds(),
-                        )),
+                        Some(Rotation::from_degrees(turn_rate * time.delta_seconds())),
                     );
                 }
             }
@@ -332,20 +1047,164 @@ pub fn update_boid_transforms(
             velocity.forward += boid_settings.acceleration;
         }
 
+        let max_speed = boid_settings.max_speed * streaks.speed_multiplier(color, &round_settings);
+        // Followers slow down with negative throttle too, only leaders get to brake past min speed.
+        let min_speed = match boid_settings.brake_enabled && leader.is_some() && acceleration < 0.0
+        {
+            true => boid_settings.brake_min_speed,
+            false => boid_settings.min_speed,
+        };
         velocity.forward += (acceleration - boid_settings.drag) * time.delta_seconds();
         velocity.forward = velocity.forward.clamp(
             // clamp requires that min <= to max, adding the extra min here so it
             // doesn't panic if max_speed is set to lower than min_speed via the inspector.
-            boid_settings.min_speed.min(boid_settings.max_speed),
-            boid_settings.max_speed,
+            min_speed.min(max_speed),
+            max_speed,
+        );
+        // Split big moves up so the gravity well pull is applied along the way.
+        let start = transform.translation.truncate();
+        let substeps = substep_count(
+            velocity.forward * time.delta_seconds(),
+            boid_settings.scaled_capture_range(round_settings.arena_radius),
+            boid_settings.max_substeps,
         );
-        transform.translation += forward * time.delta_seconds() * velocity.forward;
+        let substep_seconds = time.delta_seconds() / substeps as f32;
+        for _ in 0..substeps {
+            transform.translation += forward * substep_seconds * velocity.forward;
+            let pull = gravity_well_pull(
+                transform.translation.truncate(),
+                boid_settings.gravity_well_strength,
+            );
+            transform.translation += (pull * substep_seconds).extend(0.0);
+        }
+        velocity.travelled = transform.translation.truncate() - start;
+    }
+}
+
+/// Returns the velocity a boid at `position` drifts towards the center of the arena.
+pub fn gravity_well_pull(position: Vec2, strength: f32) -> Vec2 {
+    -position.normalize_or_zero() * strength
+}
+
+/// Returns the extra rotation in radians to draw a boid with when it is turning.
+/// Positive turn inputs turn right, so they bank clockwise.
+pub fn bank_angle(turn_input: f32, max_bank_angle: f32) -> f32 {
+    -turn_input.clamp(-1.0, 1.0) * max_bank_angle
+}
+
+/// Rotates the boid sprites into their turns after transforms have been propagated so the
+/// extra rotation is only used for rendering and never feeds back into the movement.
+#[allow(clippy::type_complexity)]
+pub fn bank_boid_sprites(
+    mut query: Query<(&mut GlobalTransform, &BoidAveragedInputs), (With<Boid>, Without<SpawnIn>)>,
+    boid_settings: Res<BoidSettings>,
+    accessibility: Res<AccessibilitySettings>,
+) {
+    if !boid_settings.banking_enabled || accessibility.reduce_motion {
+        return;
+    }
+    for (mut global_transform, inputs) in query.iter_mut() {
+        let mut transform = global_transform.compute_transform();
+        transform.rotate_z(bank_angle(
+            inputs.applied_turn(),
+            boid_settings.max_bank_angle.to_radians(),
+        ));
+        *global_transform = transform.into();
+    }
+}
+
+/// Returns the velocity a boid should be pushed away from a neighbor that is within the
+/// separation distance. The more they overlap the harder the push, up to `max_push_speed`.
+///
+/// `fallback` is the direction to push if both boids are in the exact same spot.
+pub fn separation_push(
+    position: Vec2,
+    neighbor: Vec2,
+    separation_distance: f32,
+    max_push_speed: f32,
+    fallback: Vec2,
+) -> Vec2 {
+    let offset = position - neighbor;
+    let distance = offset.length();
+    if separation_distance <= 0.0 || distance >= separation_distance {
+        return Vec2::ZERO;
     }
+    let overlap = 1.0 - (distance / separation_distance);
+    offset.try_normalize().unwrap_or(fallback) * overlap * max_push_speed
 }
 
-pub fn clear_inputs(mut query: Query<(&mut BoidAveragedInputs, &mut ActionState<PlayerActions>)>) {
-    for (mut inputs, mut action_state) in query.iter_mut() {
-        inputs.reset();
+/// Physically pushes apart boids that are too close so tight clumps spread out even when the
+/// boids are facing the wrong way to turn out of them.
+#[allow(clippy::type_complexity)]
+pub fn push_apart_boids(
+    mut query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &BoidNeighborsSeparation,
+            Option<&Leader>,
+        ),
+        With<Boid>,
+    >,
+    time: Res<Time>,
+    boid_settings: Res<BoidSettings>,
+    round_settings: Res<RoundSettings>,
+) {
+    if !boid_settings.separation_push_enabled {
+        return;
+    }
+    let separation_distance = boid_settings.scaled_separation_distance(round_settings.arena_radius);
+    let pushes: Vec<(Entity, Vec2)> = query
+        .iter()
+        // Leaders are steered by the players, don't let their flock shove them around.
+        .filter(|(_, _, _, leader)| leader.is_none())
+        .map(|(entity, transform, neighbors, _)| {
+            let position = transform.translation.truncate();
+            let push: Vec2 = neighbors
+                .entities
+                .iter()
+                .flat_map(|neighbor| query.get(*neighbor))
+                .map(|(neighbor, neighbor_transform, _, _)| {
+                    separation_push(
+                        position,
+                        neighbor_transform.translation.truncate(),
+                        separation_distance,
+                        boid_settings.separation_push_speed,
+                        // Push boids in the same spot in opposite directions.
+                        match entity < neighbor {
+                            true => Vec2::X,
+                            false => Vec2::NEG_X,
+                        },
+                    )
+                })
+                .sum();
+            (
+                entity,
+                push.clamp_length_max(boid_settings.separation_push_speed),
+            )
+        })
+        .collect();
+
+    for (entity, push) in pushes {
+        if let Ok((_, mut transform, _, _)) = query.get_mut(entity) {
+            transform.translation += (push * time.delta_seconds()).extend(0.0);
+        }
+    }
+}
+
+pub fn clear_inputs(
+    mut query: Query<(
+        Entity,
+        &mut BoidAveragedInputs,
+        &mut ActionState<PlayerActions>,
+    )>,
+    throttle: SteeringThrottle,
+) {
+    for (entity, mut inputs, mut action_state) in query.iter_mut() {
+        // Boids that won't steer next frame keep going the way they were.
+        if !throttle.skips_next_frame(entity) {
+            inputs.reset();
+        }
         action_state.set_action_data(PlayerActions::Rotate, ActionData::default());
         action_state.set_action_data(PlayerActions::Boost, ActionData::default());
     }
@@ -357,21 +1216,157 @@ pub fn update_boid_color(mut query: Query<(&mut Sprite, &BoidColor), Changed<Boi
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum GameEvent {
-    LeaderCaptured(BoidColor),
-    GameOver(Winner),
+    /// The captured leader's color and the color that captured it
+    LeaderCaptured(BoidColor, BoidColor),
+    /// The round is over. There is no winner if it was a tie.
+    GameOver(Option<Winner>),
+}
+
+/// Set once the round has been decided so the game over event is only sent once per round.
+#[derive(Resource, Debug, Default)]
+pub struct GameEnded(pub bool);
+
+impl GameEnded {
+    /// Returns the game over event the first time the round is found to be over.
+    pub fn check(&mut self, colors: impl Iterator<Item = BoidColor>) -> Option<GameEvent> {
+        if self.0 {
+            return None;
+        }
+        let event = check_game_over(colors);
+        self.0 = event.is_some();
+        event
+    }
+}
+
+/// Checks if the round is over based on the colors of all the boids that still have one.
+/// If there are no colors left the round ends in a tie.
+pub fn check_game_over(colors: impl Iterator<Item = BoidColor>) -> Option<GameEvent> {
+    let mut remaining_colors = colors.unique();
+    match (remaining_colors.next(), remaining_colors.next()) {
+        (None, _) => Some(GameEvent::GameOver(None)),
+        (Some(color), None) => Some(GameEvent::GameOver(Some(Winner { color }))),
+        _ => None,
+    }
 }
 
+/// Returns true if the two colors with the most boids around a boid are within `margin` of each
+/// other.
+pub fn is_contested(neighbor_color_counts: &HashMap<BoidColor, usize>, margin: usize) -> bool {
+    let mut counts: Vec<usize> = neighbor_color_counts
+        .values()
+        .copied()
+        .filter(|count| *count > 0)
+        .collect();
+    counts.sort_unstable_by(|a, b| b.cmp(a));
+    match counts.as_slice() {
+        [first, second, ..] => first - second < margin,
+        _ => false,
+    }
+}
+
+/// Returns true if a boid at `position` is close enough to the leader of `color` to be captured
+/// by it. Always true when `radius` is 0.
+pub fn can_capture_near_leader(
+    leader_positions: &HashMap<BoidColor, Vec2>,
+    color: BoidColor,
+    position: Vec2,
+    radius: f32,
+) -> bool {
+    if radius <= 0.0 {
+        return true;
+    }
+    leader_positions
+        .get(&color)
+        .map(|leader| leader.distance_squared(position) <= radius * radius)
+        .unwrap_or_default()
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn propagate_boid_color(
     mut commands: Commands,
     query: Query<(Entity, &BoidNeighborsCaptureRange)>,
     mut boid_colors: Query<&mut BoidColor>,
-    leader_query: Query<&Leader>,
+    leader_query: Query<(Entity, &Transform), With<Leader>>,
+    transforms: Query<&Transform>,
+    spawning_query: Query<Option<&Leader>, With<SpawnIn>>,
+    invulnerable_query: Query<(), With<Invulnerable>>,
+    contested_query: Query<(), With<Contested>>,
     mut event_writer: EventWriter<GameEvent>,
+    boid_settings: Res<BoidSettings>,
+    round_settings: Res<RoundSettings>,
+    mut game_ended: ResMut<GameEnded>,
+    mut streaks: ResMut<CaptureStreaks>,
 ) {
+    // Wait until the leaders have appeared before anyone can get captured. Boids that are
+    // still appearing, like a new wave in survival mode, can't be captured until they're done.
+    if spawning_query.iter().any(|leader| leader.is_some()) {
+        return;
+    }
+    let leader_positions: HashMap<BoidColor, Vec2> = leader_query
+        .iter()
+        .filter_map(|(entity, transform)| {
+            let color = boid_colors.get(entity).ok()?;
+            Some((*color, transform.translation.truncate()))
+        })
+        .collect();
+    let capture_range = boid_settings.scaled_capture_range(round_settings.arena_radius);
+    let recruitment_range = boid_settings.scaled_recruitment_range(round_settings.arena_radius);
+    // Where each leader is and how far it can reach, shorter for leaders that left their flock.
+    let leader_reach: HashMap<Entity, (Vec2, f32)> = match round_settings.leader_escort_size > 0.0 {
+        true => leader_query
+            .iter()
+            .filter_map(|(leader, transform)| {
+                let color = boid_colors.get(leader).ok()?;
+                let (_, leader_neighbors) = query.get(leader).ok()?;
+                let escort = leader_neighbors
+                    .entities
+                    .iter()
+                    .filter(|neighbor| boid_colors.get(**neighbor).ok() == Some(color))
+                    .count();
+                let range =
+                    leader_capture_range(capture_range, escort, round_settings.leader_escort_size);
+                Some((leader, (transform.translation.truncate(), range)))
+            })
+            .collect(),
+        false => HashMap::new(),
+    };
+    // The colors whose leader captured another leader this frame.
+    let mut capturers: Vec<BoidColor> = Vec::new();
+    // The colors whose leader was captured this frame, they're out even though the leader
+    // defeated system hasn't taken their color away yet.
+    let mut captured: Vec<BoidColor> = Vec::new();
     for (entity, neighbors) in query.iter() {
+        if spawning_query.contains(entity) {
+            continue;
+        }
         let mut neighbor_color_counts: HashMap<BoidColor, usize> = HashMap::new();
 
+        // Leave out leaders that are in capture range but too far from their flock to reach us.
+        let reachable;
+        let neighbors = match leader_reach.is_empty() {
+            true => neighbors,
+            false => {
+                let position = transforms
+                    .get(entity)
+                    .map(|t| t.translation.truncate())
+                    .unwrap_or_default();
+                reachable = BoidNeighborsCaptureRange {
+                    entities: neighbors
+                        .entities
+                        .iter()
+                        .filter(|neighbor| match leader_reach.get(*neighbor) {
+                            Some((leader, range)) => leader.distance(position) <= *range,
+                            None => true,
+                        })
+                        .copied()
+                        .collect(),
+                };
+                &reachable
+            }
+        };
+
         // Build a list of all the colors with our color last if we have one.
         // Use this later to skip checking neighbors of our color if there aren't other colors.
         let our_color = boid_colors.get(entity);
@@ -395,48 +1390,99 @@ pub fn propagate_boid_color(
                 neighbors,
                 color,
                 &query,
-                &boid_colors,
+                &boid_colors.to_readonly(),
                 &mut results,
-                10,
+                CAPTURE_SEARCH_DEPTH,
+                boid_settings.max_captures_evaluated,
             );
             if !results.is_empty() {
                 neighbor_color_counts.insert(color, results.len());
             }
         }
 
+        let contested = is_contested(&neighbor_color_counts, boid_settings.contested_margin);
+        match (contested, contested_query.contains(entity)) {
+            (true, false) => {
+                commands.entity(entity).insert(Contested);
+            }
+            (false, true) => {
+                commands.entity(entity).remove::<Contested>();
+            }
+            _ => {}
+        }
+
+        let position = transforms
+            .get(entity)
+            .map(|t| t.translation.truncate())
+            .unwrap_or_default();
         let dominate_color = neighbor_color_counts
             .into_iter()
             .filter(|(_, v)| *v != 0)
-            .max_by_key(|(_, c)| *c);
+            .filter(|(color, _)| {
+                // Our own flock defends us wherever its leader is.
+                Ok(color) == our_color
+                    || can_capture_near_leader(
+                        &leader_positions,
+                        *color,
+                        position,
+                        boid_settings.scaled_leader_capture_radius(round_settings.arena_radius),
+                    )
+            })
+            .filter(|(color, _)| {
+                // Only other colors need to be close enough to recruit us.
+                recruitment_range >= capture_range
+                    || Ok(color) == our_color
+                    || neighbors.entities.iter().any(|neighbor| {
+                        boid_colors.get(*neighbor).ok() == Some(color)
+                            && transforms
+                                .get(*neighbor)
+                                .map(|t| t.translation.truncate().distance(position))
+                                .is_ok_and(|distance| distance <= recruitment_range)
+                    })
+            })
+            // Ties go to our own color and then to the first color, so the result doesn't
+            // depend on the map's order and same-seed games play out the same.
+            .max_by_key(|(color, c)| (*c, Ok(color) == our_color, Reverse(color.index())));
         if let Some((dominate_color, count)) = dominate_color {
             if let Ok(mut our_color) = boid_colors.get_mut(entity) {
                 // Decide if we should convert it
                 if *our_color != dominate_color && count > 1 {
                     // Apply the conversion
-                    if leader_query.contains(entity) {
-                        // We converted a leader!
-                        event_writer.send(GameEvent::LeaderCaptured(*our_color))
-                        // We don't want to change the color yet as it will be handled in the
-                        // leader captured system.
+                    if invulnerable_query.contains(entity) {
+                        // Just taken over, give them a moment.
+                    } else if leader_query.contains(entity) {
+                        // Without ties a leader that just captured can't be captured back in the
+                        // same frame, or two leaders capturing each other could leave no winner.
+                        if round_settings.allow_ties || !capturers.contains(&*our_color) {
+                            // We converted a leader!
+                            capturers.push(dominate_color);
+                            captured.push(*our_color);
+                            event_writer.send(GameEvent::LeaderCaptured(*our_color, dominate_color))
+                            // We don't want to change the color yet as it will be handled in the
+                            // leader captured system.
+                        }
                     } else {
                         let _ = mem::replace(&mut *our_color, dominate_color);
+                        streaks.record_capture(dominate_color);
                     }
                 }
-            } else {
-                // Boids without a color always get converted.
+            } else if !boid_settings.strict_neutral_capture || count > 1 {
+                // Boids without a color get converted by any neighbor unless the capture is strict.
                 commands.entity(entity).insert(dominate_color);
+                streaks.record_capture(dominate_color);
             }
         }
     }
 
-    // Check if there is only one color left
-    let mut remaining_colors = boid_colors.iter().unique();
-    if let Some(first_color) = remaining_colors.next() {
-        if remaining_colors.count() == 0 {
-            event_writer.send(GameEvent::GameOver(Winner {
-                color: *first_color,
-            }));
-        }
+    // Check if there is only one color left. Leaders that captured each other this frame take
+    // both colors out at once, which is a tie if nobody else is left.
+    if let Some(event) = game_ended.check(
+        boid_colors
+            .iter()
+            .copied()
+            .filter(|color| !captured.contains(color)),
+    ) {
+        event_writer.send(event);
     }
 }
 
@@ -444,52 +1490,178 @@ pub fn propagate_boid_color(
 /// Does not include itself.
 ///
 /// Pass a vector of all the previously visited entities to prevent duplicates
+///
+/// Stops searching once `max_results` have been found, so the results are only a lower bound
+/// of the real count for large flocks.
+#[allow(clippy::too_many_arguments)]
 pub fn get_neighbors_of_color_recursive(
     entity: Entity,
     neighbors: &BoidNeighborsCaptureRange,
     color: BoidColor,
     query: &Query<(Entity, &BoidNeighborsCaptureRange)>,
-    colors: &Query<&mut BoidColor>,
+    colors: &Query<&BoidColor>,
     results: &mut Vec<Entity>,
     depth: usize,
+    max_results: usize,
 ) {
     if depth == 0 {
         return;
     }
-    neighbors
+    for (e, neighbors, color) in neighbors
         .entities
         .iter()
         .flat_map(|neighbor| query.get(*neighbor))
         // Remove colorless
         .flat_map(|(e, n)| colors.get(e).map(|c| (e, n, *c)))
         .filter(|(e, _, c)| *c == color && *e != entity)
-        .for_each(|(e, neighbors, color)| {
-            if !results.contains(&e) {
-                results.push(e);
-                get_neighbors_of_color_recursive(
-                    e,
-                    neighbors,
-                    color,
-                    query,
-                    colors,
-                    results,
-                    depth - 1,
-                );
-            }
-        });
+    {
+        if results.len() >= max_results {
+            return;
+        }
+        if !results.contains(&e) {
+            results.push(e);
+            get_neighbors_of_color_recursive(
+                e,
+                neighbors,
+                color,
+                query,
+                colors,
+                results,
+                depth - 1,
+                max_results,
+            );
+        }
+    }
+}
+
+/// The boids of the same color connected to `entity` through their capture range neighbors, as
+/// far as `propagate_boid_color` searches. Includes `entity` itself.
+pub fn capture_cluster(
+    entity: Entity,
+    query: &Query<(Entity, &BoidNeighborsCaptureRange)>,
+    colors: &Query<&BoidColor>,
+    max_results: usize,
+) -> Vec<Entity> {
+    let (color, neighbors) = match (colors.get(entity), query.get(entity)) {
+        (Ok(color), Ok((_, neighbors))) => (*color, neighbors),
+        _ => return Vec::new(),
+    };
+    let mut results = Vec::new();
+    get_neighbors_of_color_recursive(
+        entity,
+        neighbors,
+        color,
+        query,
+        colors,
+        &mut results,
+        CAPTURE_SEARCH_DEPTH,
+        max_results,
+    );
+    if !results.contains(&entity) {
+        results.insert(0, entity);
+    }
+    results
 }
 
-pub fn leader_removed(mut removals: RemovedComponents<Leader>, mut query: Query<&mut Transform>) {
+pub fn leader_removed(
+    mut commands: Commands,
+    mut removals: RemovedComponents<Leader>,
+    mut query: Query<&mut Transform>,
+    children: Query<&Children>,
+    badges: Query<(), With<TeamBadge>>,
+) {
     for entity in removals.iter() {
         if let Ok(mut transform) = query.get_mut(entity) {
             transform.scale = BOID_SCALE;
+            transform.translation.z = BOID_Z;
+        }
+        if let Ok(children) = children.get(entity) {
+            for child in children.iter().filter(|c| badges.contains(**c)) {
+                let scale = query.get(*child).map(|t| t.scale).unwrap_or(Vec3::ONE);
+                commands
+                    .entity(*child)
+                    .remove::<TeamBadge>()
+                    .insert(DeathAnim::new(scale));
+            }
         }
     }
 }
 
-pub fn leader_added(mut query: Query<&mut Transform, Added<Leader>>) {
-    for mut transform in query.iter_mut() {
+pub fn leader_added(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform, Option<&BoidColor>), Added<Leader>>,
+) {
+    for (entity, mut transform, color) in query.iter_mut() {
         transform.scale = LEADER_SCALE;
+        transform.translation.z = LEADER_Z;
+        if let Some(color) = color {
+            spawn_team_badge(&mut commands, entity, *color);
+        }
+    }
+}
+
+pub fn spawn_in_boids(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut SpawnIn, &mut Transform, Option<&Leader>)>,
+    time: Res<Time>,
+    accessibility: Res<AccessibilitySettings>,
+) {
+    for (entity, mut spawn_in, mut transform, leader) in query.iter_mut() {
+        spawn_in.timer.tick(time.delta());
+        let target_scale = match leader {
+            Some(_) => LEADER_SCALE,
+            None => BOID_SCALE,
+        };
+        // Appear straight away instead of growing in.
+        if accessibility.reduce_motion {
+            transform.scale = target_scale;
+            commands.entity(entity).remove::<SpawnIn>();
+            continue;
+        }
+        transform.scale = target_scale * spawn_in.progress();
+        if spawn_in.timer.finished() {
+            commands.entity(entity).remove::<SpawnIn>();
+        }
+    }
+}
+
+/// How many times a second invulnerable leaders flash
+const INVULNERABLE_FLASHES_PER_SECOND: f32 = 4.0;
+
+pub fn update_invulnerability(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Invulnerable, Option<&mut Sprite>)>,
+    time: Res<Time>,
+) {
+    for (entity, mut invulnerable, sprite) in query.iter_mut() {
+        invulnerable.timer.tick(time.delta());
+        let alpha = match invulnerable.timer.finished() {
+            true => {
+                commands.entity(entity).remove::<Invulnerable>();
+                1.0
+            }
+            false => {
+                let flash = invulnerable.timer.elapsed_secs() * INVULNERABLE_FLASHES_PER_SECOND;
+                0.6 + 0.4 * (flash * TAU).cos()
+            }
+        };
+        if let Some(mut sprite) = sprite {
+            sprite.color.set_a(alpha);
+        }
+    }
+}
+
+pub fn animate_deaths(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut DeathAnim, &mut Transform)>,
+    time: Res<Time>,
+) {
+    for (entity, mut death_anim, mut transform) in query.iter_mut() {
+        death_anim.timer.tick(time.delta());
+        transform.scale = death_anim.scale * death_anim.timer.percent_left();
+        if death_anim.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
     }
 }
 
@@ -498,10 +1670,11 @@ pub fn leader_defeated(
     mut event_reader: EventReader<GameEvent>,
     mut query: Query<(Entity, &BoidColor, &mut Sprite)>,
     mut next_app_state: ResMut<NextState<AppState>>,
+    attract_mode: Res<AttractMode>,
 ) {
     for event in event_reader.iter() {
         match event {
-            GameEvent::LeaderCaptured(captured_color) => {
+            GameEvent::LeaderCaptured(captured_color, _) => {
                 info!("{:?} Leader Defeated", captured_color);
                 for (entity, color, mut sprite) in query.iter_mut() {
                     if color == captured_color {
@@ -514,8 +1687,19 @@ pub fn leader_defeated(
                     }
                 }
             }
+            // The attract mode demo restarts itself instead of leaving the title screen.
+            GameEvent::GameOver(_) if attract_mode.active => {}
             GameEvent::GameOver(winner) => {
-                commands.insert_resource(winner.clone());
+                match winner {
+                    Some(winner) => {
+                        info!("{}", winner.title());
+                        commands.insert_resource(winner.clone());
+                    }
+                    None => {
+                        info!("Tie");
+                        commands.remove_resource::<Winner>();
+                    }
+                }
                 next_app_state.set(AppState::GameOver);
             }
         }
@@ -534,3 +1718,1077 @@ fn add_axis_input(
             .map_or(axis_data, |u| u.merged_with(axis_data)),
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use bevy::ecs::system::SystemState;
+    use turborand::prelude::{Rng, SeededCore};
+
+    #[test]
+    #[allow(clippy::type_complexity)]
+    fn test_capture_search_stops_at_max() {
+        let mut world = World::new();
+        let entities: Vec<Entity> = (0..50).map(|_| world.spawn(BoidColor::Red).id()).collect();
+        // Every boid is in range of every other boid.
+        for entity in entities.iter() {
+            world.entity_mut(*entity).insert(BoidNeighborsCaptureRange {
+                entities: entities.clone(),
+            });
+        }
+        let mut system_state: SystemState<(
+            Query<(Entity, &BoidNeighborsCaptureRange)>,
+            Query<&BoidColor>,
+        )> = SystemState::new(&mut world);
+        let (query, colors) = system_state.get(&world);
+        let (start, neighbors) = query.get(entities[0]).unwrap();
+        let mut results = Vec::new();
+        get_neighbors_of_color_recursive(
+            start,
+            neighbors,
+            BoidColor::Red,
+            &query,
+            &colors,
+            &mut results,
+            10,
+            10,
+        );
+        assert_eq!(results.len(), 10);
+    }
+
+    /// How `capture_world` spawns a boid, on top of the others unless it's moved with `at`.
+    #[derive(Clone, Copy)]
+    struct TestBoid {
+        color: Option<BoidColor>,
+        leader: bool,
+        x: f32,
+    }
+
+    impl TestBoid {
+        fn boid(color: BoidColor) -> Self {
+            Self {
+                color: Some(color),
+                leader: false,
+                x: 0.0,
+            }
+        }
+
+        fn neutral() -> Self {
+            Self {
+                color: None,
+                leader: false,
+                x: 0.0,
+            }
+        }
+
+        fn leader(color: BoidColor) -> Self {
+            Self {
+                leader: true,
+                ..Self::boid(color)
+            }
+        }
+
+        fn at(self, x: f32) -> Self {
+            Self { x, ..self }
+        }
+    }
+
+    /// Sets up a world with everything `propagate_boid_color` needs, where the boids in each
+    /// cluster are in each other's capture range. Returns the entities of each cluster in the
+    /// order they were given.
+    fn capture_world(
+        boid_settings: BoidSettings,
+        round_settings: RoundSettings,
+        clusters: &[Vec<TestBoid>],
+    ) -> (World, Vec<Vec<Entity>>) {
+        let mut world = World::new();
+        world.insert_resource(boid_settings);
+        world.insert_resource(round_settings);
+        world.init_resource::<GameEnded>();
+        world.init_resource::<CaptureStreaks>();
+        world.init_resource::<Events<GameEvent>>();
+        let clusters: Vec<Vec<Entity>> = clusters
+            .iter()
+            .map(|cluster| {
+                cluster
+                    .iter()
+                    .map(|boid| {
+                        let mut entity = world.spawn(Transform::from_xyz(boid.x, 0.0, 0.0));
+                        if let Some(color) = boid.color {
+                            entity.insert(color);
+                        }
+                        if boid.leader {
+                            entity.insert(Leader);
+                        }
+                        entity.id()
+                    })
+                    .collect()
+            })
+            .collect();
+        for cluster in clusters.iter() {
+            for entity in cluster.iter() {
+                world.entity_mut(*entity).insert(BoidNeighborsCaptureRange {
+                    entities: cluster.clone(),
+                });
+            }
+        }
+        (world, clusters)
+    }
+
+    /// Runs `propagate_boid_color` once.
+    fn propagate(world: &mut World) {
+        let mut schedule = Schedule::new();
+        schedule.add_system(propagate_boid_color);
+        schedule.run(world);
+    }
+
+    #[test]
+    fn test_capture_requires_nearby_leader() {
+        let capture_at = |leader_x: f32| {
+            let (mut world, clusters) = capture_world(
+                BoidSettings {
+                    leader_capture_radius: 100.0,
+                    ..default()
+                },
+                RoundSettings::default(),
+                &[
+                    vec![TestBoid::leader(BoidColor::Red).at(leader_x)],
+                    vec![
+                        TestBoid::boid(BoidColor::Red),
+                        TestBoid::boid(BoidColor::Red),
+                        TestBoid::boid(BoidColor::Red),
+                        TestBoid::boid(BoidColor::Blue),
+                    ],
+                ],
+            );
+            propagate(&mut world);
+            *world.get::<BoidColor>(clusters[1][3]).unwrap()
+        };
+        assert_eq!(capture_at(50.0), BoidColor::Red);
+        assert_eq!(capture_at(500.0), BoidColor::Blue);
+    }
+
+    #[test]
+    fn test_leaders_capturing_each_other_is_one_consistent_result() {
+        let captures_and_game_overs = |allow_ties: bool| {
+            // Each leader is surrounded by the other color's flock.
+            let surrounded_by = |leader: BoidColor, flock: BoidColor| {
+                let mut cluster = vec![TestBoid::leader(leader)];
+                cluster.extend([TestBoid::boid(flock); 3]);
+                cluster
+            };
+            let (mut world, _) = capture_world(
+                BoidSettings::default(),
+                RoundSettings {
+                    allow_ties,
+                    ..default()
+                },
+                &[
+                    surrounded_by(BoidColor::Red, BoidColor::Blue),
+                    surrounded_by(BoidColor::Blue, BoidColor::Red),
+                ],
+            );
+            propagate(&mut world);
+            let mut events: Vec<GameEvent> =
+                world.resource_mut::<Events<GameEvent>>().drain().collect();
+            let capturers: Vec<BoidColor> = events
+                .iter()
+                .filter_map(|event| match event {
+                    GameEvent::LeaderCaptured(_, by) => Some(*by),
+                    _ => None,
+                })
+                .collect();
+            // The round shouldn't be decided again on later frames.
+            for _ in 0..2 {
+                propagate(&mut world);
+                events.extend(world.resource_mut::<Events<GameEvent>>().drain());
+            }
+            let game_overs: Vec<GameEvent> = events
+                .into_iter()
+                .filter(|event| matches!(event, GameEvent::GameOver(_)))
+                .collect();
+            (capturers, game_overs)
+        };
+
+        // Both leaders are captured and both colors go out in the same frame, so a tie is
+        // called once straight away.
+        let (capturers, results) = captures_and_game_overs(true);
+        assert_eq!(capturers.len(), 2);
+        assert_eq!(results, vec![GameEvent::GameOver(None)]);
+
+        // Without ties only one of the captures counts, so its leader wins.
+        let (capturers, results) = captures_and_game_overs(false);
+        assert_eq!(capturers.len(), 1);
+        assert_eq!(
+            results,
+            vec![GameEvent::GameOver(Some(Winner {
+                color: capturers[0]
+            }))]
+        );
+    }
+
+    #[test]
+    fn test_close_fights_are_contested() {
+        let mut counts = HashMap::from([(BoidColor::Red, 5), (BoidColor::Blue, 4)]);
+        assert!(is_contested(&counts, 2));
+        assert!(!is_contested(&counts, 1));
+        assert!(!is_contested(&counts, 0));
+        counts.insert(BoidColor::Blue, 1);
+        assert!(!is_contested(&counts, 2));
+        assert!(!is_contested(&HashMap::from([(BoidColor::Red, 5)]), 2));
+
+        let (mut world, _) = capture_world(
+            BoidSettings {
+                contested_margin: 2,
+                ..default()
+            },
+            RoundSettings::default(),
+            &[],
+        );
+        // Only the boid in the middle sees its neighbors, so they can't be converted first.
+        let mut boid_near = |color: BoidColor, neighbors: &[BoidColor]| {
+            let entities = neighbors
+                .iter()
+                .map(|color| {
+                    world
+                        .spawn((*color, BoidNeighborsCaptureRange::default()))
+                        .id()
+                })
+                .collect();
+            world
+                .spawn((color, BoidNeighborsCaptureRange { entities }))
+                .id()
+        };
+        let contested = boid_near(
+            BoidColor::Blue,
+            &[BoidColor::Red, BoidColor::Red, BoidColor::Blue],
+        );
+        let dominated = boid_near(BoidColor::Blue, &[BoidColor::Red; 5]);
+        propagate(&mut world);
+        assert!(world.get::<Contested>(contested).is_some());
+        assert!(world.get::<Contested>(dominated).is_none());
+    }
+
+    #[test]
+    fn test_leader_capture_radius_doesnt_drop_defenders() {
+        // The red leader is right here and the blue one far away, but blue outnumbers red.
+        let (mut world, clusters) = capture_world(
+            BoidSettings {
+                leader_capture_radius: 100.0,
+                ..default()
+            },
+            RoundSettings::default(),
+            &[
+                vec![TestBoid::leader(BoidColor::Red)],
+                vec![TestBoid::leader(BoidColor::Blue).at(1000.0)],
+                vec![
+                    TestBoid::boid(BoidColor::Red),
+                    TestBoid::boid(BoidColor::Red),
+                    TestBoid::boid(BoidColor::Blue),
+                    TestBoid::boid(BoidColor::Blue),
+                    TestBoid::boid(BoidColor::Blue),
+                    TestBoid::boid(BoidColor::Blue),
+                ],
+            ],
+        );
+        propagate(&mut world);
+        assert_eq!(
+            *world.get::<BoidColor>(clusters[2][5]).unwrap(),
+            BoidColor::Blue
+        );
+    }
+
+    #[test]
+    fn test_recruitment_range_limits_conversion_not_detection() {
+        let capture_from = |distance: f32, recruitment_range: f32| {
+            // All within capture range so they see each other.
+            let (mut world, clusters) = capture_world(
+                BoidSettings {
+                    capture_range: 20.0,
+                    recruitment_range,
+                    ..default()
+                },
+                RoundSettings::default(),
+                &[vec![
+                    TestBoid::boid(BoidColor::Red).at(distance),
+                    TestBoid::boid(BoidColor::Red).at(distance),
+                    TestBoid::boid(BoidColor::Red).at(distance),
+                    TestBoid::boid(BoidColor::Blue),
+                ]],
+            );
+            propagate(&mut world);
+            *world.get::<BoidColor>(clusters[0][3]).unwrap()
+        };
+        assert_eq!(capture_from(15.0, 20.0), BoidColor::Red);
+        assert_eq!(capture_from(15.0, 10.0), BoidColor::Blue);
+        assert_eq!(capture_from(5.0, 10.0), BoidColor::Red);
+    }
+
+    #[test]
+    fn test_strict_neutral_capture_needs_a_group() {
+        let neutral_after = |red_neighbors: usize, strict: bool| {
+            let mut cluster = vec![TestBoid::boid(BoidColor::Red); red_neighbors];
+            cluster.push(TestBoid::neutral());
+            let (mut world, clusters) = capture_world(
+                BoidSettings {
+                    strict_neutral_capture: strict,
+                    ..default()
+                },
+                RoundSettings::default(),
+                &[cluster],
+            );
+            propagate(&mut world);
+            world.get::<BoidColor>(clusters[0][red_neighbors]).copied()
+        };
+        assert_eq!(neutral_after(1, false), Some(BoidColor::Red));
+        assert_eq!(neutral_after(1, true), None);
+        assert_eq!(neutral_after(2, true), Some(BoidColor::Red));
+    }
+
+    #[test]
+    fn test_scaled_capture_range_follows_arena_radius() {
+        let finds_neighbor = |arena_radius: f32, scale_ranges_with_arena: bool| {
+            let mut world = World::new();
+            world.insert_resource(BoidSettings {
+                scale_ranges_with_arena,
+                ..default()
+            });
+            world.insert_resource(RoundSettings {
+                arena_radius,
+                ..default()
+            });
+            let boid = |x: f32| {
+                (
+                    Boid::default(),
+                    Transform::from_xyz(x, 0.0, 0.0),
+                    BoidNeighborsCaptureRange::default(),
+                    BoidNeighborsSeparation::default(),
+                )
+            };
+            let entity = world.spawn(boid(0.0)).id();
+            // Between the default capture range and double it.
+            world.spawn(boid(30.0));
+            let mut schedule = Schedule::new();
+            schedule.add_system(update_quad_tree);
+            schedule.run(&mut world);
+            let mut schedule = Schedule::new();
+            schedule.add_system(update_boid_neighbors);
+            schedule.run(&mut world);
+            !world
+                .get::<BoidNeighborsCaptureRange>(entity)
+                .unwrap()
+                .entities
+                .is_empty()
+        };
+        let settings = BoidSettings {
+            scale_ranges_with_arena: true,
+            ..default()
+        };
+        assert_relative_eq!(
+            settings.scaled_capture_range(DEFAULT_ARENA_RADIUS * 2.0),
+            settings.capture_range * 2.0
+        );
+        assert!(!finds_neighbor(DEFAULT_ARENA_RADIUS, true));
+        assert!(finds_neighbor(DEFAULT_ARENA_RADIUS * 2.0, true));
+        assert!(!finds_neighbor(DEFAULT_ARENA_RADIUS * 2.0, false));
+    }
+
+    #[test]
+    fn test_lone_leader_has_shorter_capture_range() {
+        assert_relative_eq!(leader_capture_range(20.0, 0, 0.0), 20.0);
+        assert!(leader_capture_range(20.0, 0, 4.0) < leader_capture_range(20.0, 4, 4.0));
+        assert_relative_eq!(leader_capture_range(20.0, 10, 4.0), 20.0);
+
+        // A red leader 15 away from a blue boid, with its escort behind it out of the blue boid's
+        // capture range.
+        let captures_enemy = |escort: usize, leader_escort_size: f32| {
+            // The neighbors are found from the quad tree here rather than given as clusters.
+            let (mut world, _) = capture_world(
+                BoidSettings::default(),
+                RoundSettings {
+                    leader_escort_size,
+                    ..default()
+                },
+                &[],
+            );
+            let neighbors = || {
+                (
+                    Boid::default(),
+                    BoidNeighborsCaptureRange::default(),
+                    BoidNeighborsSeparation::default(),
+                )
+            };
+            world.spawn((
+                neighbors(),
+                Leader,
+                BoidColor::Red,
+                Transform::from_xyz(15.0, 0.0, 0.0),
+            ));
+            let enemy = world
+                .spawn((neighbors(), BoidColor::Blue, Transform::default()))
+                .id();
+            for i in 0..escort {
+                world.spawn((
+                    neighbors(),
+                    BoidColor::Red,
+                    Transform::from_xyz(30.0, i as f32 * 2.0, 0.0),
+                ));
+            }
+            let mut schedule = Schedule::new();
+            schedule.add_system(update_quad_tree);
+            schedule.run(&mut world);
+            let mut schedule = Schedule::new();
+            schedule.add_systems((update_boid_neighbors, propagate_boid_color).chain());
+            schedule.run(&mut world);
+            *world.get::<BoidColor>(enemy).unwrap() == BoidColor::Red
+        };
+        assert!(captures_enemy(4, 4.0));
+        assert!(!captures_enemy(1, 4.0));
+        // Without the rule the same leader captures.
+        assert!(captures_enemy(1, 0.0));
+    }
+
+    #[test]
+    fn test_death_anim_despawns_after_timer() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        let entity = world
+            .spawn((DeathAnim::new(Vec3::ONE), Transform::default()))
+            .id();
+        let mut schedule = Schedule::new();
+        schedule.add_system(animate_deaths);
+        let start = std::time::Instant::now();
+        let mut run_at = |world: &mut World, seconds: f32| {
+            world
+                .resource_mut::<Time>()
+                .update_with_instant(start + std::time::Duration::from_secs_f32(seconds));
+            schedule.run(world);
+        };
+        run_at(&mut world, 0.0);
+        run_at(&mut world, DEATH_ANIM_SECONDS * 0.5);
+        assert!(world.get_entity(entity).is_some());
+        assert!(world.get::<Transform>(entity).unwrap().scale.x < 1.0);
+
+        run_at(&mut world, DEATH_ANIM_SECONDS * 1.1);
+        assert!(world.get_entity(entity).is_none());
+    }
+
+    #[test]
+    fn test_invulnerable_leader_is_not_captured_until_it_expires() {
+        let (mut world, clusters) = capture_world(
+            BoidSettings::default(),
+            RoundSettings::default(),
+            &[vec![
+                TestBoid::leader(BoidColor::Blue),
+                TestBoid::boid(BoidColor::Red),
+                TestBoid::boid(BoidColor::Red),
+                TestBoid::boid(BoidColor::Red),
+            ]],
+        );
+        world.init_resource::<Time>();
+        let leader = clusters[0][0];
+        world.entity_mut(leader).insert(Invulnerable::new(1.0));
+        let mut schedule = Schedule::new();
+        schedule.add_systems((
+            update_invulnerability,
+            propagate_boid_color.after(update_invulnerability),
+        ));
+        let start = std::time::Instant::now();
+        let mut run_at = |world: &mut World, seconds: f32| {
+            world
+                .resource_mut::<Time>()
+                .update_with_instant(start + std::time::Duration::from_secs_f32(seconds));
+            schedule.run(world);
+            let events = world.resource::<Events<GameEvent>>();
+            let captured = events
+                .get_reader()
+                .iter(events)
+                .any(|e| *e == GameEvent::LeaderCaptured(BoidColor::Blue, BoidColor::Red));
+            world.resource_mut::<Events<GameEvent>>().clear();
+            captured
+        };
+        assert!(!run_at(&mut world, 0.0));
+        assert!(!run_at(&mut world, 0.5));
+        // The component is removed at the end of the frame it expires on.
+        run_at(&mut world, 1.1);
+        assert!(world.get::<Invulnerable>(leader).is_none());
+        assert!(run_at(&mut world, 1.2));
+    }
+
+    #[test]
+    fn test_bank_angle() {
+        assert!(bank_angle(-1.0, 0.3) > 0.0);
+        assert!(bank_angle(1.0, 0.3) < 0.0);
+        assert_eq!(bank_angle(0.0, 0.3), 0.0);
+        assert_eq!(bank_angle(-5.0, 0.3), 0.3);
+    }
+
+    #[test]
+    fn test_separation_push_separates_overlapping_boids() {
+        let mut a = Vec2::ZERO;
+        let mut b = Vec2::ZERO;
+        for _ in 0..10 {
+            let push_a = separation_push(a, b, 15.0, 40.0, Vec2::X);
+            let push_b = separation_push(b, a, 15.0, 40.0, Vec2::NEG_X);
+            a += push_a / 60.0;
+            b += push_b / 60.0;
+        }
+        assert!(a.distance(b) > 0.0);
+        assert!(a.x > b.x);
+    }
+
+    #[test]
+    fn test_push_apart_boids_separates_stacked_boids() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.insert_resource(BoidSettings {
+            separation_push_enabled: true,
+            ..default()
+        });
+        world.insert_resource(RoundSettings::default());
+        let a = world.spawn((Boid::default(), Transform::default())).id();
+        let b = world.spawn((Boid::default(), Transform::default())).id();
+        world
+            .entity_mut(a)
+            .insert(BoidNeighborsSeparation { entities: vec![b] });
+        world
+            .entity_mut(b)
+            .insert(BoidNeighborsSeparation { entities: vec![a] });
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(push_apart_boids);
+        let start = std::time::Instant::now();
+        world.resource_mut::<Time>().update_with_instant(start);
+        let position = |world: &World, entity: Entity| {
+            world
+                .get::<Transform>(entity)
+                .unwrap()
+                .translation
+                .truncate()
+        };
+        let mut distances = Vec::new();
+        for frame in 1..=5 {
+            world.resource_mut::<Time>().update_with_instant(
+                start + std::time::Duration::from_secs_f32(frame as f32 / 60.0),
+            );
+            schedule.run(&mut world);
+            distances.push(position(&world, a).distance(position(&world, b)));
+        }
+
+        assert!(distances[0] > 0.0);
+        assert!(distances.windows(2).all(|pair| pair[1] > pair[0]));
+        // Pushed in opposite directions rather than both the same way.
+        assert!(position(&world, a).x > 0.0);
+        assert!(position(&world, b).x < 0.0);
+    }
+
+    #[test]
+    fn test_spawn_in_progress() {
+        let mut spawn_in = SpawnIn::new(0.1);
+        assert_eq!(spawn_in.progress(), 0.0);
+        assert!(BOID_SCALE * spawn_in.progress() != BOID_SCALE);
+        spawn_in
+            .timer
+            .tick(std::time::Duration::from_secs_f32(0.1 + SPAWN_IN_SECONDS));
+        assert_eq!(spawn_in.progress(), 1.0);
+        assert_eq!(BOID_SCALE * spawn_in.progress(), BOID_SCALE);
+    }
+
+    #[test]
+    fn test_gravity_well_pulls_inward() {
+        let mut position = Vec2::new(1100.0, 0.0);
+        for _ in 0..10 {
+            position += gravity_well_pull(position, 20.0) / 60.0;
+        }
+        assert!(position.x < 1100.0);
+        assert_eq!(gravity_well_pull(position, 0.0), Vec2::ZERO);
+    }
+
+    #[test]
+    fn test_check_game_over() {
+        assert_eq!(
+            check_game_over(std::iter::empty()),
+            Some(GameEvent::GameOver(None))
+        );
+        assert_eq!(
+            check_game_over([BoidColor::Red, BoidColor::Red].into_iter()),
+            Some(GameEvent::GameOver(Some(Winner {
+                color: BoidColor::Red
+            })))
+        );
+        assert_eq!(
+            check_game_over([BoidColor::Red, BoidColor::Blue].into_iter()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_game_over_sent_once() {
+        let mut game_ended = GameEnded::default();
+        let events = (0..5)
+            .filter_map(|_| game_ended.check([BoidColor::Red].into_iter()))
+            .count();
+        assert_eq!(events, 1);
+    }
+
+    #[test]
+    fn test_neighbor_update_interval() {
+        let frames: Vec<bool> = (0..6).map(|f| is_neighbor_update_frame(f, 2)).collect();
+        assert_eq!(frames, [true, false, true, false, true, false]);
+        assert!((0..6).all(|f| is_neighbor_update_frame(f, 1)));
+        assert!(is_neighbor_update_frame(3, 0));
+    }
+
+    #[test]
+    fn test_neighbors_are_kept_between_updates() {
+        let mut world = World::new();
+        world.insert_resource(BoidSettings {
+            neighbor_update_interval: 2,
+            ..default()
+        });
+        world.init_resource::<RoundSettings>();
+        let boid = |x: f32| {
+            (
+                Boid::default(),
+                Transform::from_xyz(x, 0.0, 0.0),
+                BoidNeighborsCaptureRange::default(),
+                BoidNeighborsSeparation::default(),
+            )
+        };
+        let entity = world.spawn(boid(0.0)).id();
+        let other = world.spawn(boid(200.0)).id();
+        // Same as the app, the tree is only rebuilt on update frames.
+        let mut schedule = Schedule::new();
+        schedule.add_systems((
+            update_quad_tree.run_if(neighbor_update_due),
+            update_boid_neighbors
+                .after(update_quad_tree)
+                .run_if(resource_exists_and_changed::<BoidTree>()),
+        ));
+        let neighbors = |world: &World| {
+            world
+                .get::<BoidNeighborsCaptureRange>(entity)
+                .unwrap()
+                .entities
+                .clone()
+        };
+
+        schedule.run(&mut world);
+        world.get_mut::<Transform>(other).unwrap().translation.x = 5.0;
+        schedule.run(&mut world);
+        assert!(neighbors(&world).is_empty());
+        // The tree is rebuilt with the boid close by, the neighbors catch up the frame after.
+        schedule.run(&mut world);
+        assert!(neighbors(&world).is_empty());
+        schedule.run(&mut world);
+        assert_eq!(neighbors(&world), [other]);
+        // The stale list is kept until the next update instead of being cleared.
+        world.get_mut::<Transform>(other).unwrap().translation.x = 200.0;
+        schedule.run(&mut world);
+        assert_eq!(neighbors(&world), [other]);
+    }
+
+    #[test]
+    fn test_spawn_positions_are_spaced_out() {
+        let positions = spawn_positions(200, 300.0, 15.0, &Rng::with_seed(1));
+        assert_eq!(positions.len(), 200);
+        for (a, b) in positions.iter().tuple_combinations() {
+            assert!(a.distance(*b) >= 15.0);
+        }
+    }
+
+    #[test]
+    fn test_leaders_are_moved_apart() {
+        let rand = Rng::with_seed(3);
+        let mut positions = vec![Vec2::ZERO, Vec2::new(10.0, 0.0), Vec2::new(0.0, 10.0)];
+        separate_leaders(&mut positions, 2, 1000.0, 300.0, 0.0, &rand);
+        assert_eq!(positions[0], Vec2::ZERO);
+        assert!(positions[1].distance(positions[0]) >= 300.0);
+        assert!(positions[1].length() <= 1000.0);
+        // Regular boids aren't moved.
+        assert_eq!(positions[2], Vec2::new(0.0, 10.0));
+    }
+
+    #[test]
+    fn test_leaders_are_kept_away_from_other_flocks() {
+        let rand = Rng::with_seed(5);
+        // The second leader is far enough from the first, but right next to a boid that the
+        // first leader will pick up.
+        let mut positions = vec![
+            Vec2::ZERO,
+            Vec2::new(320.0, 0.0),
+            Vec2::new(40.0, 0.0),
+            Vec2::new(-600.0, 0.0),
+        ];
+        separate_leaders(&mut positions, 2, 1000.0, 300.0, 50.0, &rand);
+        assert_eq!(positions[0], Vec2::ZERO);
+        assert!(positions[1].distance(positions[0]) >= 300.0);
+        assert!(positions[1].distance(Vec2::new(40.0, 0.0)) >= 300.0);
+        // Boids out of the first leader's reach aren't part of its flock.
+        let mut positions = vec![Vec2::ZERO, Vec2::new(320.0, 0.0), Vec2::new(-600.0, 0.0)];
+        separate_leaders(&mut positions, 2, 1000.0, 300.0, 50.0, &rand);
+        assert_eq!(positions[1], Vec2::new(320.0, 0.0));
+    }
+
+    #[test]
+    fn test_fast_boid_finds_neighbors_it_passed() {
+        let mut tree = QuadTree::<Entity, MAX_BOIDS_PER_NODE>::new(Bounds {
+            x_min: -500.0,
+            x_max: 500.0,
+            y_min: -500.0,
+            y_max: 500.0,
+        });
+        let passed = Entity::from_raw(1);
+        tree.insert(Vec2::ZERO, passed);
+        let left_behind = Entity::from_raw(2);
+        tree.insert(Vec2::new(-100.0, 0.0), left_behind);
+
+        // Moved from -100 to 100 in one frame, straight through the other boid.
+        let position = Vec2::new(100.0, 0.0);
+        let travelled = Vec2::new(200.0, 0.0);
+        assert!(query_swept(&tree, position, travelled, 20.0, 16).contains(&passed));
+        assert!(!query_swept(&tree, position, travelled, 20.0, 1).contains(&passed));
+        assert!(query_swept(&tree, position, Vec2::ZERO, 20.0, 16).is_empty());
+        // Off by default so the capture range is the same at any speed, even the boid that was
+        // right next to where it started isn't found.
+        assert!(query_swept(
+            &tree,
+            position,
+            travelled,
+            20.0,
+            BoidSettings::default().max_substeps
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_no_debug_lines_when_turned_off() {
+        let mut world = World::new();
+        world.init_resource::<DebugLines>();
+        world.init_resource::<Time>();
+        world.init_resource::<CaptureStreaks>();
+        world.insert_resource(BoidSettings::default());
+        world.insert_resource(RoundSettings::default());
+        world.spawn((
+            Boid::default(),
+            Transform::default(),
+            ActionState::<PlayerActions>::default(),
+            BoidAveragedInputs::default(),
+            Velocity::default(),
+            BoidNeighborsSeparation {
+                entities: vec![Entity::from_raw(1)],
+            },
+            BoidNeighborsCaptureRange::default(),
+        ));
+
+        let mut schedule = Schedule::new();
+        schedule.add_systems((update_boid_transforms, draw_neighbor_links));
+        schedule.run(&mut world);
+        assert!(world.resource::<DebugLines>().positions.is_empty());
+
+        world.resource_mut::<BoidSettings>().debug_lines = true;
+        schedule.run(&mut world);
+        assert!(!world.resource::<DebugLines>().positions.is_empty());
+    }
+
+    #[test]
+    fn test_brake_slows_leader_to_brake_floor() {
+        let mut world = World::new();
+        world.init_resource::<DebugLines>();
+        world.init_resource::<Time>();
+        world.init_resource::<CaptureStreaks>();
+        world.insert_resource(BoidSettings {
+            brake_enabled: true,
+            ..default()
+        });
+        world.insert_resource(RoundSettings::default());
+        let cruising_speed = BoidSettings::default().min_speed;
+        let leader = world
+            .spawn((
+                Boid::default(),
+                Leader,
+                Transform::default(),
+                ActionState::<PlayerActions>::default(),
+                BoidAveragedInputs::default(),
+                Velocity {
+                    forward: cruising_speed,
+                    ..default()
+                },
+            ))
+            .id();
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(update_boid_transforms);
+        let start = std::time::Instant::now();
+        world.resource_mut::<Time>().update_with_instant(start);
+        let mut speeds = Vec::new();
+        for frame in 1..=10 {
+            world.resource_mut::<Time>().update_with_instant(
+                start + std::time::Duration::from_secs_f32(frame as f32 * 0.05),
+            );
+            // Inputs are cleared at the end of every frame.
+            let mut action_state = ActionState::<PlayerActions>::default();
+            action_state.press(PlayerActions::Brake);
+            world.entity_mut(leader).insert(action_state);
+            schedule.run(&mut world);
+            speeds.push(world.get::<Velocity>(leader).unwrap().forward);
+        }
+
+        let brake_min_speed = BoidSettings::default().brake_min_speed;
+        assert!(speeds[0] < cruising_speed);
+        assert!(speeds.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert!(speeds.iter().all(|speed| *speed >= brake_min_speed));
+        assert_relative_eq!(*speeds.last().unwrap(), brake_min_speed);
+
+        // With braking off the leader can't go below its cruising speed.
+        world.resource_mut::<BoidSettings>().brake_enabled = false;
+        world.get_mut::<Velocity>(leader).unwrap().forward = cruising_speed;
+        let mut action_state = ActionState::<PlayerActions>::default();
+        action_state.press(PlayerActions::Brake);
+        world.entity_mut(leader).insert(action_state);
+        schedule.run(&mut world);
+        assert_relative_eq!(
+            world.get::<Velocity>(leader).unwrap().forward,
+            cruising_speed
+        );
+    }
+
+    #[test]
+    fn test_project_path() {
+        let straight = project_path(&Transform::default(), 100.0, 0.0, 360.0, 0.5, 10);
+        assert_eq!(straight.len(), 10);
+        let end = straight.last().unwrap();
+        assert_relative_eq!(end.x, 0.0, epsilon = 0.001);
+        assert_relative_eq!(end.y, 50.0, epsilon = 0.001);
+
+        // Turning right at 360 degrees a second for half a second goes half way around a circle.
+        let radius = 100.0 / TAU;
+        let half_circle = project_path(&Transform::default(), 100.0, 1.0, 360.0, 0.5, 100);
+        let end = half_circle.last().unwrap();
+        assert_relative_eq!(end.x, radius * 2.0, epsilon = 0.5);
+        assert_relative_eq!(end.y, 0.0, epsilon = 0.5);
+    }
+
+    #[test]
+    fn test_neighbor_links_match_neighbor_lists() {
+        let separation = BoidNeighborsSeparation {
+            entities: vec![Entity::from_raw(1)],
+        };
+        let capture = BoidNeighborsCaptureRange {
+            entities: vec![Entity::from_raw(1), Entity::from_raw(2)],
+        };
+        assert_eq!(
+            neighbor_links(&separation, &capture),
+            vec![
+                (Entity::from_raw(1), SEPARATION_LINK_COLOR),
+                (Entity::from_raw(1), CAPTURE_LINK_COLOR),
+                (Entity::from_raw(2), CAPTURE_LINK_COLOR),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_leader_turn_rate_multiplier() {
+        let settings = BoidSettings {
+            leader_turn_rate_multiplier: 1.5,
+            ..default()
+        };
+        assert!(settings.turn_rate(true) > settings.turn_rate(false));
+        assert_eq!(
+            settings.turn_rate(false),
+            BoidSettings::default().turn_rate(true)
+        );
+    }
+
+    #[test]
+    fn test_agility_increases_handling() {
+        let mut settings = BoidSettings::default();
+        settings.set_agility(0.5);
+        assert_eq!(settings, BoidSettings::default());
+
+        let mut previous: Option<BoidSettings> = None;
+        for step in 0..=10 {
+            settings.set_agility(step as f32 / 10.0);
+            if let Some(previous) = previous {
+                assert!(settings.max_turn_rate_per_second > previous.max_turn_rate_per_second);
+                assert!(settings.acceleration > previous.acceleration);
+                assert!(settings.max_speed > previous.max_speed);
+            }
+            previous = Some(settings.clone());
+        }
+    }
+
+    #[test]
+    fn test_inverted_speed_warning() {
+        assert!(BoidSettings::default().warnings().is_empty());
+        let settings = BoidSettings {
+            min_speed: 200.0,
+            max_speed: 100.0,
+            ..default()
+        };
+        assert_eq!(settings.warnings().len(), 1);
+    }
+
+    #[test]
+    fn test_color_spread() {
+        assert_eq!(
+            BoidColor::spread(3),
+            vec![BoidColor::Red, BoidColor::Green, BoidColor::Blue]
+        );
+        assert_eq!(BoidColor::spread(8).len(), 8);
+        assert_eq!(
+            BoidColor::next_distinct(&[BoidColor::Red, BoidColor::Green]),
+            Some(BoidColor::Blue)
+        );
+        assert_eq!(
+            BoidColor::next_distinct(&[BoidColor::Orange]),
+            Some(BoidColor::Blue)
+        );
+        assert_eq!(BoidColor::next_distinct(&BoidColor::ALL), None);
+    }
+
+    #[test]
+    fn test_boid_color_display() {
+        let names: Vec<String> = BoidColor::ALL.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            names,
+            ["Red", "Green", "Blue", "Yellow", "Purple", "Orange", "Pink", "Cyan"]
+        );
+        assert_eq!(
+            Winner {
+                color: BoidColor::Purple
+            }
+            .title(),
+            "Purple Won!"
+        );
+    }
+
+    #[test]
+    fn test_separation_push_outside_range() {
+        assert_eq!(
+            separation_push(Vec2::ZERO, Vec2::new(20.0, 0.0), 15.0, 40.0, Vec2::X),
+            Vec2::ZERO
+        );
+    }
+
+    #[test]
+    fn test_input_smoothing_damps_single_frame_spike() {
+        let mut raw = BoidAveragedInputs::default();
+        let mut smooth = BoidAveragedInputs::default();
+        let applied = |inputs: &mut BoidAveragedInputs, smoothing: f32, turn: f32| {
+            inputs.add_turn(turn);
+            let (turn, _) = inputs.smoothed(smoothing);
+            inputs.reset();
+            turn
+        };
+
+        assert_eq!(applied(&mut raw, 0.0, 1.0), 1.0);
+        let spike = applied(&mut smooth, 0.75, 1.0);
+        assert_relative_eq!(spike, 0.25);
+        // The spike fades out over the next frames instead of stopping dead.
+        let after = applied(&mut smooth, 0.75, 0.0);
+        assert!(after > 0.0 && after < spike);
+        assert_eq!(applied(&mut raw, 0.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_turning_back_into_the_arena_is_applied() {
+        let mut world = World::new();
+        world.init_resource::<DebugLines>();
+        world.init_resource::<Time>();
+        world.init_resource::<CaptureStreaks>();
+        world.init_resource::<BoidSettings>();
+        world.insert_resource(RoundSettings::default());
+        // Past the edge on the right, facing up, so it turns left back towards the center.
+        let x = RoundSettings::default().turn_back_radius() + 100.0;
+        let boid = world
+            .spawn((
+                Boid::default(),
+                Transform::from_xyz(x, 0.0, 0.0),
+                ActionState::<PlayerActions>::default(),
+                BoidAveragedInputs::default(),
+                Velocity::default(),
+            ))
+            .id();
+        let mut schedule = Schedule::new();
+        schedule.add_system(update_boid_transforms);
+        let start = std::time::Instant::now();
+        world.resource_mut::<Time>().update_with_instant(start);
+        world
+            .resource_mut::<Time>()
+            .update_with_instant(start + std::time::Duration::from_secs_f32(0.05));
+        schedule.run(&mut world);
+
+        let applied_turn = world
+            .get::<BoidAveragedInputs>(boid)
+            .unwrap()
+            .applied_turn();
+        assert!(applied_turn < 0.0);
+        assert!(bank_angle(applied_turn, 0.3) > 0.0);
+    }
+
+    #[test]
+    fn test_leaders_draw_above_their_flock() {
+        let mut world = World::new();
+        let boid = world.spawn(Transform::from_xyz(0.0, 0.0, BOID_Z)).id();
+        let leader = world.spawn(Transform::from_xyz(0.0, 0.0, BOID_Z)).id();
+        let mut schedule = Schedule::new();
+        schedule.add_systems((leader_added, leader_removed));
+        world.entity_mut(leader).insert(Leader);
+        schedule.run(&mut world);
+        let z =
+            |world: &World, entity: Entity| world.get::<Transform>(entity).unwrap().translation.z;
+        assert!(z(&world, leader) > z(&world, boid));
+
+        // Captured leaders drop back into the flock.
+        world.entity_mut(leader).remove::<Leader>();
+        schedule.run(&mut world);
+        assert_eq!(z(&world, leader), z(&world, boid));
+    }
+
+    #[test]
+    #[allow(clippy::type_complexity)]
+    fn test_capture_cluster_is_connected_same_color_boids() {
+        let mut world = World::new();
+        // a - b - c - d is a chain of capture range neighbors, d is blue and e is on its own.
+        let [a, b, c, d, e] = [
+            BoidColor::Red,
+            BoidColor::Red,
+            BoidColor::Red,
+            BoidColor::Blue,
+            BoidColor::Red,
+        ]
+        .map(|color| world.spawn(color).id());
+        let f = world.spawn(BoidColor::Red).id();
+        for (entity, entities) in [
+            (a, vec![b]),
+            (b, vec![a, c]),
+            (c, vec![b, d]),
+            (d, vec![c, f]),
+            (e, vec![]),
+            (f, vec![d]),
+        ] {
+            world
+                .entity_mut(entity)
+                .insert(BoidNeighborsCaptureRange { entities });
+        }
+        let mut system_state: SystemState<(
+            Query<(Entity, &BoidNeighborsCaptureRange)>,
+            Query<&BoidColor>,
+        )> = SystemState::new(&mut world);
+        let (query, colors) = system_state.get(&world);
+        let cluster = |entity| {
+            capture_cluster(entity, &query, &colors, 100)
+                .into_iter()
+                .sorted()
+                .collect::<Vec<_>>()
+        };
+
+        // Red boids on the other side of a blue one aren't part of the cluster.
+        assert_eq!(cluster(a), vec![a, b, c]);
+        assert_eq!(cluster(c), vec![a, b, c]);
+        assert_eq!(cluster(d), vec![d]);
+        assert_eq!(cluster(e), vec![e]);
+    }
+}
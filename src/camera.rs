@@ -1,20 +1,192 @@
-use crate::math::Average;
-use crate::{Camera2d, Leader, PlayerActions, Query, ScalingMode, SCENE_HEIGHT};
-use bevy::math::Vec2Swizzles;
+use crate::accessibility::AccessibilitySettings;
+use crate::math::{smooth_noise, weighted_average};
+use crate::stats::BoidCensus;
+use crate::viewports::ViewportRelative;
+use crate::{
+    Boid, BoidColor, Camera2d, GameEvent, GlobalActions, Leader, PlayerActions, Query,
+    RoundSettings, ScalingMode, Velocity, Winner, SCENE_HEIGHT,
+};
 use bevy::prelude::*;
+use bevy_inspector_egui::InspectorOptions;
 use leafwing_input_manager::prelude::*;
 use std::time::Duration;
 
+/// The smallest height the camera can be zoomed in to
+const MIN_CAMERA_HEIGHT: f32 = 200.0;
+/// The largest height a Camera2dFollow can be zoomed out to
+const MAX_CAMERA_HEIGHT: f32 = 1200.0;
+/// How far players can zoom a Camera2dFollowMany in or out from its automatic framing
+const MAX_FOLLOW_MANY_ZOOM: f32 = 500.0;
+/// How much trauma every camera gets when a leader is captured
+const CAPTURE_TRAUMA: f32 = 0.5;
+/// How far the camera moves when it has the most trauma
+const MAX_SHAKE_DISTANCE: f32 = 12.0;
+const TRAUMA_DECAY_PER_SECOND: f32 = 1.0;
+/// How long the cameras take to sweep over to the winning flock when the round ends
+const WINNER_SWEEP_SECONDS: f32 = 1.0;
+/// How long the border flashed around a player's view after they capture a leader takes to fade
+pub const CAPTURE_FLASH_SECONDS: f32 = 0.4;
+/// How quickly a chase camera turns to match its target, higher is faster.
+const CHASE_ROTATION_SMOOTHING: f32 = 2.0;
+/// How much taller than the flock's spread the view is when auto zoom fits it fully
+const AUTO_ZOOM_SPREAD_MARGIN: f32 = 4.0;
+/// How quickly auto zoom eases to its target height, kept low so it isn't distracting.
+const AUTO_ZOOM_SMOOTHING: f32 = 0.5;
+/// How long auto zoom waits after the player zooms themselves
+const AUTO_ZOOM_PAUSE_SECONDS: f32 = 5.0;
+/// How quickly a camera glides over to a new target after a cut, higher is faster.
+const CUT_SMOOTHING: f32 = 4.0;
+
+#[derive(Reflect, Debug, Resource, InspectorOptions)]
+#[reflect(Resource)]
+pub struct CameraSettings {
+    /// How far the camera looks ahead of the player for every unit per second they are moving,
+    /// when the lead blend is all the way up. 0 is off.
+    #[inspector(min = 0.0, max = 10.0)]
+    pub lead_per_speed: f32,
+    /// How much a player's camera looks ahead of their leader, 0 keeps it centered on them and
+    /// 1 uses the full offset and lead.
+    #[inspector(min = 0.0, max = 1.0)]
+    pub lead_blend: f32,
+    /// How quickly the camera catches up to the lead, higher is faster.
+    #[inspector(min = 0.1, max = 100.0)]
+    pub lead_smoothing: f32,
+    /// The furthest a shared screen camera will zoom out to fit everyone. Players further away
+    /// are shown with an arrow at the edge of the screen instead. 0 is off.
+    #[inspector(min = 0.0, max = 10000.0)]
+    pub max_shared_height: f32,
+    /// New player cameras turn with their leader so up is always the way they're going.
+    pub chase_camera: bool,
+    /// Shown behind the game, like in the gaps between split screen viewports.
+    pub letterbox_color: Color,
+    /// Player cameras zoom out when their flock is spread out and in when it's tight.
+    pub auto_zoom: bool,
+    /// How much auto zoom fits the view to the flock, 0 keeps the default height.
+    #[inspector(min = 0.0, max = 1.0)]
+    pub auto_zoom_strength: f32,
+    /// How many times more human players count than bots when a shared screen camera decides
+    /// where to center. 1 treats everyone the same.
+    #[inspector(min = 1.0, max = 100.0)]
+    pub human_focus_weight: f32,
+    /// Bots further than this from the human players are left out of the shared screen camera's
+    /// framing. 0 is off.
+    #[inspector(min = 0.0, max = 10000.0)]
+    pub max_bot_framing_distance: f32,
+    /// How quickly the shared screen camera eases to its new center and zoom, higher is faster.
+    /// 0 snaps straight there.
+    #[inspector(min = 0.0, max = 100.0)]
+    pub shared_smoothing: f32,
+    /// A single target this far from all the others is left out of the shared screen camera's
+    /// framing, so one player wandering off doesn't zoom everyone else out. 0 is off.
+    #[inspector(min = 0.0, max = 10000.0)]
+    pub shared_outlier_distance: f32,
+    /// How fast the game runs for a moment after a leader is captured, from 0.0 to 1.0.
+    #[inspector(min = 0.0, max = 1.0)]
+    pub hit_stop_speed: f32,
+    /// How long the game takes to get back to full speed after a capture in seconds. 0 is off.
+    #[inspector(min = 0.0, max = 5.0)]
+    pub hit_stop_seconds: f32,
+    /// Draws an arrow at the middle of each flock on the shared screen showing which way it's
+    /// heading and how fast.
+    pub flock_arrows: bool,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            lead_per_speed: 0.5,
+            lead_blend: 0.0,
+            lead_smoothing: 3.0,
+            max_shared_height: 0.0,
+            chase_camera: false,
+            letterbox_color: Color::BLACK,
+            auto_zoom: false,
+            auto_zoom_strength: 0.5,
+            human_focus_weight: 1.0,
+            max_bot_framing_distance: 0.0,
+            shared_smoothing: 4.0,
+            shared_outlier_distance: 0.0,
+            hit_stop_speed: 0.2,
+            hit_stop_seconds: 0.0,
+            flock_arrows: false,
+        }
+    }
+}
+
+/// Returns how far ahead of a target moving in `direction` at `speed` the camera should look.
+pub fn velocity_lead(direction: Vec2, speed: f32, lead_per_speed: f32) -> Vec2 {
+    direction.normalize_or_zero() * speed * lead_per_speed
+}
+
+/// Where a camera following `target` looks, moved away from it by `blend` of the offset and lead.
+pub fn blended_follow_position(target: Vec2, offset: Vec2, lead: Vec2, blend: f32) -> Vec2 {
+    target + (offset + lead) * blend.clamp(0.0, 1.0)
+}
+
+/// Eases `current` towards `target`, used to turn chase cameras without snapping.
+pub fn chase_rotation(current: Quat, target: Quat, delta_seconds: f32) -> Quat {
+    let t = 1.0 - (-CHASE_ROTATION_SMOOTHING * delta_seconds).exp();
+    current.slerp(target, t)
+}
+
+/// Converts a cursor position to the world position under it for a camera.
+/// `cursor` and `viewport_min` are in logical window pixels from the top left of the window,
+/// `view_size` is how much of the world the camera shows.
+pub fn cursor_to_world(
+    cursor: Vec2,
+    viewport_min: Vec2,
+    viewport_size: Vec2,
+    view_size: Vec2,
+    camera_transform: &Transform,
+) -> Option<Vec2> {
+    let uv = (cursor - viewport_min) / viewport_size;
+    if !(0.0..=1.0).contains(&uv.x) || !(0.0..=1.0).contains(&uv.y) {
+        return None;
+    }
+    // Window y goes down, world y goes up.
+    let local = (Vec2::new(uv.x, 1.0 - uv.y) - 0.5) * view_size;
+    Some(
+        camera_transform
+            .transform_point(local.extend(0.0))
+            .truncate(),
+    )
+}
+
 pub fn update_camera_follow_system(
-    mut cameras: Query<(&Camera2dFollow, &mut Transform), With<Camera2d>>,
-    transforms: Query<&GlobalTransform>,
+    mut cameras: Query<(&mut Camera2dFollow, &mut Transform), With<Camera2d>>,
+    transforms: Query<(&GlobalTransform, &Transform, Option<&Velocity>), Without<Camera2d>>,
+    camera_settings: Res<CameraSettings>,
+    time: Res<Time>,
 ) {
-    for (camera_follow, mut transform) in cameras.iter_mut() {
-        if let Ok(target_transform) = transforms.get(camera_follow.target) {
-            let mut translation = target_transform.translation() + camera_follow.offset.xyy();
+    for (mut camera_follow, mut transform) in cameras.iter_mut() {
+        if let Ok((target_transform, target_local, velocity)) = transforms.get(camera_follow.target)
+        {
+            if camera_follow.rotate_with_target {
+                // Use the local rotation so sprite banking doesn't rock the camera.
+                transform.rotation = chase_rotation(
+                    transform.rotation,
+                    target_local.rotation,
+                    time.delta_seconds(),
+                );
+            }
+            let target_lead = velocity_lead(
+                target_transform.up().truncate(),
+                velocity.map(|v| v.forward).unwrap_or_default(),
+                camera_settings.lead_per_speed,
+            );
+            // Ease towards the lead so it doesn't snap when the target turns around.
+            let t = 1.0 - (-camera_settings.lead_smoothing * time.delta_seconds()).exp();
+            camera_follow.lead = camera_follow.lead.lerp(target_lead, t);
+            let t = 1.0 - (-CUT_SMOOTHING * time.delta_seconds()).exp();
+            camera_follow.cut_offset = camera_follow.cut_offset.lerp(Vec2::ZERO, t);
+            let position = blended_follow_position(
+                target_transform.translation().truncate(),
+                camera_follow.offset,
+                camera_follow.lead,
+                camera_settings.lead_blend,
+            ) + camera_follow.cut_offset;
             // Keep the z position of the camera.
-            translation.z = transform.translation.z;
-            transform.translation = translation;
+            transform.translation = position.extend(transform.translation.z);
         }
     }
 }
@@ -24,72 +196,679 @@ pub fn update_camera_follow_system(
 pub struct Camera2dFollow {
     pub target: Entity,
     pub offset: Vec2,
+    /// How far ahead of the target the camera is currently looking
+    pub lead: Vec2,
+    /// Turns the camera with the target instead of keeping north up
+    pub rotate_with_target: bool,
+    /// Seconds until auto zoom takes over again after the player zoomed
+    pub auto_zoom_paused: f32,
+    /// Where the camera was relative to its target when it last switched targets, shrinks
+    /// to nothing so the cut is smooth.
+    pub cut_offset: Vec2,
+    /// Easing the zoom back to the default height after the player recentered
+    pub recentering: bool,
 }
 
-impl FromWorld for Camera2dFollow {
-    fn from_world(world: &mut World) -> Self {
+impl Camera2dFollow {
+    pub fn new(target: Entity) -> Self {
         Self {
-            target: world.entities().reserve_entity(),
+            target,
             offset: default(),
+            lead: default(),
+            rotate_with_target: false,
+            auto_zoom_paused: 0.0,
+            cut_offset: default(),
+            recentering: false,
         }
     }
 }
 
+impl FromWorld for Camera2dFollow {
+    fn from_world(world: &mut World) -> Self {
+        Self::new(world.entities().reserve_entity())
+    }
+}
+
 pub fn camera_zoom(
-    mut query: Query<(&Camera2dFollow, &mut OrthographicProjection)>,
-    player_query: Query<(Entity, &ActionState<PlayerActions>)>,
+    mut query: Query<(&mut Camera2dFollow, &mut OrthographicProjection)>,
+    mut follow_many_query: Query<&mut Camera2dFollowMany>,
+    player_query: Query<(
+        Entity,
+        &ActionState<PlayerActions>,
+        Option<&CameraFollowTarget>,
+    )>,
     time: Res<Time>,
+    accessibility: Res<AccessibilitySettings>,
 ) {
-    for (entity, action_state) in player_query.iter() {
-        let amount = match action_state.just_pressed(PlayerActions::CameraZoom) {
-            true => 50.0,
-            false => match action_state.current_duration(PlayerActions::CameraZoom)
-                > Duration::from_secs_f32(0.25)
-            {
-                true => 320.0 * time.delta_seconds(),
+    for (entity, action_state, follow_target) in player_query.iter() {
+        let amount = match accessibility.reduce_motion {
+            // Zoom smoothly while the button is held instead of jumping on the first press.
+            true => match action_state.pressed(PlayerActions::CameraZoom) {
+                true => 160.0 * time.delta_seconds(),
                 false => 0.0,
             },
+            false => match action_state.just_pressed(PlayerActions::CameraZoom) {
+                true => 50.0,
+                false => match action_state.current_duration(PlayerActions::CameraZoom)
+                    > Duration::from_secs_f32(0.25)
+                {
+                    true => 320.0 * time.delta_seconds(),
+                    false => 0.0,
+                },
+            },
         };
-        for (camera_follow, mut projection) in query.iter_mut() {
+        for (mut camera_follow, mut projection) in query.iter_mut() {
             if camera_follow.target == entity {
                 if let ScalingMode::FixedVertical(x) = projection.scaling_mode {
                     if let Some(axis_pair) =
                         action_state.clamped_axis_pair(PlayerActions::CameraZoom)
                     {
+                        if amount > 0.0 && axis_pair.y() != 0.0 {
+                            camera_follow.auto_zoom_paused = AUTO_ZOOM_PAUSE_SECONDS;
+                            camera_follow.recentering = false;
+                        }
                         projection.scaling_mode = ScalingMode::FixedVertical(
-                            (x - axis_pair.y() * amount).clamp(200.0, 1200.0),
+                            (x - axis_pair.y() * amount)
+                                .clamp(MIN_CAMERA_HEIGHT, MAX_CAMERA_HEIGHT),
                         );
                     }
                 }
             }
         }
+        // Players sharing a screen all zoom the same camera.
+        if follow_target.is_some() {
+            if let Some(axis_pair) = action_state.clamped_axis_pair(PlayerActions::CameraZoom) {
+                for mut follow_many in follow_many_query.iter_mut() {
+                    follow_many.zoom = (follow_many.zoom - axis_pair.y() * amount)
+                        .clamp(-MAX_FOLLOW_MANY_ZOOM, MAX_FOLLOW_MANY_ZOOM);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the root mean square distance of a flock from its leader.
+pub fn flock_spread(leader: Vec2, flock: impl Iterator<Item = Vec2>) -> f32 {
+    let (sum, count) = flock.fold((0.0, 0), |(sum, count), position| {
+        (sum + position.distance_squared(leader), count + 1)
+    });
+    match count {
+        0 => 0.0,
+        _ => (sum / count as f32).sqrt(),
+    }
+}
+
+/// Returns how tall a player's view should be to show a flock with `spread`, blended with the
+/// default height by `strength`.
+pub fn auto_zoom_height(spread: f32, strength: f32) -> f32 {
+    let fit_height = spread * AUTO_ZOOM_SPREAD_MARGIN;
+    (SCENE_HEIGHT + (fit_height - SCENE_HEIGHT) * strength.clamp(0.0, 1.0))
+        .clamp(MIN_CAMERA_HEIGHT, MAX_CAMERA_HEIGHT)
+}
+
+pub fn auto_zoom_cameras(
+    mut cameras: Query<(&mut Camera2dFollow, &mut OrthographicProjection)>,
+    boids: Query<(&Transform, &BoidColor)>,
+    camera_settings: Res<CameraSettings>,
+    time: Res<Time>,
+) {
+    if !camera_settings.auto_zoom {
+        return;
+    }
+    for (mut camera_follow, mut projection) in cameras.iter_mut() {
+        if camera_follow.auto_zoom_paused > 0.0 {
+            camera_follow.auto_zoom_paused -= time.delta_seconds();
+            continue;
+        }
+        let (leader, color) = match boids.get(camera_follow.target) {
+            Ok((transform, color)) => (transform.translation.truncate(), color),
+            Err(_) => continue,
+        };
+        let spread = flock_spread(
+            leader,
+            boids
+                .iter()
+                .filter(|(_, c)| *c == color)
+                .map(|(t, _)| t.translation.truncate()),
+        );
+        if let ScalingMode::FixedVertical(x) = projection.scaling_mode {
+            let target = auto_zoom_height(spread, camera_settings.auto_zoom_strength);
+            let t = 1.0 - (-AUTO_ZOOM_SMOOTHING * time.delta_seconds()).exp();
+            projection.scaling_mode = ScalingMode::FixedVertical(x + (target - x) * t);
+        }
+    }
+}
+
+/// Eases a player's camera back onto their leader and resets the zoom when they press the
+/// recenter button. Players whose leader was captured or handed to a bot don't have their own
+/// controls anymore, so the same buttons recenter every camera that isn't following a human.
+pub fn recenter_camera(
+    mut query: Query<(&mut Camera2dFollow, &mut OrthographicProjection)>,
+    player_query: Query<&ActionState<PlayerActions>>,
+    humans: Query<(), With<InputMap<PlayerActions>>>,
+    global_actions: Query<&ActionState<GlobalActions>>,
+    camera_settings: Res<CameraSettings>,
+    time: Res<Time>,
+) {
+    let recenter_spectators = global_actions
+        .iter()
+        .any(|action_state| action_state.just_pressed(GlobalActions::RecenterCamera));
+    for (mut camera_follow, mut projection) in query.iter_mut() {
+        let pressed = match humans.contains(camera_follow.target) {
+            true => player_query
+                .get(camera_follow.target)
+                .map(|action_state| action_state.just_pressed(PlayerActions::RecenterCamera))
+                .unwrap_or_default(),
+            false => recenter_spectators,
+        };
+        if pressed {
+            // Glide back from where the camera was looking, the same as a cut to a new target.
+            let lead = camera_follow.lead * camera_settings.lead_blend.clamp(0.0, 1.0);
+            camera_follow.cut_offset += lead;
+            camera_follow.lead = Vec2::ZERO;
+            camera_follow.recentering = true;
+        }
+        if camera_follow.recentering {
+            let height = match projection.scaling_mode {
+                ScalingMode::FixedVertical(x) => {
+                    let t = smoothing_step(camera_settings.lead_smoothing, time.delta_seconds());
+                    x + (SCENE_HEIGHT - x) * t
+                }
+                _ => SCENE_HEIGHT,
+            };
+            camera_follow.recentering = (height - SCENE_HEIGHT).abs() > 1.0;
+            projection.scaling_mode = ScalingMode::FixedVertical(match camera_follow.recentering {
+                true => height,
+                false => SCENE_HEIGHT,
+            });
+        }
     }
 }
 
 // Add to the camera
-#[derive(Component)]
-pub struct Camera2dFollowMany;
+#[derive(Component, Default)]
+pub struct Camera2dFollowMany {
+    /// How much the players have zoomed out from the automatic framing
+    pub zoom: f32,
+    /// Where the camera is easing towards the center of the targets from, None until it has
+    /// framed them once.
+    pub center: Option<Vec2>,
+    /// The view height the camera is easing from, None until it has framed the targets once.
+    pub height: Option<f32>,
+}
 
 // Add to an entity to be followed by the Camera2dFollowMany camera
 #[derive(Component)]
 pub struct CameraFollowTarget;
 
+/// Returns how tall the view of a Camera2dFollowMany should be to fit targets that are
+/// `max_distance` apart, after applying the players' zoom and limiting it to `max_height`.
+pub fn follow_many_height(max_distance: Option<f32>, zoom: f32, max_height: Option<f32>) -> f32 {
+    let height = max_distance.map(|x| x + 500.0).unwrap_or(SCENE_HEIGHT) + zoom;
+    max_height
+        .map(|max| height.min(max))
+        .unwrap_or(height)
+        .max(MIN_CAMERA_HEIGHT)
+}
+
+/// Returns where to point at a target from inside of a view going from `view_min` to `view_max`,
+/// or None if the target is in view and doesn't need it.
+pub fn edge_indicator(view_min: Vec2, view_max: Vec2, target: Vec2, margin: f32) -> Option<Vec2> {
+    let inside = target.cmpge(view_min).all() && target.cmple(view_max).all();
+    match inside {
+        true => None,
+        false => Some(target.clamp(view_min + margin, view_max - margin)),
+    }
+}
+
+/// Returns the targets a shared screen camera should frame and how much each one counts towards
+/// its center. Targets are a position and whether they're a human player.
+pub fn framed_targets(
+    targets: &[(Vec2, bool)],
+    camera_settings: &CameraSettings,
+) -> Vec<(Vec2, f32)> {
+    let humans_center = weighted_average(
+        targets
+            .iter()
+            .filter(|(_, human)| *human)
+            .map(|(position, _)| (*position, 1.0)),
+    );
+    targets
+        .iter()
+        .filter(|(position, human)| {
+            match (
+                *human,
+                humans_center,
+                camera_settings.max_bot_framing_distance > 0.0,
+            ) {
+                (false, Some(humans_center), true) => {
+                    position.distance(humans_center) <= camera_settings.max_bot_framing_distance
+                }
+                _ => true,
+            }
+        })
+        .map(|(position, human)| {
+            let weight = match human {
+                true => camera_settings.human_focus_weight.max(1.0),
+                false => 1.0,
+            };
+            (*position, weight)
+        })
+        .collect()
+}
+
+/// Leaves out the one target furthest from all the others if it's more than `outlier_distance`
+/// away from them. Needs at least three targets so two players apart are still both framed.
+pub fn without_outlier(mut framed: Vec<(Vec2, f32)>, outlier_distance: f32) -> Vec<(Vec2, f32)> {
+    if outlier_distance <= 0.0 || framed.len() < 3 {
+        return framed;
+    }
+    let outlier = framed
+        .iter()
+        .enumerate()
+        .map(|(i, (a, _))| {
+            let nearest = framed
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, (b, _))| a.distance(*b))
+                .fold(f32::INFINITY, f32::min);
+            (i, nearest)
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b));
+    if let Some((i, nearest)) = outlier {
+        if nearest > outlier_distance {
+            framed.remove(i);
+        }
+    }
+    framed
+}
+
+/// Returns the furthest distance between any two of the framed targets.
+pub fn framed_spread(framed: &[(Vec2, f32)]) -> Option<f32> {
+    framed
+        .iter()
+        .enumerate()
+        .flat_map(|(i, (a, _))| framed[i + 1..].iter().map(|(b, _)| a.distance(*b)))
+        .max_by(|a, b| a.total_cmp(b))
+}
+
+/// How far to ease towards a target this frame, all the way when `smoothing` is 0.
+fn smoothing_step(smoothing: f32, delta_seconds: f32) -> f32 {
+    match smoothing > 0.0 {
+        true => 1.0 - (-smoothing * delta_seconds).exp(),
+        false => 1.0,
+    }
+}
+
 pub fn update_camera_follow_many_system(
-    mut cameras: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2dFollowMany>>,
-    targets: Query<&GlobalTransform, With<CameraFollowTarget>>,
+    mut cameras: Query<(
+        &mut Transform,
+        &mut OrthographicProjection,
+        &mut Camera2dFollowMany,
+    )>,
+    targets: Query<(&GlobalTransform, Option<&InputMap<PlayerActions>>), With<CameraFollowTarget>>,
+    camera_settings: Res<CameraSettings>,
+    time: Res<Time>,
 ) {
+    let max_height = match camera_settings.max_shared_height > 0.0 {
+        true => Some(camera_settings.max_shared_height),
+        false => None,
+    };
+    let targets: Vec<(Vec2, bool)> = targets
+        .iter()
+        .map(|(transform, input_map)| (transform.translation().truncate(), input_map.is_some()))
+        .collect();
+    let framed = without_outlier(
+        framed_targets(&targets, &camera_settings),
+        camera_settings.shared_outlier_distance,
+    );
+    let max_distance = framed_spread(&framed);
+    // Ease the center and zoom so players swapping sides doesn't jerk the view around.
+    let t = smoothing_step(camera_settings.shared_smoothing, time.delta_seconds());
+    for (mut transform, mut projection, mut follow_many) in cameras.iter_mut() {
+        let target_height = follow_many_height(max_distance, follow_many.zoom, max_height);
+        let height = match follow_many.height {
+            Some(height) => height + (target_height - height) * t,
+            None => target_height,
+        };
+        follow_many.height = Some(height);
+        projection.scaling_mode = ScalingMode::FixedVertical(height);
+        if let Some(target_center) = weighted_average(framed.iter().copied()) {
+            let center = match follow_many.center {
+                Some(center) => center.lerp(target_center, t),
+                None => target_center,
+            };
+            follow_many.center = Some(center);
+            transform.translation = center.extend(transform.translation.z);
+        }
+    }
+}
+
+/// Shakes the camera while it has trauma, which fades over time.
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct CameraShake {
+    /// From 0.0 to 1.0, the shake grows with the square of this.
+    pub trauma: f32,
+}
+
+/// Returns how much trauma capturing a leader adds to the cameras.
+pub fn capture_trauma(reduce_motion: bool) -> f32 {
+    match reduce_motion {
+        true => 0.0,
+        false => CAPTURE_TRAUMA,
+    }
+}
+
+pub fn add_capture_trauma(
+    mut events: EventReader<GameEvent>,
+    mut cameras: Query<&mut CameraShake>,
+    accessibility: Res<AccessibilitySettings>,
+) {
+    for event in events.iter() {
+        if let GameEvent::LeaderCaptured(..) = event {
+            for mut shake in cameras.iter_mut() {
+                shake.trauma =
+                    (shake.trauma + capture_trauma(accessibility.reduce_motion)).min(1.0);
+            }
+        }
+    }
+}
+
+/// A border flashed in a player's team color around their view when they capture a leader.
+#[derive(Debug, Clone, Copy)]
+pub struct CaptureFlash {
+    pub viewport: ViewportRelative,
+    pub color: BoidColor,
+    /// Seconds left until the flash has faded out
+    pub remaining: f32,
+}
+
+impl CaptureFlash {
+    /// How opaque the flash is, fading from 1.0 to 0.0.
+    pub fn alpha(&self) -> f32 {
+        (self.remaining / CAPTURE_FLASH_SECONDS).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct CaptureFlashes(pub Vec<CaptureFlash>);
+
+/// Flashes the view of each local player whose flock captured a leader, so players can tell their
+/// own captures apart in split screen. Skipped entirely when motion is reduced.
+pub fn update_capture_flashes(
+    mut events: EventReader<GameEvent>,
+    mut flashes: ResMut<CaptureFlashes>,
+    round_settings: Res<RoundSettings>,
+    accessibility: Res<AccessibilitySettings>,
+    time: Res<Time>,
+) {
+    for flash in flashes.0.iter_mut() {
+        flash.remaining -= time.delta_seconds();
+    }
+    flashes.0.retain(|flash| flash.remaining > 0.0);
+    for event in events.iter() {
+        if let GameEvent::LeaderCaptured(_, captured_by) = event {
+            if accessibility.reduce_motion {
+                continue;
+            }
+            let viewports = round_settings
+                .players
+                .iter()
+                .enumerate()
+                .filter(|(_, player)| player.color == *captured_by)
+                .filter_map(|(i, _)| round_settings.player_viewport(i, 0.0));
+            for viewport in viewports {
+                flashes.0.push(CaptureFlash {
+                    viewport,
+                    color: *captured_by,
+                    remaining: CAPTURE_FLASH_SECONDS,
+                });
+            }
+        }
+    }
+}
+
+/// Slows the game down for a moment after a capture for emphasis.
+#[derive(Resource, Debug, Default)]
+pub struct HitStop {
+    /// Real seconds left until the game is back to full speed
+    remaining: f32,
+    duration: f32,
+}
+
+/// Returns how fast the game runs `progress` of the way through a hit stop, easing from `dip`
+/// back up to full speed.
+pub fn hit_stop_speed(dip: f32, progress: f32) -> f32 {
+    let progress = progress.clamp(0.0, 1.0);
+    dip + (1.0 - dip) * progress * progress
+}
+
+/// Starts a hit stop on captures and eases the game back to full speed. Only the game's time is
+/// slowed, sounds keep playing normally.
+pub fn update_hit_stop(
+    mut events: EventReader<GameEvent>,
+    mut hit_stop: ResMut<HitStop>,
+    mut time: ResMut<Time>,
+    camera_settings: Res<CameraSettings>,
+    accessibility: Res<AccessibilitySettings>,
+) {
+    let captured = events
+        .iter()
+        .any(|event| matches!(event, GameEvent::LeaderCaptured(..)));
+    if captured && !accessibility.reduce_motion && camera_settings.hit_stop_seconds > 0.0 {
+        hit_stop.duration = camera_settings.hit_stop_seconds;
+        hit_stop.remaining = camera_settings.hit_stop_seconds;
+        time.set_relative_speed(camera_settings.hit_stop_speed.clamp(0.0, 1.0));
+        return;
+    }
+    if hit_stop.remaining <= 0.0 {
+        return;
+    }
+    // Count down in real time so the slow down doesn't drag itself out.
+    hit_stop.remaining -= time.raw_delta_seconds();
+    let speed = match hit_stop.remaining > 0.0 {
+        true => hit_stop_speed(
+            camera_settings.hit_stop_speed.clamp(0.0, 1.0),
+            1.0 - hit_stop.remaining / hit_stop.duration,
+        ),
+        false => 1.0,
+    };
+    time.set_relative_speed(speed);
+}
+
+/// Offsets the cameras after the follow systems have placed them so the shake never builds up.
+pub fn shake_cameras(mut cameras: Query<(&mut CameraShake, &mut Transform)>, time: Res<Time>) {
+    let seconds = time.elapsed_seconds() * 20.0;
+    for (mut shake, mut transform) in cameras.iter_mut() {
+        let distance = shake.trauma.powi(2) * MAX_SHAKE_DISTANCE;
+        transform.translation.x += smooth_noise(seconds, 0.0) * distance;
+        transform.translation.y += smooth_noise(seconds, 10.0) * distance;
+        shake.trauma = (shake.trauma - TRAUMA_DECAY_PER_SECOND * time.delta_seconds()).max(0.0);
+    }
+}
+
+/// Eases the cameras over to frame the winning flock when the round ends, the game over dialog
+/// waits until it's done.
+#[derive(Resource, Debug, Default)]
+pub struct WinnerSweep {
+    /// Real seconds since the round ended
+    pub elapsed: f32,
+}
+
+/// Returns how far through the sweep to the winner the cameras are after `elapsed` seconds,
+/// easing in and out from 0.0 to 1.0.
+pub fn winner_sweep_progress(elapsed: f32) -> f32 {
+    let t = (elapsed / WINNER_SWEEP_SECONDS).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Starts the sweep to the winner, skipped when motion is reduced.
+pub fn start_winner_sweep(mut commands: Commands, accessibility: Res<AccessibilitySettings>) {
+    if !accessibility.reduce_motion {
+        commands.insert_resource(WinnerSweep::default());
+    }
+}
+
+pub fn stop_winner_sweep(mut commands: Commands) {
+    commands.remove_resource::<WinnerSweep>();
+}
+
+/// Used to hold the game over dialog back until the cameras have reached the winner.
+pub fn winner_sweep_done(sweep: Option<Res<WinnerSweep>>) -> bool {
+    sweep
+        .map(|sweep| sweep.elapsed >= WINNER_SWEEP_SECONDS)
+        .unwrap_or(true)
+}
+
+/// Moves the cameras from wherever they're following over to the winning flock, zooming to fit
+/// it the same way auto zoom fits a player's flock. The boids stop moving once the round is
+/// over so the cameras settle on the flock as it was when it won.
+#[allow(clippy::type_complexity)]
+pub fn sweep_to_winner(
+    sweep: Option<ResMut<WinnerSweep>>,
+    mut cameras: Query<
+        (&mut Transform, &mut OrthographicProjection),
+        (
+            Or<(With<Camera2dFollow>, With<Camera2dFollowMany>)>,
+            Without<Boid>,
+        ),
+    >,
+    boids: Query<(&Transform, &BoidColor), With<Boid>>,
+    winner: Option<Res<Winner>>,
+    census: Res<BoidCensus>,
+    time: Res<Time>,
+) {
+    let mut sweep = match sweep {
+        Some(sweep) => sweep,
+        None => return,
+    };
+    // Real time so a hit stop from the final capture doesn't drag the sweep out.
+    sweep.elapsed += time.raw_delta_seconds();
+    let (color, flock) = match winner.and_then(|w| census.flock(&w.color).map(|f| (w.color, f))) {
+        Some(winner) => winner,
+        None => return,
+    };
+    let spread = flock_spread(
+        flock.centroid,
+        boids
+            .iter()
+            .filter(|(_, c)| **c == color)
+            .map(|(t, _)| t.translation.truncate()),
+    );
+    let height = auto_zoom_height(spread, 1.0);
+    let t = winner_sweep_progress(sweep.elapsed);
     for (mut transform, mut projection) in cameras.iter_mut() {
-        let targets_center: Vec2 = targets.iter().map(|t| t.translation().truncate()).avg();
-        let max_distance: Option<f32> = targets
-            .iter_combinations::<2>()
-            .map(|[a, b]| a.translation().distance_squared(b.translation()))
-            .max_by(|a, b| a.total_cmp(b));
-        projection.scaling_mode = ScalingMode::FixedVertical(
-            max_distance
-                .map(|x| x.sqrt() + 500.0)
-                .unwrap_or(SCENE_HEIGHT),
-        );
-        transform.translation = targets_center.extend(transform.translation.z);
+        let position = transform.translation.truncate().lerp(flock.centroid, t);
+        transform.translation = position.extend(transform.translation.z);
+        if let ScalingMode::FixedVertical(x) = projection.scaling_mode {
+            projection.scaling_mode = ScalingMode::FixedVertical(x + (height - x) * t);
+        }
+    }
+}
+
+/// Returns the next living leader after the one with `current` color, going through the colors
+/// in order and wrapping around. Backwards goes the other way.
+pub fn next_leader(
+    current: Option<BoidColor>,
+    leaders: &[(Entity, BoidColor)],
+    forwards: bool,
+) -> Option<Entity> {
+    let mut leaders = leaders.to_vec();
+    leaders.sort_by_key(|(_, color)| color.index());
+    if !forwards {
+        leaders.reverse();
+    }
+    let after_current = |(_, color): &&(Entity, BoidColor)| match (current, forwards) {
+        (Some(current), true) => color.index() > current.index(),
+        (Some(current), false) => color.index() < current.index(),
+        (None, _) => true,
+    };
+    leaders
+        .iter()
+        .find(after_current)
+        .or_else(|| leaders.first())
+        .map(|(entity, _)| *entity)
+}
+
+/// Added to a camera when its player hands their flock to a bot to watch instead. Spectating
+/// cameras show which team they're following and can be cycled through the leaders.
+#[derive(Component, Debug)]
+pub struct Spectating;
+
+/// Lets spectators cycle the cameras through each team's leader. Every camera spectates once no
+/// humans are playing.
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+pub fn cycle_spectator_camera(
+    mut commands: Commands,
+    global_actions: Query<&ActionState<GlobalActions>>,
+    mut cameras: Query<
+        (Entity, &Transform, Option<&mut Camera2dFollow>),
+        (
+            With<Camera2d>,
+            Or<(With<Camera2dFollow>, With<Camera2dFollowMany>)>,
+        ),
+    >,
+    leaders: Query<(Entity, &BoidColor), With<Leader>>,
+    humans: Query<(), (With<Leader>, With<InputMap<PlayerActions>>)>,
+    spectating: Query<(), With<Spectating>>,
+    colors: Query<&BoidColor>,
+    transforms: Query<&GlobalTransform>,
+) {
+    let everyone_spectating = humans.is_empty();
+    if !everyone_spectating && spectating.is_empty() {
+        return;
+    }
+    let forwards = match global_actions.iter().find_map(|action_state| {
+        match (
+            action_state.just_pressed(GlobalActions::SpectateNext),
+            action_state.just_pressed(GlobalActions::SpectatePrevious),
+        ) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        }
+    }) {
+        Some(forwards) => forwards,
+        None => return,
+    };
+    let leaders: Vec<(Entity, BoidColor)> = leaders.iter().map(|(e, c)| (e, *c)).collect();
+    for (entity, transform, camera_follow) in cameras.iter_mut() {
+        if !everyone_spectating && !spectating.contains(entity) {
+            continue;
+        }
+        let current = camera_follow
+            .as_ref()
+            .and_then(|camera_follow| colors.get(camera_follow.target).ok())
+            .copied();
+        let target = match next_leader(current, &leaders, forwards) {
+            Some(target) => target,
+            None => return,
+        };
+        let target_position = transforms
+            .get(target)
+            .map(|t| t.translation().truncate())
+            .unwrap_or_default();
+        let cut_offset = transform.translation.truncate() - target_position;
+        match camera_follow {
+            Some(mut camera_follow) => {
+                camera_follow.cut_offset = cut_offset - camera_follow.offset;
+                camera_follow.target = target;
+                camera_follow.lead = Vec2::ZERO;
+            }
+            // Shared screen cameras switch from framing everyone to following one leader.
+            None => {
+                commands
+                    .entity(entity)
+                    .remove::<Camera2dFollowMany>()
+                    .insert(Camera2dFollow {
+                        cut_offset,
+                        ..Camera2dFollow::new(target)
+                    });
+            }
+        }
     }
 }
 
@@ -101,3 +880,509 @@ pub fn remove_camera_follow_target_on_capture(
         commands.entity(entity).remove::<CameraFollowTarget>();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use std::f32::consts::{PI, TAU};
+
+    #[test]
+    fn test_recenter_falls_back_to_global_actions_once_captured() {
+        let mut world = World::new();
+        let human = world
+            .spawn((
+                InputMap::<PlayerActions>::default(),
+                ActionState::<PlayerActions>::default(),
+            ))
+            .id();
+        // The input map is taken away when a leader is captured.
+        let captured = world.spawn(ActionState::<PlayerActions>::default()).id();
+        let camera_for = |world: &mut World, target: Entity| {
+            world
+                .spawn((
+                    Camera2dFollow::new(target),
+                    OrthographicProjection {
+                        scaling_mode: ScalingMode::FixedVertical(SCENE_HEIGHT * 2.0),
+                        ..default()
+                    },
+                ))
+                .id()
+        };
+        let human_camera = camera_for(&mut world, human);
+        let captured_camera = camera_for(&mut world, captured);
+        let mut global_actions = ActionState::<GlobalActions>::default();
+        global_actions.press(GlobalActions::RecenterCamera);
+        world.spawn(global_actions);
+
+        world.init_resource::<CameraSettings>();
+        world.init_resource::<Time>();
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(recenter_camera);
+        schedule.run(&mut world);
+
+        let recentering = |world: &World, camera: Entity| {
+            world.get::<Camera2dFollow>(camera).unwrap().recentering
+        };
+        assert!(recentering(&world, captured_camera));
+        // Players that are still playing use their own button.
+        assert!(!recentering(&world, human_camera));
+    }
+
+    #[test]
+    fn test_recenter_eases_back_onto_leader() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.insert_resource(CameraSettings {
+            lead_blend: 1.0,
+            ..default()
+        });
+        let leader_position = Vec3::new(100.0, 0.0, 0.0);
+        let mut action_state = ActionState::<PlayerActions>::default();
+        action_state.press(PlayerActions::RecenterCamera);
+        let leader = world
+            .spawn((
+                InputMap::<PlayerActions>::default(),
+                action_state,
+                Transform::from_translation(leader_position),
+                GlobalTransform::from_translation(leader_position),
+            ))
+            .id();
+        let lead = Vec2::new(0.0, 200.0);
+        let camera = world
+            .spawn((
+                Camera2d::default(),
+                Camera2dFollow {
+                    lead,
+                    ..Camera2dFollow::new(leader)
+                },
+                Transform::from_translation(leader_position + lead.extend(0.0)),
+                OrthographicProjection {
+                    scaling_mode: ScalingMode::FixedVertical(SCENE_HEIGHT * 2.0),
+                    ..default()
+                },
+            ))
+            .id();
+
+        let mut schedule = Schedule::new();
+        schedule.add_systems((recenter_camera, update_camera_follow_system).chain());
+        let start = std::time::Instant::now();
+        world.resource_mut::<Time>().update_with_instant(start);
+        let mut distances = Vec::new();
+        let mut heights = Vec::new();
+        for frame in 1..=5 {
+            world
+                .resource_mut::<Time>()
+                .update_with_instant(start + Duration::from_secs_f32(frame as f32 * 0.1));
+            schedule.run(&mut world);
+            // Only pressed on the first frame.
+            world
+                .entity_mut(leader)
+                .insert(ActionState::<PlayerActions>::default());
+            let translation = world.get::<Transform>(camera).unwrap().translation;
+            distances.push(translation.truncate().distance(leader_position.truncate()));
+            if let ScalingMode::FixedVertical(height) = world
+                .get::<OrthographicProjection>(camera)
+                .unwrap()
+                .scaling_mode
+            {
+                heights.push(height);
+            }
+        }
+
+        // Eases rather than jumping straight onto the leader.
+        assert!(distances[0] > 0.0 && distances[0] < lead.length());
+        assert!(distances.windows(2).all(|pair| pair[1] < pair[0]));
+        assert!(heights[0] > SCENE_HEIGHT && heights[0] < SCENE_HEIGHT * 2.0);
+        assert!(heights.windows(2).all(|pair| pair[1] < pair[0]));
+
+        for _ in 0..100 {
+            schedule.run(&mut world);
+        }
+        assert!(matches!(
+            world.get::<OrthographicProjection>(camera).unwrap().scaling_mode,
+            ScalingMode::FixedVertical(height) if height == SCENE_HEIGHT
+        ));
+        assert!(!world.get::<Camera2dFollow>(camera).unwrap().recentering);
+    }
+
+    #[test]
+    fn test_next_leader_wraps_and_skips_eliminated() {
+        let red = Entity::from_raw(1);
+        let green = Entity::from_raw(2);
+        let blue = Entity::from_raw(3);
+        let leaders = [
+            (blue, BoidColor::Blue),
+            (red, BoidColor::Red),
+            (green, BoidColor::Green),
+        ];
+        assert_eq!(next_leader(None, &leaders, true), Some(red));
+        assert_eq!(
+            next_leader(Some(BoidColor::Red), &leaders, true),
+            Some(green)
+        );
+        assert_eq!(
+            next_leader(Some(BoidColor::Blue), &leaders, true),
+            Some(red)
+        );
+        assert_eq!(
+            next_leader(Some(BoidColor::Red), &leaders, false),
+            Some(blue)
+        );
+
+        // Green was captured so it's skipped over.
+        let remaining = [(blue, BoidColor::Blue), (red, BoidColor::Red)];
+        assert_eq!(
+            next_leader(Some(BoidColor::Red), &remaining, true),
+            Some(blue)
+        );
+        assert_eq!(
+            next_leader(Some(BoidColor::Green), &remaining, true),
+            Some(blue)
+        );
+        assert_eq!(next_leader(Some(BoidColor::Red), &[], true), None);
+    }
+
+    #[test]
+    fn test_human_focus_biases_shared_camera() {
+        let targets = [
+            (Vec2::new(-100.0, 0.0), true),
+            (Vec2::new(100.0, 0.0), false),
+            (Vec2::new(300.0, 0.0), false),
+        ];
+        let center = |camera_settings: &CameraSettings| {
+            weighted_average(framed_targets(&targets, camera_settings).into_iter()).unwrap()
+        };
+        let uniform = center(&CameraSettings::default());
+        assert_relative_eq!(uniform.x, 100.0);
+
+        let focused = center(&CameraSettings {
+            human_focus_weight: 4.0,
+            ..default()
+        });
+        assert!(focused.x < uniform.x);
+        assert!(focused.x > -100.0);
+
+        // The far off bot is left out of the framing entirely.
+        let ignoring = framed_targets(
+            &targets,
+            &CameraSettings {
+                max_bot_framing_distance: 250.0,
+                ..default()
+            },
+        );
+        assert_eq!(ignoring.len(), 2);
+    }
+
+    #[test]
+    fn test_capture_dips_time_then_recovers() {
+        let mut world = World::new();
+        world.init_resource::<Events<GameEvent>>();
+        world.init_resource::<AccessibilitySettings>();
+        world.init_resource::<HitStop>();
+        world.init_resource::<Time>();
+        world.insert_resource(CameraSettings {
+            hit_stop_speed: 0.2,
+            hit_stop_seconds: 0.5,
+            ..default()
+        });
+        let mut schedule = Schedule::new();
+        schedule.add_system(update_hit_stop);
+        let start = std::time::Instant::now();
+        let mut run_at = |world: &mut World, seconds: f32| {
+            world
+                .resource_mut::<Time>()
+                .update_with_instant(start + Duration::from_secs_f32(seconds));
+            schedule.run(world);
+            world.resource::<Time>().relative_speed()
+        };
+
+        assert_eq!(run_at(&mut world, 0.0), 1.0);
+        world.send_event(GameEvent::LeaderCaptured(BoidColor::Red, BoidColor::Blue));
+        assert_relative_eq!(run_at(&mut world, 0.1), 0.2);
+        let recovering = run_at(&mut world, 0.35);
+        assert!(recovering > 0.2 && recovering < 1.0);
+        assert_eq!(run_at(&mut world, 0.7), 1.0);
+    }
+
+    #[test]
+    fn test_capture_flashes_only_the_capturers_viewport() {
+        use crate::round::{PlayerSettings, PlayerType};
+        use crate::MultiplayerMode;
+
+        let mut world = World::new();
+        world.init_resource::<Events<GameEvent>>();
+        world.init_resource::<AccessibilitySettings>();
+        world.init_resource::<CaptureFlashes>();
+        world.init_resource::<Time>();
+        world.insert_resource(RoundSettings {
+            players: vec![
+                PlayerSettings {
+                    player_type: PlayerType::Wasd,
+                    color: BoidColor::Red,
+                },
+                PlayerSettings {
+                    player_type: PlayerType::ArrowKeys,
+                    color: BoidColor::Blue,
+                },
+            ],
+            multiplayer_mode: MultiplayerMode::SplitScreenVertical,
+            ..default()
+        });
+        world.send_event(GameEvent::LeaderCaptured(BoidColor::Blue, BoidColor::Red));
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(update_capture_flashes);
+        schedule.run(&mut world);
+
+        let flashes = &world.resource::<CaptureFlashes>().0;
+        assert_eq!(flashes.len(), 1);
+        assert_eq!(flashes[0].color, BoidColor::Red);
+        let left = ViewportRelative::left();
+        assert_eq!(
+            (flashes[0].viewport.x, flashes[0].viewport.width),
+            (left.x, left.width)
+        );
+        assert_eq!(flashes[0].alpha(), 1.0);
+
+        world.resource_mut::<AccessibilitySettings>().reduce_motion = true;
+        world.resource_mut::<CaptureFlashes>().0.clear();
+        world.send_event(GameEvent::LeaderCaptured(BoidColor::Red, BoidColor::Blue));
+        schedule.run(&mut world);
+        assert!(world.resource::<CaptureFlashes>().0.is_empty());
+    }
+
+    #[test]
+    fn test_game_over_sweeps_to_winning_flock() {
+        use crate::stats::FlockMotion;
+
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<WinnerSweep>();
+        world.insert_resource(Winner {
+            color: BoidColor::Red,
+        });
+        let centroid = Vec2::new(300.0, 0.0);
+        let mut census = BoidCensus::default();
+        census.flocks.insert(
+            BoidColor::Red,
+            FlockMotion {
+                centroid,
+                ..default()
+            },
+        );
+        world.insert_resource(census);
+        for x in [290.0, 310.0] {
+            world.spawn((
+                Boid::default(),
+                BoidColor::Red,
+                Transform::from_xyz(x, 0.0, 0.0),
+            ));
+        }
+        let camera = world
+            .spawn((
+                Camera2dFollowMany::default(),
+                Transform::default(),
+                OrthographicProjection {
+                    scaling_mode: ScalingMode::FixedVertical(SCENE_HEIGHT),
+                    ..default()
+                },
+            ))
+            .id();
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(sweep_to_winner);
+        let start = std::time::Instant::now();
+        let mut camera_x_at = |world: &mut World, seconds: f32| {
+            world
+                .resource_mut::<Time>()
+                .update_with_instant(start + Duration::from_secs_f32(seconds));
+            schedule.run(world);
+            world.get::<Transform>(camera).unwrap().translation.x
+        };
+        camera_x_at(&mut world, 0.0);
+        let mut last_x = 0.0;
+        for seconds in [0.25, 0.5, 0.75] {
+            let x = camera_x_at(&mut world, seconds);
+            assert!(x > last_x && x < centroid.x);
+            last_x = x;
+        }
+        assert!(world.resource::<WinnerSweep>().elapsed < WINNER_SWEEP_SECONDS);
+        assert_relative_eq!(camera_x_at(&mut world, 1.1), centroid.x);
+    }
+
+    #[test]
+    fn test_velocity_lead() {
+        assert_eq!(velocity_lead(Vec2::Y, 120.0, 0.5), Vec2::new(0.0, 60.0));
+        assert_eq!(
+            velocity_lead(Vec2::X * 3.0, 100.0, 0.5),
+            Vec2::new(50.0, 0.0)
+        );
+        assert_eq!(velocity_lead(Vec2::Y, 120.0, 0.0), Vec2::ZERO);
+    }
+
+    #[test]
+    fn test_lead_blend_goes_from_centered_to_fully_led() {
+        let target = Vec2::new(100.0, 50.0);
+        let lead = velocity_lead(Vec2::Y, 120.0, 0.5);
+        assert_eq!(
+            blended_follow_position(target, Vec2::ZERO, lead, 0.0),
+            target
+        );
+        assert_eq!(
+            blended_follow_position(target, Vec2::ZERO, lead, 1.0),
+            target + lead
+        );
+        assert_eq!(
+            blended_follow_position(target, Vec2::ZERO, lead, 0.5),
+            target + lead * 0.5
+        );
+    }
+
+    #[test]
+    fn test_reduce_motion_disables_capture_shake() {
+        let mut world = World::new();
+        world.init_resource::<Events<GameEvent>>();
+        world.insert_resource(AccessibilitySettings {
+            reduce_motion: true,
+            ..default()
+        });
+        let camera = world.spawn(CameraShake::default()).id();
+        world
+            .resource_mut::<Events<GameEvent>>()
+            .send(GameEvent::LeaderCaptured(
+                crate::BoidColor::Red,
+                crate::BoidColor::Blue,
+            ));
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(add_capture_trauma);
+        schedule.run(&mut world);
+        assert_eq!(world.get::<CameraShake>(camera).unwrap().trauma, 0.0);
+
+        world.resource_mut::<AccessibilitySettings>().reduce_motion = false;
+        world
+            .resource_mut::<Events<GameEvent>>()
+            .send(GameEvent::LeaderCaptured(
+                crate::BoidColor::Red,
+                crate::BoidColor::Blue,
+            ));
+        schedule.run(&mut world);
+        assert_eq!(
+            world.get::<CameraShake>(camera).unwrap().trauma,
+            CAPTURE_TRAUMA
+        );
+    }
+
+    #[test]
+    fn test_chase_rotation_approaches_target_heading() {
+        let target = Quat::from_rotation_z(1.5);
+        let mut rotation = Quat::IDENTITY;
+        let mut last_angle = rotation.angle_between(target);
+        for _ in 0..120 {
+            rotation = chase_rotation(rotation, target, 1.0 / 60.0);
+            let angle = rotation.angle_between(target);
+            assert!(angle < last_angle);
+            last_angle = angle;
+        }
+        assert!(last_angle < 0.3);
+    }
+
+    #[test]
+    fn test_cursor_to_world() {
+        let viewport_min = Vec2::new(100.0, 0.0);
+        let viewport_size = Vec2::new(400.0, 200.0);
+        let view_size = Vec2::new(1000.0, 500.0);
+        let camera = Transform::from_xyz(50.0, -20.0, 10.0);
+        let world =
+            |cursor| cursor_to_world(cursor, viewport_min, viewport_size, view_size, &camera);
+
+        assert_eq!(world(Vec2::new(300.0, 100.0)), Some(Vec2::new(50.0, -20.0)));
+        assert_eq!(world(Vec2::new(100.0, 0.0)), Some(Vec2::new(-450.0, 230.0)));
+        assert_eq!(
+            world(Vec2::new(500.0, 200.0)),
+            Some(Vec2::new(550.0, -270.0))
+        );
+        assert_eq!(world(Vec2::new(50.0, 100.0)), None);
+
+        // Chase cameras are rotated so up on screen is the way the camera faces.
+        let rotated = Transform::from_rotation(Quat::from_rotation_z(PI / 2.0));
+        let top = cursor_to_world(
+            Vec2::new(300.0, 0.0),
+            viewport_min,
+            viewport_size,
+            view_size,
+            &rotated,
+        )
+        .unwrap();
+        assert_relative_eq!(top.x, -250.0, epsilon = 0.001);
+        assert_relative_eq!(top.y, 0.0, epsilon = 0.001);
+    }
+
+    #[test]
+    fn test_scattered_flock_zooms_out_further() {
+        let leader = Vec2::ZERO;
+        let ring =
+            |radius: f32| (0..12).map(move |i| Vec2::from_angle(i as f32 / 12.0 * TAU) * radius);
+        let tight = auto_zoom_height(flock_spread(leader, ring(20.0)), 1.0);
+        let scattered = auto_zoom_height(flock_spread(leader, ring(200.0)), 1.0);
+        assert!(scattered > tight);
+        assert!(tight >= MIN_CAMERA_HEIGHT);
+        assert!(scattered <= MAX_CAMERA_HEIGHT);
+        assert_eq!(
+            auto_zoom_height(flock_spread(leader, ring(200.0)), 0.0),
+            SCENE_HEIGHT
+        );
+    }
+
+    #[test]
+    fn test_follow_many_height_zoom() {
+        assert_eq!(follow_many_height(Some(100.0), 0.0, None), 600.0);
+        assert_eq!(follow_many_height(Some(100.0), 200.0, None), 800.0);
+        assert_eq!(follow_many_height(None, 0.0, None), SCENE_HEIGHT);
+        assert_eq!(
+            follow_many_height(Some(0.0), -MAX_FOLLOW_MANY_ZOOM, None),
+            MIN_CAMERA_HEIGHT
+        );
+    }
+
+    #[test]
+    fn test_single_outlier_doesnt_zoom_out_shared_camera() {
+        let outlier_distance = 1000.0;
+        let group = vec![
+            (Vec2::new(-100.0, 0.0), 1.0),
+            (Vec2::new(100.0, 0.0), 1.0),
+            (Vec2::new(0.0, 150.0), 1.0),
+        ];
+        let mut with_outlier = group.clone();
+        with_outlier.push((Vec2::new(20000.0, 0.0), 1.0));
+
+        let spread = framed_spread(&without_outlier(with_outlier.clone(), outlier_distance));
+        assert_eq!(spread, framed_spread(&group));
+        assert!(spread.unwrap() <= outlier_distance);
+        assert!(follow_many_height(spread, 0.0, None) <= outlier_distance + 500.0);
+
+        // Turned off, or with just two players, everyone is still framed.
+        assert_eq!(without_outlier(with_outlier.clone(), 0.0), with_outlier);
+        let pair = vec![(Vec2::ZERO, 1.0), (Vec2::new(20000.0, 0.0), 1.0)];
+        assert_eq!(without_outlier(pair.clone(), outlier_distance), pair);
+    }
+
+    #[test]
+    fn test_far_target_gets_edge_indicator_instead_of_zoom() {
+        let height = follow_many_height(Some(3000.0), 0.0, Some(1000.0));
+        assert_eq!(height, 1000.0);
+
+        let half_view = Vec2::splat(height / 2.0);
+        let far_target = Vec2::new(3000.0, 0.0);
+        assert_eq!(
+            edge_indicator(-half_view, half_view, far_target, 20.0),
+            Some(Vec2::new(480.0, 0.0))
+        );
+        assert_eq!(
+            edge_indicator(-half_view, half_view, Vec2::new(100.0, 0.0), 20.0),
+            None
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{Boid, BoidColor};
+use bevy::prelude::*;
+
+/// Positions are rounded to this many steps per unit before hashing, so tiny float differences
+/// between machines don't count as a desync.
+const POSITION_STEPS_PER_UNIT: f32 = 16.0;
+/// Headings are rounded to this many steps per radian before hashing.
+const HEADING_STEPS_PER_RADIAN: f32 = 256.0;
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// A checksum of where every boid is, which way it's facing and what color it is, updated every
+/// frame. Two games started from the same seed should produce the same checksums, peers can
+/// compare them to spot when they've drifted apart.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StateChecksum {
+    /// How many times the checksum has been updated
+    pub tick: u64,
+    pub checksum: u64,
+}
+
+/// The rounded state of a single boid that goes into the checksum.
+fn quantize(transform: &Transform, color: Option<&BoidColor>) -> [i64; 4] {
+    let position = transform.translation.truncate() * POSITION_STEPS_PER_UNIT;
+    let heading = transform.rotation.to_euler(EulerRot::XYZ).2 * HEADING_STEPS_PER_RADIAN;
+    [
+        position.x.round() as i64,
+        position.y.round() as i64,
+        heading.round() as i64,
+        color.map(|c| c.index() as i64).unwrap_or(-1),
+    ]
+}
+
+/// Hashes the boids in a way that doesn't depend on their order, the platform or the Rust
+/// version, unlike the standard library's hasher.
+pub fn state_checksum<'a>(
+    boids: impl Iterator<Item = (&'a Transform, Option<&'a BoidColor>)>,
+) -> u64 {
+    let mut boids: Vec<[i64; 4]> = boids
+        .map(|(transform, color)| quantize(transform, color))
+        .collect();
+    boids.sort_unstable();
+    // FNV-1a
+    boids
+        .iter()
+        .flatten()
+        .flat_map(|value| value.to_le_bytes())
+        .fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+        })
+}
+
+pub fn update_state_checksum(
+    mut checksum: ResMut<StateChecksum>,
+    query: Query<(&Transform, Option<&BoidColor>), With<Boid>>,
+) {
+    checksum.tick += 1;
+    checksum.checksum = state_checksum(query.iter());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::steering_systems;
+    use crate::boids::{
+        clear_inputs, propagate_boid_color, push_apart_boids, spawn_positions,
+        update_boid_neighbors, update_quad_tree, GameEnded,
+    };
+    use crate::offscreen::SteeringFrame;
+    use crate::streaks::CaptureStreaks;
+    use crate::{
+        spawn_boid_facing, update_boid_transforms, BoidSettings, GameEvent, Leader, RoundSeed,
+        RoundSettings,
+    };
+    use bevy::ecs::system::SystemState;
+    use bevy_prototype_debug_lines::DebugLines;
+    use std::f32::consts::TAU;
+    use std::time::{Duration, Instant};
+    use turborand::prelude::*;
+
+    /// Runs a small match without rendering anything and returns the checksum after every tick.
+    /// Runs the same simulation systems as the game, from finding neighbors to captures.
+    fn headless_checksums(seed: u64, ticks: u32) -> Vec<u64> {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<DebugLines>();
+        world.init_resource::<CaptureStreaks>();
+        world.init_resource::<StateChecksum>();
+        world.init_resource::<GameEnded>();
+        world.init_resource::<SteeringFrame>();
+        world.init_resource::<Events<GameEvent>>();
+        world.insert_resource(RoundSeed(seed));
+        world.insert_resource(BoidSettings {
+            separation_push_enabled: true,
+            ..default()
+        });
+        world.insert_resource(RoundSettings::default());
+
+        let rand = Rng::with_seed(seed);
+        // Packed in tight so boids find each other and get captured within a few ticks.
+        let mut system_state: SystemState<Commands> = SystemState::new(&mut world);
+        let mut commands = system_state.get_mut(&mut world);
+        for (i, position) in spawn_positions(50, 150.0, 0.0, &rand)
+            .into_iter()
+            .enumerate()
+        {
+            let entity =
+                spawn_boid_facing(&mut commands, Handle::default(), position, rand.f32() * TAU);
+            match i % 3 {
+                0 => commands.entity(entity).insert(BoidColor::Red),
+                1 => commands.entity(entity).insert(BoidColor::Blue),
+                _ => continue,
+            };
+            if i < 2 {
+                commands.entity(entity).insert(Leader);
+            }
+        }
+        system_state.apply(&mut world);
+
+        let mut schedule = Schedule::new();
+        schedule.add_systems(
+            (
+                update_quad_tree,
+                apply_system_buffers,
+                update_boid_neighbors,
+            )
+                .chain(),
+        );
+        schedule.add_systems(
+            steering_systems()
+                .after(update_boid_neighbors)
+                .before(propagate_boid_color),
+        );
+        schedule.add_systems(
+            (
+                propagate_boid_color,
+                update_boid_transforms,
+                push_apart_boids,
+                update_state_checksum,
+                clear_inputs,
+            )
+                .chain()
+                .after(update_boid_neighbors),
+        );
+        let start = Instant::now();
+        (0..ticks)
+            .map(|tick| {
+                world
+                    .resource_mut::<Time>()
+                    .update_with_instant(start + Duration::from_millis(16 * tick as u64));
+                schedule.run(&mut world);
+                world.resource::<StateChecksum>().checksum
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_same_seed_runs_have_matching_checksums() {
+        let first = headless_checksums(42, 20);
+        assert_eq!(first, headless_checksums(42, 20));
+        // The boids are moving so every tick should be different.
+        assert!(first.windows(2).all(|pair| pair[0] != pair[1]));
+        assert_ne!(first, headless_checksums(7, 20));
+    }
+}
@@ -0,0 +1,145 @@
+use crate::stats::BoidCensus;
+use crate::{BoidColor, GameEnded, GameEvent, RoundSettings, Winner};
+use bevy::prelude::*;
+use itertools::Itertools;
+
+/// How long the color with the biggest share of the boids has held on to it in control mode.
+#[derive(Resource, Debug, Default)]
+pub struct ControlTimer {
+    pub color: Option<BoidColor>,
+    pub held_seconds: f32,
+}
+
+impl ControlTimer {
+    /// Adds `delta_seconds` to the hold time of the color in control, starting over when a
+    /// different color takes control or nobody has it. Returns the color once it's held control
+    /// for `required_seconds`.
+    pub fn tick(
+        &mut self,
+        in_control: Option<BoidColor>,
+        delta_seconds: f32,
+        required_seconds: f32,
+    ) -> Option<BoidColor> {
+        match in_control {
+            Some(color) if self.color == Some(color) => self.held_seconds += delta_seconds,
+            _ => {
+                self.color = in_control;
+                self.held_seconds = 0.0;
+            }
+        }
+        match self.held_seconds >= required_seconds {
+            true => self.color,
+            false => None,
+        }
+    }
+}
+
+/// Returns the color that has at least `share` of all the boids, counting boids that haven't
+/// joined a flock yet. Nobody is in control when the biggest flocks are tied.
+pub fn color_in_control(census: &BoidCensus, share: f32) -> Option<BoidColor> {
+    let total = census.total();
+    if total == 0 {
+        return None;
+    }
+    let mut largest = census
+        .counts
+        .iter()
+        .filter(|(_, count)| **count as f32 / total as f32 >= share)
+        .max_set_by_key(|(_, count)| **count);
+    match largest.len() {
+        1 => largest.pop().map(|(color, _)| *color),
+        _ => None,
+    }
+}
+
+/// Ends a control round once a color has held the required share of the boids for long enough.
+pub fn update_control(
+    mut timer: ResMut<ControlTimer>,
+    mut game_ended: ResMut<GameEnded>,
+    mut event_writer: EventWriter<GameEvent>,
+    census: Res<BoidCensus>,
+    round_settings: Res<RoundSettings>,
+    time: Res<Time>,
+) {
+    if !round_settings.control_mode || game_ended.0 {
+        return;
+    }
+    let in_control = color_in_control(&census, round_settings.control_share);
+    if let Some(color) = timer.tick(
+        in_control,
+        time.delta_seconds(),
+        round_settings.control_seconds,
+    ) {
+        game_ended.0 = true;
+        event_writer.send(GameEvent::GameOver(Some(Winner { color })));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_tied_flocks_arent_in_control() {
+        let census = |red: usize, blue: usize| BoidCensus {
+            counts: [(BoidColor::Red, red), (BoidColor::Blue, blue)]
+                .into_iter()
+                .collect(),
+            ..default()
+        };
+        // Both have half, picking one would depend on the map's order.
+        for _ in 0..10 {
+            assert_eq!(color_in_control(&census(50, 50), 0.5), None);
+        }
+        assert_eq!(color_in_control(&census(51, 49), 0.5), Some(BoidColor::Red));
+        assert_eq!(color_in_control(&census(0, 0), 0.5), None);
+    }
+
+    #[test]
+    fn test_holding_control_wins_but_spikes_dont() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<ControlTimer>();
+        world.init_resource::<GameEnded>();
+        world.init_resource::<BoidCensus>();
+        world.init_resource::<Events<GameEvent>>();
+        world.insert_resource(RoundSettings {
+            control_mode: true,
+            control_share: 0.6,
+            control_seconds: 5.0,
+            ..default()
+        });
+        let mut schedule = Schedule::new();
+        schedule.add_system(update_control);
+        let start = Instant::now();
+        let mut game_over_at = |world: &mut World, seconds: u64, red: usize, blue: usize| {
+            world.resource_mut::<BoidCensus>().counts =
+                [(BoidColor::Red, red), (BoidColor::Blue, blue)]
+                    .into_iter()
+                    .collect();
+            world
+                .resource_mut::<Time>()
+                .update_with_instant(start + Duration::from_secs(seconds));
+            schedule.run(world);
+            world
+                .resource_mut::<Events<GameEvent>>()
+                .drain()
+                .find(|event| matches!(event, GameEvent::GameOver(_)))
+        };
+
+        // Red spikes over 60% a couple of times but never for long enough.
+        for (seconds, red) in [(0, 70), (3, 70), (4, 50), (5, 70), (8, 70), (9, 40)] {
+            assert_eq!(game_over_at(&mut world, seconds, red, 100 - red), None);
+        }
+        for seconds in 10..15 {
+            assert_eq!(game_over_at(&mut world, seconds, 65, 35), None);
+        }
+        assert_eq!(
+            game_over_at(&mut world, 15, 65, 35),
+            Some(GameEvent::GameOver(Some(Winner {
+                color: BoidColor::Red
+            })))
+        );
+    }
+}
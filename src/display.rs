@@ -0,0 +1,109 @@
+use crate::store::SettingsFile;
+use bevy::prelude::*;
+use bevy::window::{WindowMode, WindowResolution};
+use serde::{Deserialize, Serialize};
+
+/// The window modes that can be saved, kept separate from Bevy's so the file doesn't change
+/// when Bevy does.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum SavedWindowMode {
+    #[default]
+    Windowed,
+    BorderlessFullscreen,
+    SizedFullscreen,
+    Fullscreen,
+}
+
+impl From<WindowMode> for SavedWindowMode {
+    fn from(mode: WindowMode) -> Self {
+        match mode {
+            WindowMode::Windowed => Self::Windowed,
+            WindowMode::BorderlessFullscreen => Self::BorderlessFullscreen,
+            WindowMode::SizedFullscreen => Self::SizedFullscreen,
+            WindowMode::Fullscreen => Self::Fullscreen,
+        }
+    }
+}
+
+/// The window mode and size the player picked in the settings menu, remembered between launches.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplaySettings {
+    pub window_mode: SavedWindowMode,
+    /// Only used by the custom size fullscreen mode
+    pub window_width: f32,
+    pub window_height: f32,
+    /// How much the camera looks ahead of the player's leader, see `CameraSettings::lead_blend`
+    pub camera_lead: f32,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            window_mode: SavedWindowMode::Windowed,
+            window_width: 1280.0,
+            window_height: 800.0,
+            camera_lead: 0.0,
+        }
+    }
+}
+
+impl DisplaySettings {
+    /// The window mode to use on this platform. Browsers can only make the canvas fill the
+    /// screen, so every fullscreen mode is borderless on the web.
+    pub fn window_mode(&self) -> WindowMode {
+        match self.window_mode {
+            SavedWindowMode::Windowed => WindowMode::Windowed,
+            #[cfg(target_arch = "wasm32")]
+            _ => WindowMode::BorderlessFullscreen,
+            #[cfg(not(target_arch = "wasm32"))]
+            SavedWindowMode::BorderlessFullscreen => WindowMode::BorderlessFullscreen,
+            #[cfg(not(target_arch = "wasm32"))]
+            SavedWindowMode::SizedFullscreen => WindowMode::SizedFullscreen,
+            #[cfg(not(target_arch = "wasm32"))]
+            SavedWindowMode::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+
+    pub fn apply_to_window(&self, window: &mut Window) {
+        let mode = self.window_mode();
+        if window.mode != mode {
+            window.mode = mode;
+        }
+        if mode == WindowMode::SizedFullscreen {
+            window.resolution = WindowResolution::new(self.window_width, self.window_height);
+        }
+    }
+}
+
+impl SettingsFile for DisplaySettings {
+    const FILE: &'static str = "display.ron";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{from_ron, to_ron};
+
+    #[test]
+    fn test_round_trip() {
+        let settings = DisplaySettings {
+            window_mode: SavedWindowMode::SizedFullscreen,
+            window_width: 1920.0,
+            window_height: 1080.0,
+            camera_lead: 0.5,
+        };
+        assert_eq!(
+            from_ron::<DisplaySettings>(&to_ron(&settings).unwrap()).unwrap(),
+            settings
+        );
+        // Files saved before a setting existed still load.
+        assert_eq!(
+            from_ron::<DisplaySettings>("(window_mode: BorderlessFullscreen)").unwrap(),
+            DisplaySettings {
+                window_mode: SavedWindowMode::BorderlessFullscreen,
+                ..default()
+            }
+        );
+    }
+}
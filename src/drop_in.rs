@@ -0,0 +1,377 @@
+use crate::camera::{
+    Camera2dFollow, Camera2dFollowMany, CameraFollowTarget, CameraSettings, Spectating,
+};
+use crate::round::MultiplayerMode;
+use crate::viewports::{PlayerViewports, ViewportLayoutPreference, ViewportRelative};
+use crate::{
+    spawn_player_camera, BoidAveragedInputs, BoidSettings, Bot, Invulnerable, Leader,
+    PlayerActions, PlayerType, RoundSettings, SceneRoot,
+};
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Sent when someone presses the join button on a device, they take over a bot if the device
+/// isn't playing yet or hand their leader back to the bot if it is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DropInEvent(pub PlayerType);
+
+/// Added to a leader a player took over from a bot.
+#[derive(Component, Debug)]
+pub struct DroppedIn {
+    pub device: PlayerType,
+    /// The bot to give control back to when the player drops out
+    pub bot: Bot,
+}
+
+pub fn detect_drop_in(
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    round_settings: Res<RoundSettings>,
+    mut event_writer: EventWriter<DropInEvent>,
+) {
+    if !round_settings.drop_in {
+        return;
+    }
+    if keys.just_pressed(KeyCode::Tab) {
+        event_writer.send(DropInEvent(PlayerType::Wasd));
+    }
+    // Alt+Enter toggles fullscreen.
+    if keys.just_pressed(KeyCode::Return) && !keys.any_pressed([KeyCode::LAlt, KeyCode::RAlt]) {
+        event_writer.send(DropInEvent(PlayerType::ArrowKeys));
+    }
+    for gamepad in gamepads.iter() {
+        if buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::West)) {
+            event_writer.send(DropInEvent(PlayerType::GamePad(Some(gamepad))));
+        }
+    }
+}
+
+/// Returns the bot leader closest to `point`.
+pub fn nearest_bot_leader(
+    point: Vec2,
+    leaders: impl Iterator<Item = (Entity, Vec2)>,
+) -> Option<Entity> {
+    leaders
+        .min_by(|(_, a), (_, b)| {
+            a.distance_squared(point)
+                .total_cmp(&b.distance_squared(point))
+        })
+        .map(|(entity, _)| entity)
+}
+
+#[allow(clippy::type_complexity)]
+pub fn handle_drop_in(
+    mut commands: Commands,
+    mut events: EventReader<DropInEvent>,
+    bot_leaders: Query<(Entity, &GlobalTransform, &Bot), With<Leader>>,
+    dropped_in: Query<(Entity, &DroppedIn), With<Leader>>,
+    cameras: Query<&GlobalTransform, Or<(With<Camera2dFollow>, With<Camera2dFollowMany>)>>,
+    boid_settings: Res<BoidSettings>,
+    round_settings: Res<RoundSettings>,
+) {
+    // Join near whatever the first camera is looking at.
+    let center = cameras
+        .iter()
+        .next()
+        .map(|t| t.translation().truncate())
+        .unwrap_or_default();
+    let mut taken = Vec::new();
+    // Devices that joined this frame, their DroppedIn hasn't been added yet.
+    let mut joined: Vec<PlayerType> = Vec::new();
+    for DropInEvent(device) in events.iter() {
+        if let Some((entity, dropped_in)) = dropped_in.iter().find(|(_, d)| d.device == *device) {
+            let mut entity_commands = commands.entity(entity);
+            entity_commands
+                .remove::<(InputMap<PlayerActions>, DroppedIn, CameraFollowTarget)>()
+                // Clear any buttons that were held so they don't get stuck on.
+                .insert(ActionState::<PlayerActions>::default())
+                .insert(BoidAveragedInputs::default());
+            dropped_in.bot.insert(&mut entity_commands);
+            continue;
+        }
+        let in_use = round_settings
+            .players
+            .iter()
+            .map(|player| &player.player_type)
+            .chain(dropped_in.iter().map(|(_, d)| &d.device))
+            .chain(joined.iter())
+            .any(|player_type| player_type.shares_device(device));
+        if in_use {
+            info!("Can't drop in with {device}, it's already playing");
+            continue;
+        }
+
+        let available = bot_leaders
+            .iter()
+            .filter(|(entity, ..)| !taken.contains(entity))
+            .map(|(entity, transform, _)| (entity, transform.translation().truncate()));
+        let (entity, input_map) = match (nearest_bot_leader(center, available), device.input_map())
+        {
+            (Some(entity), Some(input_map)) => (entity, input_map),
+            _ => continue,
+        };
+        let bot = *bot_leaders.get(entity).unwrap().2;
+        taken.push(entity);
+        joined.push(*device);
+        let mut entity_commands = commands.entity(entity);
+        Bot::remove(&mut entity_commands);
+        entity_commands
+            .insert(input_map)
+            .insert(BoidAveragedInputs::default())
+            .insert(CameraFollowTarget)
+            .insert(Invulnerable::new(
+                boid_settings.respawn_invulnerability_seconds,
+            ))
+            .insert(DroppedIn {
+                device: *device,
+                bot,
+            });
+    }
+}
+
+/// Gives players that dropped in a view of their own and takes it away again when they leave,
+/// laying the split screen out again each time. Shared screen cameras already frame them.
+#[allow(clippy::too_many_arguments)]
+pub fn layout_drop_in_cameras(
+    mut commands: Commands,
+    joined: Query<Entity, Added<DroppedIn>>,
+    mut left: RemovedComponents<DroppedIn>,
+    mut player_cameras: Query<(
+        Entity,
+        &mut Camera2dFollow,
+        &mut ViewportRelative,
+        &mut Camera,
+    )>,
+    shared_cameras: Query<(), With<Camera2dFollowMany>>,
+    humans: Query<(), With<InputMap<PlayerActions>>>,
+    scene_root: Query<Entity, With<SceneRoot>>,
+    round_settings: Res<RoundSettings>,
+    camera_settings: Res<CameraSettings>,
+) {
+    let left: Vec<Entity> = left.iter().collect();
+    if !shared_cameras.is_empty() || (joined.is_empty() && left.is_empty()) {
+        return;
+    }
+    let mut cameras: Vec<(Entity, Entity, isize)> = player_cameras
+        .iter()
+        .map(|(camera, follow, _, c)| (camera, follow.target, c.order))
+        .collect();
+    cameras.sort_by_key(|(.., order)| *order);
+
+    // The last view is kept to spectate from once everyone has left.
+    for target in left {
+        if let Some(i) = cameras.iter().position(|(_, t, _)| *t == target) {
+            if cameras.len() > 1 {
+                commands.entity(cameras[i].0).despawn_recursive();
+                cameras.remove(i);
+            }
+        }
+    }
+
+    let mut new_targets = Vec::new();
+    for target in joined.iter() {
+        // Take over a view that's spectating a bot before making a new one.
+        let spectating = cameras
+            .iter()
+            .find(|(_, t, _)| !humans.contains(*t) || *t == target)
+            .map(|(camera, ..)| *camera);
+        match spectating.and_then(|camera| player_cameras.get_mut(camera).ok()) {
+            Some((camera, mut follow, ..)) => {
+                follow.target = target;
+                commands.entity(camera).remove::<Spectating>();
+                if let Some(entry) = cameras.iter_mut().find(|(c, ..)| *c == camera) {
+                    entry.1 = target;
+                }
+            }
+            None => new_targets.push(target),
+        }
+    }
+
+    let viewports = PlayerViewports::new(
+        (cameras.len() + new_targets.len()) as u8,
+        match round_settings.multiplayer_mode {
+            MultiplayerMode::SplitScreenVertical => ViewportLayoutPreference::Vertical,
+            _ => ViewportLayoutPreference::Horizontal,
+        },
+        2.0,
+    );
+    for (viewport_id, (camera, ..)) in cameras.iter().enumerate() {
+        if let Ok((.., mut viewport, mut camera)) = player_cameras.get_mut(*camera) {
+            *viewport = viewports.get(viewport_id);
+            camera.order = (1000 + viewport_id) as isize;
+        }
+    }
+    for (i, target) in new_targets.into_iter().enumerate() {
+        let viewport_id = cameras.len() + i;
+        let camera = spawn_player_camera(
+            &mut commands,
+            target,
+            viewport_id,
+            viewports.get(viewport_id),
+            &camera_settings,
+        );
+        if let Ok(root) = scene_root.get_single() {
+            commands.entity(root).add_child(camera);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::bots::hunter::Hunter;
+    use crate::round::PlayerSettings;
+    use crate::BoidColor;
+
+    fn bots_only() -> RoundSettings {
+        RoundSettings {
+            players: Vec::new(),
+            ..default()
+        }
+    }
+
+    #[test]
+    fn test_join_takes_over_bot_and_leave_gives_it_back() {
+        let mut world = World::new();
+        world.init_resource::<Events<DropInEvent>>();
+        world.init_resource::<BoidSettings>();
+        world.insert_resource(bots_only());
+        let leader = world
+            .spawn((
+                Leader,
+                Bot::Hunter,
+                Hunter::default(),
+                GlobalTransform::default(),
+            ))
+            .id();
+        let mut schedule = Schedule::new();
+        schedule.add_system(handle_drop_in);
+
+        world
+            .resource_mut::<Events<DropInEvent>>()
+            .send(DropInEvent(PlayerType::Wasd));
+        schedule.run(&mut world);
+        assert_eq!(
+            world.get::<InputMap<PlayerActions>>(leader),
+            PlayerType::Wasd.input_map().as_ref()
+        );
+        assert!(world.get::<Bot>(leader).is_none());
+        assert!(world.get::<Hunter>(leader).is_none());
+
+        world
+            .resource_mut::<Events<DropInEvent>>()
+            .send(DropInEvent(PlayerType::Wasd));
+        schedule.run(&mut world);
+        assert!(world.get::<InputMap<PlayerActions>>(leader).is_none());
+        assert_eq!(world.get::<Bot>(leader), Some(&Bot::Hunter));
+        assert!(world.get::<Hunter>(leader).is_some());
+    }
+
+    #[test]
+    fn test_cant_join_with_a_device_thats_playing() {
+        let mut world = World::new();
+        world.init_resource::<Events<DropInEvent>>();
+        world.init_resource::<BoidSettings>();
+        let gamepad = Gamepad { id: 0 };
+        world.insert_resource(RoundSettings {
+            players: vec![PlayerSettings {
+                player_type: PlayerType::GamePad(Some(gamepad)),
+                color: BoidColor::Red,
+            }],
+            ..default()
+        });
+        let bots: Vec<Entity> = (0..2)
+            .map(|_| {
+                world
+                    .spawn((Leader, Bot::Hunter, GlobalTransform::default()))
+                    .id()
+            })
+            .collect();
+        let mut schedule = Schedule::new();
+        schedule.add_system(handle_drop_in);
+
+        // Pressing join on the same gamepad twice in a frame can't take two bots either.
+        for device in [
+            PlayerType::GamePad(Some(gamepad)),
+            PlayerType::GamePad(None),
+            PlayerType::AnyDevice,
+        ] {
+            world
+                .resource_mut::<Events<DropInEvent>>()
+                .send(DropInEvent(device));
+        }
+        schedule.run(&mut world);
+        for bot in bots.iter() {
+            assert!(world.get::<DroppedIn>(*bot).is_none());
+        }
+
+        world
+            .resource_mut::<Events<DropInEvent>>()
+            .send(DropInEvent(PlayerType::Wasd));
+        world
+            .resource_mut::<Events<DropInEvent>>()
+            .send(DropInEvent(PlayerType::Wasd));
+        schedule.run(&mut world);
+        let joined = bots
+            .iter()
+            .filter(|bot| world.get::<DroppedIn>(**bot).is_some())
+            .count();
+        assert_eq!(joined, 1);
+    }
+
+    #[test]
+    fn test_joining_splits_the_screen() {
+        let mut world = World::new();
+        world.insert_resource(bots_only());
+        world.init_resource::<CameraSettings>();
+        let human = world.spawn(PlayerType::Wasd.input_map().unwrap()).id();
+        let camera = world
+            .spawn((
+                Camera2dFollow::new(human),
+                ViewportRelative::fullscreen(),
+                Camera::default(),
+            ))
+            .id();
+        let joiner = world.spawn(PlayerType::ArrowKeys.input_map().unwrap()).id();
+        let mut schedule = Schedule::new();
+        schedule.add_system(layout_drop_in_cameras);
+        schedule.run(&mut world);
+
+        world.entity_mut(joiner).insert(DroppedIn {
+            device: PlayerType::ArrowKeys,
+            bot: Bot::Hunter,
+        });
+        schedule.run(&mut world);
+        let mut cameras = world.query::<(&Camera2dFollow, &ViewportRelative)>();
+        let views: Vec<(Entity, ViewportRelative)> = cameras
+            .iter(&world)
+            .map(|(follow, viewport)| (follow.target, *viewport))
+            .collect();
+        assert_eq!(views.len(), 2);
+        assert!(views.iter().any(|(target, _)| *target == joiner));
+        assert!(views
+            .iter()
+            .all(|(_, viewport)| viewport.width * viewport.height < 1.0));
+
+        // Leaving gives the screen back.
+        world.entity_mut(joiner).remove::<DroppedIn>();
+        schedule.run(&mut world);
+        assert_eq!(cameras.iter(&world).count(), 1);
+        let viewport = world.get::<ViewportRelative>(camera).unwrap();
+        assert!(viewport.width * viewport.height >= 1.0);
+    }
+
+    #[test]
+    fn test_nearest_bot_leader() {
+        let leaders = [
+            (Entity::from_raw(1), Vec2::new(100.0, 0.0)),
+            (Entity::from_raw(2), Vec2::new(10.0, 10.0)),
+        ];
+        assert_eq!(
+            nearest_bot_leader(Vec2::ZERO, leaders.into_iter()),
+            Some(Entity::from_raw(2))
+        );
+        assert_eq!(nearest_bot_leader(Vec2::ZERO, std::iter::empty()), None);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::attract::AttractMode;
+use crate::round::PlayerSettings;
+use crate::stats::MatchStats;
+use crate::store::SettingsFile;
+use crate::{BoidColor, GameEvent, RoundSettings};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Older matches are dropped once there are this many.
+const MAX_HISTORY_ENTRIES: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Seconds since the unix epoch when the match ended
+    pub timestamp: u64,
+    pub players: Vec<PlayerSettings>,
+    /// None if the match was a tie
+    pub winner: Option<BoidColor>,
+    pub duration_seconds: f32,
+}
+
+/// The results of the last few matches, newest first, shown on the title screen.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MatchHistory {
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl MatchHistory {
+    pub fn push(&mut self, entry: HistoryEntry) {
+        self.entries.insert(0, entry);
+        self.entries.truncate(MAX_HISTORY_ENTRIES);
+    }
+}
+
+impl SettingsFile for MatchHistory {
+    const FILE: &'static str = "history.ron";
+}
+
+pub fn record_match_history(
+    mut events: EventReader<GameEvent>,
+    mut history: ResMut<MatchHistory>,
+    round_settings: Res<RoundSettings>,
+    match_stats: Res<MatchStats>,
+    attract_mode: Res<AttractMode>,
+) {
+    for event in events.iter() {
+        if let GameEvent::GameOver(winner) = event {
+            // The title screen demo isn't a real match.
+            if attract_mode.active {
+                continue;
+            }
+            history.push(HistoryEntry {
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+                players: round_settings.players.clone(),
+                winner: winner.as_ref().map(|winner| winner.color),
+                duration_seconds: match_stats.elapsed_seconds,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{from_ron, to_ron};
+    use crate::Winner;
+
+    #[test]
+    fn test_round_trip() {
+        let mut history = MatchHistory::default();
+        history.push(HistoryEntry {
+            timestamp: 1_700_000_000,
+            players: RoundSettings::default().players,
+            winner: Some(BoidColor::Red),
+            duration_seconds: 93.5,
+        });
+        history.push(HistoryEntry {
+            timestamp: 1_700_000_200,
+            players: RoundSettings::one_vs_one().players,
+            winner: None,
+            duration_seconds: 12.0,
+        });
+        assert_eq!(
+            from_ron::<MatchHistory>(&to_ron(&history).unwrap()).unwrap(),
+            history
+        );
+    }
+
+    #[test]
+    fn test_history_is_capped() {
+        let mut history = MatchHistory::default();
+        for timestamp in 0..MAX_HISTORY_ENTRIES as u64 + 5 {
+            history.push(HistoryEntry {
+                timestamp,
+                players: Vec::new(),
+                winner: None,
+                duration_seconds: 0.0,
+            });
+        }
+        assert_eq!(history.entries.len(), MAX_HISTORY_ENTRIES);
+        assert_eq!(history.entries[0].timestamp, MAX_HISTORY_ENTRIES as u64 + 4);
+    }
+
+    #[test]
+    fn test_game_over_appends_one_entry() {
+        let mut world = World::new();
+        world.init_resource::<Events<GameEvent>>();
+        world.init_resource::<MatchHistory>();
+        world.init_resource::<AttractMode>();
+        world.insert_resource(RoundSettings::default());
+        world.init_resource::<MatchStats>();
+        world.send_event(GameEvent::LeaderCaptured(BoidColor::Blue, BoidColor::Red));
+        world.send_event(GameEvent::GameOver(Some(Winner {
+            color: BoidColor::Red,
+        })));
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(record_match_history);
+        schedule.run(&mut world);
+        schedule.run(&mut world);
+
+        let history = world.resource::<MatchHistory>();
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].winner, Some(BoidColor::Red));
+    }
+}
@@ -1,8 +1,17 @@
+use crate::ai::bots::{Bot, BotDebugControls};
+use crate::boids::capture_cluster;
+use crate::camera::cursor_to_world;
+use crate::stats::BoidCensus;
+use crate::{Boid, BoidColor, BoidNeighborsCaptureRange, BoidSettings, Leader, Velocity};
+use bevy::diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin};
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
-use bevy_egui::{egui, EguiContext, EguiPlugin};
+use bevy_egui::{egui, EguiContext, EguiContexts, EguiPlugin};
 use bevy_inspector_egui::bevy_inspector::ui_for_world;
 use bevy_inspector_egui::DefaultInspectorConfigPlugin;
+use bevy_prototype_debug_lines::DebugLines;
+
+const CAPTURE_CLUSTER_COLOR: Color = Color::WHITE;
 
 pub struct InspectorPlugin;
 
@@ -14,16 +23,49 @@ impl Plugin for InspectorPlugin {
         if !app.is_plugin_added::<EguiPlugin>() {
             app.add_plugin(EguiPlugin);
         }
-        app.add_system(hotkey);
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugin(FrameTimeDiagnosticsPlugin);
+        }
+        app.init_resource::<InspectorVisible>()
+            .init_resource::<Agility>()
+            .init_resource::<CaptureClusterSelection>()
+            .add_system(hotkey)
+            .add_system(teleport_leader.run_if(inspector_visible))
+            .add_system(select_capture_cluster.run_if(inspector_visible))
+            .add_system(draw_capture_cluster);
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct InspectorVisible(pub bool);
+
+/// The inspector's agility slider, kept so it doesn't jump around when the individual settings
+/// are changed.
+#[derive(Resource, Debug)]
+struct Agility(f32);
+
+impl Default for Agility {
+    fn default() -> Self {
+        Self(0.5)
     }
 }
 
-fn hotkey(world: &mut World, mut show_inspector: Local<bool>) {
+/// The boid whose capture cluster is drawn when `show_capture_cluster` is on.
+#[derive(Resource, Debug, Default)]
+pub struct CaptureClusterSelection(pub Option<Entity>);
+
+fn inspector_visible(visible: Res<InspectorVisible>) -> bool {
+    visible.0
+}
+
+fn hotkey(world: &mut World) {
     if let Some(keys) = world.get_resource::<Input<KeyCode>>() {
         if keys.just_pressed(KeyCode::F12) {
-            *show_inspector = !*show_inspector;
+            let mut visible = world.resource_mut::<InspectorVisible>();
+            visible.0 = !visible.0;
         }
     }
+    let show_inspector = world.resource::<InspectorVisible>().0;
     let egui_context = world
         .query_filtered::<&mut EguiContext, With<PrimaryWindow>>()
         .single_mut(world)
@@ -35,10 +77,21 @@ fn hotkey(world: &mut World, mut show_inspector: Local<bool>) {
         ..default()
     };
     egui_context.set_style(inspector_style);
-    if *show_inspector {
+    if show_inspector {
+        let warnings = world
+            .get_resource::<BoidSettings>()
+            .map(|settings| settings.warnings())
+            .unwrap_or_default();
         egui::Window::new("Inspector")
             .default_size([90.0, 400.0])
             .show(&egui_context, |ui| {
+                for warning in warnings {
+                    ui.colored_label(egui::Color32::RED, warning);
+                }
+                summary(world, ui);
+                agility_slider(world, ui);
+                bot_debug_controls(world, ui);
+                ui.separator();
                 egui::ScrollArea::vertical().show(ui, |ui| {
                     ui_for_world(world, ui);
                     ui.allocate_space(ui.available_size());
@@ -47,3 +100,181 @@ fn hotkey(world: &mut World, mut show_inspector: Local<bool>) {
     }
     egui_context.set_style(old_style);
 }
+
+/// Totals that are hard to see from the individual entities.
+fn summary(world: &World, ui: &mut egui::Ui) {
+    let fps = world
+        .get_resource::<Diagnostics>()
+        .and_then(|diagnostics| diagnostics.get(FrameTimeDiagnosticsPlugin::FPS))
+        .and_then(|fps| fps.smoothed());
+    if let Some(fps) = fps {
+        ui.label(format!("FPS: {fps:.0}"));
+    }
+    if let Some(census) = world.get_resource::<BoidCensus>() {
+        ui.label(format!("Boids: {}", census.total()));
+        ui.label(format!("Leaders: {}", census.leaders));
+        for color in BoidColor::ALL {
+            let count = census.count(&color);
+            if count > 0 {
+                ui.label(format!("{}: {count}", color.name()));
+            }
+        }
+        if census.uncolored > 0 {
+            ui.label(format!("No color: {}", census.uncolored));
+        }
+    }
+}
+
+/// One slider for the overall feel, the individual settings are still below for fine tuning.
+fn agility_slider(world: &mut World, ui: &mut egui::Ui) {
+    let mut agility = world.resource::<Agility>().0;
+    if ui
+        .add(egui::Slider::new(&mut agility, 0.0..=1.0).text("Agility"))
+        .changed()
+    {
+        world.resource_mut::<Agility>().0 = agility;
+        if let Some(mut boid_settings) = world.get_resource_mut::<BoidSettings>() {
+            boid_settings.set_agility(agility);
+        }
+    }
+}
+
+fn bot_debug_controls(world: &mut World, ui: &mut egui::Ui) {
+    if let Some(mut controls) = world.get_resource_mut::<BotDebugControls>() {
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Pause bots:");
+            for bot in Bot::PAUSABLE {
+                let mut paused = controls.is_paused(bot);
+                if ui.checkbox(&mut paused, bot.to_string()).changed() {
+                    match paused {
+                        true => controls.paused.insert(bot),
+                        false => controls.paused.remove(&bot),
+                    };
+                }
+            }
+        });
+    }
+}
+
+/// Ctrl + click moves the first player's leader to the cursor, for setting up captures to test.
+/// Clicks on the inspector window are left alone.
+#[allow(clippy::type_complexity)]
+fn teleport_leader(
+    mut egui_context: EguiContexts,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform, &OrthographicProjection)>,
+    mut leaders: Query<(&mut Transform, &mut Velocity), (With<Leader>, Without<Bot>)>,
+) {
+    if !keys.any_pressed([KeyCode::LControl, KeyCode::RControl])
+        || !mouse_buttons.just_pressed(MouseButton::Left)
+        || egui_context.ctx_mut().wants_pointer_input()
+    {
+        return;
+    }
+    let target = cursor_world_position(&windows, &cameras);
+    if let (Some(target), Some((mut transform, mut velocity))) = (target, leaders.iter_mut().next())
+    {
+        transform.translation = target.extend(transform.translation.z);
+        // Don't count the jump as movement so nothing draws a streak across the arena.
+        velocity.travelled = Vec2::ZERO;
+    }
+}
+
+/// Where the cursor is in the world, using the first active camera it's over.
+fn cursor_world_position(
+    windows: &Query<&Window, With<PrimaryWindow>>,
+    cameras: &Query<(&Camera, &GlobalTransform, &OrthographicProjection)>,
+) -> Option<Vec2> {
+    let window = windows.get_single().ok()?;
+    // Bevy's cursor position starts at the bottom left of the window.
+    let cursor = window
+        .cursor_position()
+        .map(|cursor| Vec2::new(cursor.x, window.height() - cursor.y))?;
+    cameras
+        .iter()
+        .filter(|(camera, _, _)| camera.is_active)
+        .find_map(|(camera, camera_transform, projection)| {
+            let viewport_min = camera
+                .viewport
+                .as_ref()
+                .map(|v| v.physical_position.as_vec2() / window.scale_factor() as f32)
+                .unwrap_or_default();
+            cursor_to_world(
+                cursor,
+                viewport_min,
+                camera.logical_viewport_size()?,
+                projection.area.size(),
+                &camera_transform.compute_transform(),
+            )
+        })
+}
+
+/// Clicking selects the boid nearest the cursor to show its capture cluster. Clicks on the
+/// inspector window are left alone.
+#[allow(clippy::too_many_arguments)]
+fn select_capture_cluster(
+    mut egui_context: EguiContexts,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform, &OrthographicProjection)>,
+    boids: Query<(Entity, &Transform), With<Boid>>,
+    boid_settings: Res<BoidSettings>,
+    mut selection: ResMut<CaptureClusterSelection>,
+) {
+    // Ctrl + click is for teleporting.
+    if !boid_settings.show_capture_cluster
+        || keys.any_pressed([KeyCode::LControl, KeyCode::RControl])
+        || !mouse_buttons.just_pressed(MouseButton::Left)
+        || egui_context.ctx_mut().wants_pointer_input()
+    {
+        return;
+    }
+    let target = match cursor_world_position(&windows, &cameras) {
+        Some(target) => target,
+        None => return,
+    };
+    selection.0 = boids
+        .iter()
+        .min_by(|(_, a), (_, b)| {
+            a.translation
+                .truncate()
+                .distance_squared(target)
+                .total_cmp(&b.translation.truncate().distance_squared(target))
+        })
+        .map(|(entity, _)| entity);
+}
+
+/// Draws the links between the boids the capture search reaches from the selected boid.
+fn draw_capture_cluster(
+    selection: Res<CaptureClusterSelection>,
+    query: Query<(Entity, &BoidNeighborsCaptureRange)>,
+    colors: Query<&BoidColor>,
+    transforms: Query<&Transform, With<Boid>>,
+    boid_settings: Res<BoidSettings>,
+    mut lines: ResMut<DebugLines>,
+) {
+    let selected = match (boid_settings.show_capture_cluster, selection.0) {
+        (true, Some(selected)) => selected,
+        _ => return,
+    };
+    let cluster = capture_cluster(
+        selected,
+        &query,
+        &colors,
+        boid_settings.max_captures_evaluated,
+    );
+    for (entity, neighbors) in query.iter_many(&cluster) {
+        let start = match transforms.get(entity) {
+            Ok(transform) => transform.translation,
+            Err(_) => continue,
+        };
+        for neighbor in neighbors.entities.iter().filter(|n| cluster.contains(*n)) {
+            if let Ok(end) = transforms.get(*neighbor) {
+                lines.line_colored(start, end.translation, 0.0, CAPTURE_CLUSTER_COLOR);
+            }
+        }
+    }
+}
@@ -1,34 +1,87 @@
+mod accessibility;
 mod ai;
+mod attract;
+mod audio;
+mod badges;
 mod boids;
 mod camera;
+mod checksum;
+mod control;
+mod display;
+mod drop_in;
+mod history;
 mod inspector;
 mod math;
+mod offscreen;
+mod presets;
 mod quadtree;
 mod round;
+mod snapshot;
+mod stats;
+mod step;
+mod store;
+mod streaks;
+mod survival;
+mod territory;
 mod ui;
 mod viewports;
 
+use crate::accessibility::AccessibilitySettings;
 use crate::ai::bots::Bot;
+use crate::ai::scent::ScentField;
+use crate::attract::{
+    keep_logo_in_view, simulating, stop_attract_mode, update_attract_mode, AttractMode, TitleCamera,
+};
+use crate::audio::{load_sounds, play_capture_sounds};
+use crate::badges::add_badge_meshes;
 use crate::boids::{
-    clear_inputs, leader_added, leader_defeated, leader_removed, propagate_boid_color,
-    update_boid_color, update_boid_neighbors, update_boid_transforms, update_quad_tree, Boid,
-    BoidAveragedInputs, BoidColor, BoidNeighborsCaptureRange, BoidNeighborsSeparation,
-    BoidSettings, GameEvent, Leader, Velocity,
+    animate_deaths, bank_boid_sprites, clear_inputs, draw_contested_boids, draw_neighbor_links,
+    draw_path_previews, leader_added, leader_defeated, leader_removed, neighbor_update_due,
+    propagate_boid_color, push_apart_boids, separate_leaders, spawn_in_boids, spawn_positions,
+    update_boid_color, update_boid_neighbors, update_boid_transforms, update_invulnerability,
+    update_quad_tree, Boid, BoidAveragedInputs, BoidColor, BoidNeighborsCaptureRange,
+    BoidNeighborsSeparation, BoidSettings, BoidTree, GameEnded, GameEvent, Invulnerable, Leader,
+    SpawnIn, Velocity,
 };
 use crate::camera::{
-    camera_zoom, remove_camera_follow_target_on_capture, update_camera_follow_many_system,
-    update_camera_follow_system, Camera2dFollow, Camera2dFollowMany, CameraFollowTarget,
+    add_capture_trauma, auto_zoom_cameras, camera_zoom, cycle_spectator_camera, recenter_camera,
+    remove_camera_follow_target_on_capture, shake_cameras, start_winner_sweep, stop_winner_sweep,
+    sweep_to_winner, update_camera_follow_many_system, update_camera_follow_system,
+    update_capture_flashes, update_hit_stop, Camera2dFollow, Camera2dFollowMany,
+    CameraFollowTarget, CameraSettings, CameraShake, CaptureFlashes, HitStop,
 };
+use crate::checksum::{update_state_checksum, StateChecksum};
+use crate::control::{update_control, ControlTimer};
+use crate::display::DisplaySettings;
+use crate::drop_in::{detect_drop_in, handle_drop_in, layout_drop_in_cameras, DropInEvent};
+use crate::history::{record_match_history, MatchHistory};
 use crate::inspector::InspectorPlugin;
 use crate::math::how_much_right_or_left;
-use crate::round::{MultiplayerMode, PlayerType, RoundSettings};
-use crate::ui::Logo;
-use crate::viewports::{
-    set_camera_viewports, PlayerViewports, ViewportLayoutPreference, ViewportRelative,
+use crate::offscreen::{mark_offscreen_boids, SteeringFrame};
+use crate::round::{
+    draw_boundary_ring, resize_arena, validate_input_map, ArenaCircle, MultiplayerMode, PlayerType,
+    RoundSettings,
+};
+use crate::snapshot::{
+    dump_match_state, restore_snapshot, MatchSnapshot, RestoreSnapshot, RESTORE_FLAG,
+};
+use crate::stats::{
+    flush_match_recorder, record_match, reset_match_recorder, update_boid_census,
+    update_match_stats, BoidCensus, MatchRecorder, MatchStats, RECORD_FLAG,
 };
+use crate::step::{finish_simulation_step, not_frozen, request_simulation_step, StepMode};
+use crate::store::{insert_settings_file, save_settings_file, settings_changed};
+use crate::streaks::{update_capture_streaks, CaptureStreaks};
+use crate::survival::{end_survival_round, spawn_waves, SurvivalTimers};
+use crate::territory::{territory_update_due, update_territories};
+use crate::ui::Logo;
+use crate::viewports::{set_camera_viewports, ViewportRelative};
 use bevy::core_pipeline::clear_color::ClearColorConfig;
 use bevy::prelude::*;
 use bevy::render::camera::ScalingMode;
+use bevy::render::view::RenderLayers;
+use bevy::time::TimeSystem;
+use bevy::transform::TransformSystem;
 use bevy::window::WindowMode;
 use bevy_egui_kbgp::KbgpPlugin;
 use bevy_prototype_debug_lines::DebugLinesPlugin;
@@ -37,10 +90,21 @@ use std::f32::consts::PI;
 use turborand::prelude::*;
 
 const SCENE_HEIGHT: f32 = 500.0;
-const BOID_COUNT: usize = 400;
+/// Where the logo sits on the title screen relative to the center of the view
+const LOGO_OFFSET: Vec2 = Vec2::new(0.0, 100.0);
+const LOGO_SCALE: f32 = 0.3;
 const ARENA_PADDING: f32 = 100.0;
+/// A render layer nothing is on, used for cameras that only clear the screen.
+const BACKGROUND_CAMERA_LAYER: u8 = RenderLayers::TOTAL_LAYERS as u8 - 1;
 const BOID_SCALE: Vec3 = Vec3::splat(0.01);
 const LEADER_SCALE: Vec3 = Vec3::splat(0.014);
+const BOID_Z: f32 = 5.0;
+/// Leaders are drawn slightly in front of their flock so they can't get lost in a crowd.
+const LEADER_Z: f32 = 5.5;
+/// The longest a boid waits to start growing in at the start of a round
+const SPAWN_IN_STAGGER_SECONDS: f32 = 0.2;
+/// Run with this flag to turn off anti-aliasing on slower hardware.
+const PERFORMANCE_MODE_FLAG: &str = "--performance";
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Default, States)]
 pub enum AppState {
@@ -52,16 +116,43 @@ pub enum AppState {
     Paused,
 }
 
-#[derive(Debug, Clone, Reflect, Resource)]
+#[derive(Debug, Clone, PartialEq, Reflect, Resource)]
 pub struct Winner {
     pub color: BoidColor,
 }
 
+impl Winner {
+    /// The heading shown on the game over screen
+    pub fn title(&self) -> String {
+        format!("{} Won!", self.color)
+    }
+}
+
 fn main() {
+    let performance_mode = std::env::args().any(|arg| arg == PERFORMANCE_MODE_FLAG);
+    let msaa = match performance_mode {
+        true => Msaa::Off,
+        false => Msaa::Sample8,
+    };
     let mut app = App::new();
-    app.insert_resource(Msaa::Sample8)
+    app.insert_resource(msaa)
         .insert_resource(RoundSettings::default())
         .insert_resource(BoidSettings::default())
+        .insert_resource(GameEnded::default())
+        .init_resource::<AttractMode>()
+        .init_resource::<StepMode>()
+        .init_resource::<MatchHistory>()
+        .init_resource::<DisplaySettings>()
+        .init_resource::<HitStop>()
+        .init_resource::<CaptureFlashes>()
+        .init_resource::<SteeringFrame>()
+        .init_resource::<StateChecksum>()
+        .init_resource::<RoundSeed>()
+        .insert_resource(CaptureStreaks::default())
+        .insert_resource(BoidCensus::default())
+        .insert_resource(MatchStats::default())
+        .insert_resource(CameraSettings::default())
+        .insert_resource(AccessibilitySettings::default())
         .insert_resource(ClearColor(Color::BLACK))
         .add_plugins(
             DefaultPlugins
@@ -89,40 +180,215 @@ fn main() {
         .register_type::<BoidNeighborsCaptureRange>()
         .register_type::<BoidNeighborsSeparation>()
         .register_type::<Camera2dFollow>()
+        .register_type::<StepMode>()
         .register_type::<BoidColor>()
         .register_type::<Velocity>()
         .register_type::<BoidAveragedInputs>()
         .register_type::<ViewportRelative>()
         .register_type::<BoidSettings>()
+        .register_type::<CameraSettings>()
+        .register_type::<AccessibilitySettings>()
         .add_event::<GameEvent>()
+        .add_event::<DropInEvent>()
         .add_startup_system(setup)
+        .add_startup_system(load_sounds)
         .add_systems(
             (setup_game.after(despawn_game), despawn_game)
                 .in_schedule(OnEnter(AppState::LoadRound)),
         )
         .add_system(despawn_game.in_schedule(OnEnter(AppState::Title)))
+        .add_system(update_attract_mode.in_set(OnUpdate(AppState::Title)))
+        .add_system(stop_attract_mode.in_schedule(OnExit(AppState::Title)))
+        .add_system(keep_logo_in_view.after(update_camera_follow_many_system))
         .add_systems(
             (
-                update_quad_tree,
-                update_boid_neighbors.after(update_quad_tree),
+                update_quad_tree.run_if(neighbor_update_due),
+                // Only find new neighbors when the tree was rebuilt, otherwise keep the old ones.
+                update_boid_neighbors
+                    .after(update_quad_tree)
+                    .run_if(resource_exists_and_changed::<BoidTree>()),
             )
+                .in_base_set(CoreSet::First)
+                .distributive_run_if(not_frozen),
+        )
+        .add_system(
+            mark_offscreen_boids
+                .run_if(simulating)
                 .in_base_set(CoreSet::First),
         )
-        .add_system(update_boid_transforms.in_set(OnUpdate(AppState::Playing)))
+        .add_system(update_boid_transforms.run_if(simulating))
+        .add_system(
+            update_capture_streaks
+                .run_if(not_frozen)
+                .in_set(OnUpdate(AppState::Playing)),
+        )
+        .add_system(update_boid_census)
+        .add_system(resize_arena.run_if(resource_changed::<RoundSettings>()))
+        .add_system(
+            update_match_stats
+                .after(update_boid_census)
+                .run_if(not_frozen)
+                .in_set(OnUpdate(AppState::Playing)),
+        )
+        .add_system(draw_neighbor_links.in_set(OnUpdate(AppState::Playing)))
+        .add_system(draw_contested_boids.in_set(OnUpdate(AppState::Playing)))
+        .add_system(draw_path_previews.in_set(OnUpdate(AppState::Playing)))
+        .add_system(draw_boundary_ring.in_set(OnUpdate(AppState::Playing)))
+        .add_systems(
+            (
+                detect_drop_in,
+                handle_drop_in.after(detect_drop_in),
+                layout_drop_in_cameras,
+            )
+                .in_set(OnUpdate(AppState::Playing)),
+        )
+        .add_systems(
+            (spawn_waves, end_survival_round.after(spawn_waves))
+                .distributive_run_if(not_frozen)
+                .in_set(OnUpdate(AppState::Playing)),
+        )
+        .add_system(
+            update_control
+                .after(update_boid_census)
+                .run_if(not_frozen)
+                .in_set(OnUpdate(AppState::Playing)),
+        )
+        .add_system(
+            update_territories
+                .run_if(territory_update_due)
+                .run_if(not_frozen)
+                .in_set(OnUpdate(AppState::Playing)),
+        )
+        .add_system(
+            push_apart_boids
+                .after(update_boid_transforms)
+                .run_if(simulating),
+        )
+        .add_system(
+            update_state_checksum
+                .after(push_apart_boids)
+                .run_if(not_frozen)
+                .in_set(OnUpdate(AppState::Playing)),
+        )
+        .add_system(
+            bank_boid_sprites
+                .run_if(simulating)
+                .in_base_set(CoreSet::PostUpdate)
+                .after(TransformSystem::TransformPropagate),
+        )
         .add_system(clear_inputs.in_base_set(CoreSet::Last))
+        .add_systems(
+            (finish_simulation_step, request_simulation_step)
+                .chain()
+                .in_base_set(CoreSet::Last),
+        )
         .add_system(update_boid_color)
         .add_system(set_camera_viewports)
         .add_system(update_camera_follow_system)
         .add_system(update_camera_follow_many_system)
         .add_system(remove_camera_follow_target_on_capture)
         .add_system(camera_zoom)
-        .add_system(leader_defeated)
+        .add_system(auto_zoom_cameras.after(camera_zoom))
+        .add_system(
+            recenter_camera
+                .after(auto_zoom_cameras)
+                .before(update_camera_follow_system),
+        )
+        .add_system(
+            cycle_spectator_camera
+                .before(update_camera_follow_system)
+                .in_set(OnUpdate(AppState::Playing)),
+        )
+        .add_system(start_winner_sweep.in_schedule(OnEnter(AppState::GameOver)))
+        .add_system(stop_winner_sweep.in_schedule(OnExit(AppState::GameOver)))
+        .add_system(
+            sweep_to_winner
+                .after(update_camera_follow_system)
+                .after(update_camera_follow_many_system)
+                .after(auto_zoom_cameras)
+                .before(shake_cameras)
+                .in_set(OnUpdate(AppState::GameOver)),
+        )
+        .add_system(add_capture_trauma)
+        .add_system(update_capture_flashes)
+        // After the clock has ticked so the countdown uses this frame's real delta.
+        .add_system(
+            update_hit_stop
+                .after(TimeSystem)
+                .in_base_set(CoreSet::First),
+        )
+        .add_system(play_capture_sounds.after(update_boid_census))
+        .add_system(
+            shake_cameras
+                .after(update_camera_follow_system)
+                .after(update_camera_follow_many_system),
+        )
+        .add_system(leader_defeated.run_if(not_frozen))
+        .add_system(record_match_history)
         .add_system(
             propagate_boid_color
-                .run_if(in_state(AppState::Playing))
+                .run_if(simulating)
                 .in_base_set(CoreSet::PreUpdate),
         )
-        .add_systems((leader_removed, leader_added).in_base_set(CoreSet::PostUpdate));
+        .add_systems((leader_removed, leader_added).in_base_set(CoreSet::PostUpdate))
+        .add_system(animate_deaths.run_if(not_frozen))
+        .add_system(
+            update_invulnerability
+                .run_if(not_frozen)
+                .in_base_set(CoreSet::PreUpdate)
+                .before(propagate_boid_color),
+        )
+        .add_system(add_badge_meshes.in_base_set(CoreSet::Last))
+        .add_system(
+            spawn_in_boids
+                .after(leader_added)
+                .run_if(not_frozen)
+                .in_base_set(CoreSet::PostUpdate),
+        );
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        app.add_system(dump_match_state.in_set(OnUpdate(AppState::Playing)))
+            .add_system(restore_snapshot.in_schedule(OnEnter(AppState::Playing)))
+            .add_system(
+                record_match
+                    .after(update_boid_census)
+                    .run_if(not_frozen)
+                    .in_set(OnUpdate(AppState::Playing)),
+            )
+            .add_system(reset_match_recorder.in_schedule(OnEnter(AppState::LoadRound)))
+            .add_system(flush_match_recorder.in_schedule(OnEnter(AppState::GameOver)))
+            .add_system(save_settings_file::<MatchHistory>.run_if(settings_changed::<MatchHistory>))
+            .add_system(
+                save_settings_file::<DisplaySettings>.run_if(settings_changed::<DisplaySettings>),
+            )
+            .add_system(
+                save_settings_file::<AccessibilitySettings>
+                    .run_if(settings_changed::<AccessibilitySettings>),
+            );
+        // There's no file system to keep the history in on the web, so it only lasts the session.
+        insert_settings_file::<MatchHistory>(&mut app);
+        insert_settings_file::<DisplaySettings>(&mut app);
+        insert_settings_file::<AccessibilitySettings>(&mut app);
+        if let Some(path) = std::env::args().skip_while(|arg| arg != RECORD_FLAG).nth(1) {
+            app.insert_resource(MatchRecorder::new(path.into()));
+        }
+        let restore_path = std::env::args()
+            .skip_while(|arg| arg != RESTORE_FLAG)
+            .nth(1);
+        if let Some(path) = restore_path {
+            match MatchSnapshot::load(std::path::Path::new(&path)) {
+                Ok(snapshot) => {
+                    // Skip the menus and start the round straight away.
+                    app.insert_resource(snapshot.round_settings.clone())
+                        .insert_resource(snapshot.boid_settings.clone())
+                        .insert_resource(RestoreSnapshot(snapshot))
+                        .insert_resource(NextState(Some(AppState::LoadRound)));
+                }
+                Err(e) => error!("Failed to load match snapshot {path}: {e}"),
+            }
+        }
+    }
 
     app.run();
 }
@@ -132,8 +398,12 @@ pub enum PlayerActions {
     Rotate,
     Direction,
     Throttle,
+    /// Slows leaders below their usual min speed when braking is turned on
+    Brake,
     Boost,
     CameraZoom,
+    RecenterCamera,
+    Formation,
 }
 
 /// Actions that any player can trigger
@@ -143,6 +413,15 @@ pub enum GlobalActions {
     ToggleBoidSettings,
     ToggleWorldInspector,
     ToggleFullScreen,
+    ToggleUiVisibility,
+    /// Point the cameras at the next team's leader while spectating
+    SpectateNext,
+    SpectatePrevious,
+    /// Freeze the boids so they can be stepped through a frame at a time
+    ToggleStepMode,
+    StepSimulation,
+    /// Recenters the cameras of players that were captured or are spectating
+    RecenterCamera,
 }
 
 fn setup(
@@ -155,30 +434,33 @@ fn setup(
     commands
         .spawn(SpriteBundle {
             texture: asset_server.load("title.png"),
-            transform: Transform::from_xyz(0.0, 100.0, 5.0).with_scale(Vec3::splat(0.3)),
+            transform: Transform::from_translation(LOGO_OFFSET.extend(5.0))
+                .with_scale(Vec3::splat(LOGO_SCALE)),
             visibility: Visibility::Hidden,
             ..default()
         })
         .insert(Logo)
         .insert(Name::new("Logo"));
-    commands.spawn(ColorMesh2dBundle {
-        mesh: meshes
-            .add(Mesh::from(shape::Circle::new(
-                round_settings.arena_radius + 2.0,
-            )))
-            .into(),
-        material: materials.add(ColorMaterial::from(Color::WHITE)),
-        ..default()
-    });
+    // The arena is a unit circle scaled up to the arena radius so it can be resized while playing.
+    let border = ArenaCircle { padding: 2.0 };
+    commands.spawn((
+        ColorMesh2dBundle {
+            mesh: meshes.add(Mesh::from(shape::Circle::new(1.0))).into(),
+            material: materials.add(ColorMaterial::from(Color::WHITE)),
+            transform: Transform::from_scale(border.scale(&round_settings)),
+            ..default()
+        },
+        border,
+    ));
+    let arena = ArenaCircle { padding: 0.0 };
     commands
         .spawn(ColorMesh2dBundle {
-            mesh: meshes
-                .add(Mesh::from(shape::Circle::new(round_settings.arena_radius)))
-                .into(),
+            mesh: meshes.add(Mesh::from(shape::Circle::new(1.0))).into(),
             material: materials.add(ColorMaterial::from(asset_server.load("waves.png"))),
-            transform: Transform::from_xyz(0.0, 0.0, 0.01),
+            transform: Transform::from_xyz(0.0, 0.0, 0.01).with_scale(arena.scale(&round_settings)),
             ..default()
         })
+        .insert(arena)
         .insert(InputManagerBundle {
             action_state: default(),
             input_map: {
@@ -187,6 +469,17 @@ fn setup(
                     .insert(KeyCode::Back, GlobalActions::ToggleMenu)
                     .insert(KeyCode::F1, GlobalActions::ToggleMenu)
                     .insert(KeyCode::F11, GlobalActions::ToggleFullScreen)
+                    .insert(KeyCode::F10, GlobalActions::ToggleUiVisibility)
+                    .insert(KeyCode::F7, GlobalActions::ToggleStepMode)
+                    .insert(KeyCode::F8, GlobalActions::StepSimulation)
+                    .insert(KeyCode::Right, GlobalActions::SpectateNext)
+                    .insert(KeyCode::Left, GlobalActions::SpectatePrevious)
+                    .insert(GamepadButtonType::DPadRight, GlobalActions::SpectateNext)
+                    .insert(GamepadButtonType::DPadLeft, GlobalActions::SpectatePrevious)
+                    .insert(KeyCode::C, GlobalActions::RecenterCamera)
+                    .insert(KeyCode::Numpad0, GlobalActions::RecenterCamera)
+                    .insert(MouseButton::Middle, GlobalActions::RecenterCamera)
+                    .insert(GamepadButtonType::RightThumb, GlobalActions::RecenterCamera)
                     .insert(MouseButton::Right, GlobalActions::ToggleMenu)
                     .insert(GamepadButtonType::East, GlobalActions::ToggleMenu)
                     .insert(GamepadButtonType::Select, GlobalActions::ToggleMenu)
@@ -219,22 +512,28 @@ fn setup(
             },
         });
 
-    commands.spawn(Camera2dBundle {
-        projection: OrthographicProjection {
-            scaling_mode: ScalingMode::FixedVertical(SCENE_HEIGHT),
+    commands
+        .spawn(Camera2dBundle {
+            projection: OrthographicProjection {
+                scaling_mode: ScalingMode::FixedVertical(SCENE_HEIGHT),
+                ..Default::default()
+            },
+            camera: Camera {
+                order: 10,
+                ..default()
+            },
             ..Default::default()
-        },
-        camera: Camera {
-            order: 10,
-            ..default()
-        },
-        ..Default::default()
-    });
+        })
+        .insert(TitleCamera);
 }
 
 #[derive(Component, Debug, Copy, Clone)]
 pub struct SceneRoot;
 
+/// The seed the current round's boids were placed with.
+#[derive(Resource, Debug, Copy, Clone, Default)]
+pub struct RoundSeed(pub u64);
+
 fn despawn_game(mut commands: Commands, scene_root: Query<Entity, With<SceneRoot>>) {
     if let Ok(root) = scene_root.get_single() {
         info!("Restarting");
@@ -242,18 +541,139 @@ fn despawn_game(mut commands: Commands, scene_root: Query<Entity, With<SceneRoot
     }
 }
 
+/// Spawns a boid without a color at `position` facing a random direction.
+/// Add `SpawnIn` to have it grow in, otherwise it starts at full size.
+pub fn spawn_boid(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    position: Vec2,
+    rand: &impl TurboRand,
+) -> Entity {
+    spawn_boid_facing(
+        commands,
+        asset_server.load("bird.png"),
+        position,
+        rand.f32_normalized() * PI * 2.0,
+    )
+}
+
+/// Spawns a boid without a color at `position` rotated `heading` radians from facing up.
+pub fn spawn_boid_facing(
+    commands: &mut Commands,
+    texture: Handle<Image>,
+    position: Vec2,
+    heading: f32,
+) -> Entity {
+    commands
+        .spawn(SpriteBundle {
+            texture,
+            transform: Transform::from_xyz(position.x, position.y, BOID_Z)
+                .with_rotation(Quat::from_rotation_z(heading))
+                .with_scale(BOID_SCALE),
+            ..Default::default()
+        })
+        .insert(Name::new("Boid"))
+        .insert(BoidNeighborsSeparation::default())
+        .insert(BoidNeighborsCaptureRange::default())
+        .insert(ActionState::<PlayerActions>::default())
+        .insert(BoidAveragedInputs::default())
+        .insert(Boid::default())
+        .insert(Velocity::default())
+        .id()
+}
+
+/// Spawns a camera that follows `target` in its own part of the screen.
+pub fn spawn_player_camera(
+    commands: &mut Commands,
+    target: Entity,
+    viewport_id: usize,
+    viewport: ViewportRelative,
+    camera_settings: &CameraSettings,
+) -> Entity {
+    commands
+        .spawn(Camera2dBundle {
+            projection: OrthographicProjection {
+                scaling_mode: ScalingMode::FixedVertical(SCENE_HEIGHT),
+                ..Default::default()
+            },
+            camera_2d: Camera2d {
+                clear_color: ClearColorConfig::None,
+            },
+            camera: Camera {
+                order: (1000 + viewport_id) as isize,
+                ..default()
+            },
+            ..Default::default()
+        })
+        .insert(Camera2dFollow {
+            rotate_with_target: camera_settings.chase_camera,
+            ..Camera2dFollow::new(target)
+        })
+        .insert(CameraShake::default())
+        .insert(viewport)
+        .insert(Name::new(format!("Camera {viewport_id}")))
+        .id()
+}
+
+#[allow(clippy::too_many_arguments)]
 fn setup_game(
     mut commands: Commands,
     asset_server: ResMut<AssetServer>,
     mut app_state: ResMut<NextState<AppState>>,
     round_settings: Res<RoundSettings>,
+    mut game_ended: ResMut<GameEnded>,
+    mut streaks: ResMut<CaptureStreaks>,
+    camera_settings: Res<CameraSettings>,
+    boid_settings: Res<BoidSettings>,
 ) {
+    if let Err(e) = round_settings.validate() {
+        error!("Can't start the round: {e}");
+        app_state.set(AppState::Title);
+        return;
+    }
+    *game_ended = GameEnded::default();
+    *streaks = CaptureStreaks::default();
+    commands.insert_resource(ScentField::new(round_settings.arena_radius));
+    commands.insert_resource(MatchStats::default());
+    commands.insert_resource(ControlTimer::default());
+    // Keep the seed so the round can be recreated from a bug report.
+    let seed = Rng::new().gen_u64();
+    commands.insert_resource(RoundSeed(seed));
+    let boid_count = match round_settings.survival_mode {
+        true => {
+            // Offset so the waves don't land on the same spots as the boids placed below.
+            commands.insert_resource(SurvivalTimers::new(&round_settings, seed.wrapping_add(1)));
+            (round_settings.players.len() + round_settings.wave_size).min(round_settings.max_boids)
+        }
+        false => {
+            commands.remove_resource::<SurvivalTimers>();
+            round_settings.boid_count as usize
+        }
+    };
     // Spawn a root node to attach everything to so we can recursively delete everything
     // when reloading.
     let scene_root = commands
         .spawn((Name::new("Root"), SceneRoot, SpatialBundle::default()))
         .id();
 
+    // Clears the whole window before the player cameras draw so nothing is left behind in the
+    // gaps between viewports. It's on its own render layer so it doesn't draw the game itself.
+    let background_camera = commands
+        .spawn(Camera2dBundle {
+            camera_2d: Camera2d {
+                clear_color: ClearColorConfig::Custom(camera_settings.letterbox_color),
+            },
+            camera: Camera {
+                order: 999,
+                ..default()
+            },
+            ..Default::default()
+        })
+        .insert(RenderLayers::layer(BACKGROUND_CAMERA_LAYER))
+        .insert(Name::new("Background Camera"))
+        .id();
+    commands.entity(scene_root).add_child(background_camera);
+
     let shared_camera = match round_settings.multiplayer_mode {
         MultiplayerMode::SharedScreen if round_settings.local_player_count() > 1 => {
             let camera = commands
@@ -263,7 +683,7 @@ fn setup_game(
                         ..Default::default()
                     },
                     camera_2d: Camera2d {
-                        clear_color: ClearColorConfig::Custom(Color::BLACK),
+                        clear_color: ClearColorConfig::None,
                     },
                     camera: Camera {
                         order: 1000,
@@ -271,7 +691,8 @@ fn setup_game(
                     },
                     ..Default::default()
                 })
-                .insert(Camera2dFollowMany)
+                .insert(Camera2dFollowMany::default())
+                .insert(CameraShake::default())
                 .insert(Name::new("Camera"))
                 .id();
             commands.entity(scene_root).add_child(camera);
@@ -280,35 +701,31 @@ fn setup_game(
         _ => None,
     };
 
-    let rand = Rng::new();
-    for x in 0..BOID_COUNT {
-        let r = (round_settings.arena_radius - ARENA_PADDING) * rand.f32();
-        let theta = rand.f32() * 2.0 * PI;
-        let entity = commands
-            .spawn(SpriteBundle {
-                texture: asset_server.load("bird.png"),
-                transform: Transform::from_xyz(r * theta.cos(), r * theta.sin(), 5.0)
-                    .with_rotation(Quat::from_rotation_z(rand.f32_normalized() * PI * 2.0))
-                    .with_scale(BOID_SCALE),
-                ..Default::default()
-            })
+    let rand = Rng::with_seed(seed);
+    let mut positions = spawn_positions(
+        boid_count,
+        round_settings.arena_radius - ARENA_PADDING,
+        round_settings.spawn_min_distance,
+        &rand,
+    );
+    separate_leaders(
+        &mut positions,
+        round_settings.players.len(),
+        round_settings.arena_radius - ARENA_PADDING,
+        round_settings.min_leader_separation,
+        boid_settings.scaled_capture_range(round_settings.arena_radius),
+        &rand,
+    );
+    for (x, position) in positions.into_iter().enumerate() {
+        let entity = spawn_boid(&mut commands, &asset_server, position, &rand);
+        commands
+            .entity(entity)
             .insert(Name::new(format!("Boid {x}")))
-            .insert(BoidNeighborsSeparation::default())
-            .insert(BoidNeighborsCaptureRange::default())
-            .insert(ActionState::<PlayerActions>::default())
-            .insert(BoidAveragedInputs::default())
-            .insert(Boid::default())
-            .insert(Velocity::default())
-            .id();
+            .insert(SpawnIn::new(
+                SPAWN_IN_STAGGER_SECONDS * x as f32 / boid_count as f32,
+            ));
 
-        let viewports = PlayerViewports::new(
-            round_settings.local_player_count() as u8,
-            match &round_settings.multiplayer_mode {
-                MultiplayerMode::SplitScreenVertical => ViewportLayoutPreference::Vertical,
-                _ => ViewportLayoutPreference::Horizontal,
-            },
-            2.0,
-        );
+        let viewports = round_settings.player_viewports(2.0);
         match shared_camera {
             Some(_) => {
                 if let Some(player_settings) = round_settings.players.get(x) {
@@ -319,31 +736,13 @@ fn setup_game(
             }
             None => {
                 if let Some(viewport_id) = round_settings.player_viewport_id(x) {
-                    let camera = commands
-                        .spawn(Camera2dBundle {
-                            projection: OrthographicProjection {
-                                scaling_mode: ScalingMode::FixedVertical(SCENE_HEIGHT),
-                                ..Default::default()
-                            },
-                            camera_2d: Camera2d {
-                                clear_color: match viewport_id == 0 {
-                                    true => ClearColorConfig::Custom(Color::BLACK),
-                                    false => ClearColorConfig::None,
-                                },
-                            },
-                            camera: Camera {
-                                order: (1000 + viewport_id) as isize,
-                                ..default()
-                            },
-                            ..Default::default()
-                        })
-                        .insert(Camera2dFollow {
-                            target: entity,
-                            offset: Default::default(),
-                        })
-                        .insert(viewports.get(viewport_id))
-                        .insert(Name::new(format!("Camera {viewport_id}")))
-                        .id();
+                    let camera = spawn_player_camera(
+                        &mut commands,
+                        entity,
+                        viewport_id,
+                        viewports.get(viewport_id),
+                        &camera_settings,
+                    );
                     commands.entity(scene_root).add_child(camera);
                 }
             }
@@ -357,11 +756,20 @@ fn setup_game(
                 .insert(Leader);
 
             if let Some(input_map) = player_settings.player_type.input_map() {
+                if let Err(missing) = validate_input_map(&input_map) {
+                    for binding in missing {
+                        warn!("Player {}: {binding}", x + 1);
+                    }
+                }
                 commands.entity(entity).insert(input_map);
             }
 
             if let PlayerType::Bot(selected_bot) = player_settings.player_type {
-                selected_bot.insert(&mut commands.entity(entity));
+                selected_bot.insert_with_jitter(
+                    &mut commands.entity(entity),
+                    round_settings.bot_jitter,
+                    &rand,
+                );
             }
         }
 
@@ -369,3 +777,37 @@ fn setup_game(
     }
     app_state.set(AppState::Playing);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    #[test]
+    fn test_leaders_spawn_apart() {
+        let round_settings = RoundSettings::default();
+        let min_separation = round_settings.min_leader_separation;
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_state::<AppState>()
+            .insert_resource(round_settings)
+            .init_resource::<GameEnded>()
+            .init_resource::<CaptureStreaks>()
+            .init_resource::<CameraSettings>()
+            .init_resource::<BoidSettings>()
+            .add_system(setup_game);
+        app.update();
+
+        let leaders: Vec<Vec2> = app
+            .world
+            .query_filtered::<&Transform, With<Leader>>()
+            .iter(&app.world)
+            .map(|transform| transform.translation.truncate())
+            .collect();
+        assert_eq!(leaders.len(), 8);
+        for (a, b) in leaders.iter().tuple_combinations() {
+            assert!(a.distance(*b) >= min_separation);
+        }
+    }
+}
@@ -42,44 +42,53 @@ pub fn direction_to_turn_away_from_target(transform: &Transform, target: Vec2) -
     -angle_to(transform_z_angle, angle_to_target) / PI
 }
 
-pub trait Average<A, B>
-where
-    Self: Iterator<Item = A>,
-{
-    fn avg(self) -> B;
+/// Averages points where each one counts `weight` times, or None if the weights add up to 0.
+pub fn weighted_average(points: impl Iterator<Item = (Vec2, f32)>) -> Option<Vec2> {
+    let (sum, total_weight) = points.fold((Vec2::ZERO, 0.0), |(sum, total), (point, weight)| {
+        (sum + point * weight, total + weight)
+    });
+    match total_weight > 0.0 {
+        true => Some(sum / total_weight),
+        false => None,
+    }
 }
 
-impl<'a, I: Iterator<Item = &'a Vec2>> Average<&'a Vec2, Vec2> for I {
-    fn avg(self) -> Vec2 {
-        let mut count = 0;
-        let sum = self.fold(Vec2::default(), |a, b| {
-            count += 1;
-            a + *b
-        });
-        sum / count as f32
-    }
+/// Returns a smoothly changing value between -1.0 and 1.0 over time.
+/// Different seeds give different looking curves.
+pub fn smooth_noise(seconds: f32, seed: f32) -> f32 {
+    ((seconds * 1.3 + seed).sin() + (seconds * 0.7 + seed * 1.7).sin()) / 2.0
 }
 
-impl<I: Iterator<Item = Vec2>> Average<Vec2, Vec2> for I {
-    fn avg(self) -> Vec2 {
-        let mut count = 0;
-        let sum = self.fold(Vec2::default(), |a, b| {
-            count += 1;
-            a + b
-        });
-        sum / count as f32
+/// Returns the points of the smallest convex polygon that contains all of `points`,
+/// going counter-clockwise. Points along the edges are left out.
+pub fn convex_hull(points: &[Vec2]) -> Vec<Vec2> {
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
     }
-}
 
-impl<I: Iterator<Item = f32>> Average<f32, f32> for I {
-    fn avg(self) -> f32 {
-        let mut count = 0;
-        let sum = self.fold(f32::default(), |a, b| {
-            count += 1;
-            a + b
-        });
-        sum / count as f32
+    // Andrew's monotone chain: build the lower half then the upper half, dropping any point
+    // that would make a clockwise turn.
+    let mut hull: Vec<Vec2> = Vec::with_capacity(points.len() * 2);
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let start = hull.len();
+        for point in pass {
+            while hull.len() >= start + 2 {
+                let a = hull[hull.len() - 2];
+                let b = hull[hull.len() - 1];
+                if (b - a).perp_dot(point - a) > 0.0 {
+                    break;
+                }
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        // The last point of each half is the first point of the other.
+        hull.pop();
     }
+    hull
 }
 
 /// returns the shortest rotation required to reach rotation b from rotation a in radians.
@@ -106,6 +115,16 @@ mod tests {
     use super::*;
     use approx::assert_relative_eq;
 
+    #[test]
+    fn test_weighted_average() {
+        let points = [(Vec2::ZERO, 3.0), (Vec2::new(4.0, 8.0), 1.0)];
+        assert_eq!(
+            weighted_average(points.into_iter()),
+            Some(Vec2::new(1.0, 2.0))
+        );
+        assert_eq!(weighted_average([(Vec2::ONE, 0.0)].into_iter()), None);
+    }
+
     #[test]
     fn test_distance_between_two_angles() {
         assert_relative_eq!(
@@ -158,6 +177,15 @@ mod tests {
             max_relative = 0.001
         );
     }
+    #[test]
+    fn test_smooth_noise() {
+        let samples: Vec<f32> = (0..100)
+            .map(|i| smooth_noise(i as f32 * 0.1, 3.0))
+            .collect();
+        assert!(samples.iter().all(|x| (-1.0..=1.0).contains(x)));
+        assert!(samples.iter().any(|x| x.abs() > 0.01));
+    }
+
     #[test]
     fn test_wrap_f32() {
         assert_relative_eq!(wrap_f32(105.0, 50.0, 75.0), 55.0, max_relative = 0.001);
@@ -207,4 +235,26 @@ mod tests {
             max_relative = 0.001
         );
     }
+
+    #[test]
+    fn test_convex_hull() {
+        let points = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(0.5, 1.5),
+            Vec2::new(0.0, 2.0),
+            Vec2::new(1.0, 0.0),
+        ];
+        assert_eq!(
+            convex_hull(&points),
+            vec![
+                Vec2::new(0.0, 0.0),
+                Vec2::new(2.0, 0.0),
+                Vec2::new(2.0, 2.0),
+                Vec2::new(0.0, 2.0),
+            ]
+        );
+    }
 }
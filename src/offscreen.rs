@@ -0,0 +1,171 @@
+use crate::camera::Camera2dFollow;
+use crate::{Boid, BoidSettings, Leader};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+/// Added to boids that none of the players' own cameras can see. They only steer every
+/// `offscreen_steering_interval` frames but keep moving, counting and capturing as usual.
+#[derive(Component, Debug)]
+pub struct Offscreen;
+
+/// Counts up every frame, used to spread the offscreen boids' steering over several frames.
+#[derive(Resource, Debug, Default)]
+pub struct SteeringFrame(pub u32);
+
+/// Returns the area of the world a camera can see, grown to fit if the camera is rotated.
+/// `area` is the camera's projection area.
+pub fn camera_world_rect(transform: &Transform, area: Rect) -> Rect {
+    let corners = [
+        area.min,
+        Vec2::new(area.min.x, area.max.y),
+        area.max,
+        Vec2::new(area.max.x, area.min.y),
+    ]
+    .map(|corner| (transform.rotation * corner.extend(0.0)).truncate());
+    let min = corners
+        .iter()
+        .fold(Vec2::splat(f32::MAX), |min, c| min.min(*c));
+    let max = corners
+        .iter()
+        .fold(Vec2::splat(f32::MIN), |max, c| max.max(*c));
+    let center = transform.translation.truncate();
+    Rect::from_corners(center + min, center + max)
+}
+
+/// Whether a boid skips steering on `frame`. Boids take turns so only a few steer each frame.
+pub fn skips_frame(entity: Entity, frame: u32, interval: u32) -> bool {
+    interval > 1 && !frame.wrapping_add(entity.index()).is_multiple_of(interval)
+}
+
+/// Lets the steering systems check whether an offscreen boid should steer this frame.
+#[derive(SystemParam)]
+pub struct SteeringThrottle<'w, 's> {
+    frame: Res<'w, SteeringFrame>,
+    boid_settings: Res<'w, BoidSettings>,
+    offscreen: Query<'w, 's, (), With<Offscreen>>,
+}
+
+impl SteeringThrottle<'_, '_> {
+    pub fn skips(&self, entity: Entity) -> bool {
+        self.skips_frame(entity, self.frame.0)
+    }
+
+    /// Used when clearing inputs so boids that won't steer next frame keep going the same way.
+    pub fn skips_next_frame(&self, entity: Entity) -> bool {
+        self.skips_frame(entity, self.frame.0.wrapping_add(1))
+    }
+
+    fn skips_frame(&self, entity: Entity, frame: u32) -> bool {
+        self.offscreen.contains(entity)
+            && skips_frame(
+                entity,
+                frame,
+                self.boid_settings.offscreen_steering_interval,
+            )
+    }
+}
+
+/// Flags the boids outside every player's viewport so their steering can be throttled.
+/// Shared screen cameras see most of the arena anyway so only the players' own cameras count.
+#[allow(clippy::type_complexity)]
+pub fn mark_offscreen_boids(
+    mut commands: Commands,
+    mut frame: ResMut<SteeringFrame>,
+    cameras: Query<(&Transform, &OrthographicProjection), With<Camera2dFollow>>,
+    boids: Query<(Entity, &Transform, Option<&Offscreen>), (With<Boid>, Without<Leader>)>,
+    boid_settings: Res<BoidSettings>,
+) {
+    frame.0 = frame.0.wrapping_add(1);
+    let rects: Vec<Rect> = cameras
+        .iter()
+        .map(|(transform, projection)| camera_world_rect(transform, projection.area))
+        .collect();
+    let throttled = boid_settings.offscreen_steering_interval > 1 && !rects.is_empty();
+    for (entity, transform, offscreen) in boids.iter() {
+        let position = transform.translation.truncate();
+        let visible = !throttled || rects.iter().any(|rect| rect.contains(position));
+        match (visible, offscreen.is_some()) {
+            (false, false) => {
+                commands.entity(entity).insert(Offscreen);
+            }
+            (true, true) => {
+                commands.entity(entity).remove::<Offscreen>();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_4;
+
+    #[test]
+    fn test_boids_outside_every_viewport_are_offscreen() {
+        let mut world = World::new();
+        world.insert_resource(BoidSettings {
+            offscreen_steering_interval: 4,
+            ..default()
+        });
+        world.init_resource::<SteeringFrame>();
+        let projection = || OrthographicProjection {
+            area: Rect::new(-100.0, -100.0, 100.0, 100.0),
+            ..default()
+        };
+        for x in [0.0, 1000.0] {
+            let target = world.spawn_empty().id();
+            world.spawn((
+                Camera2dFollow::new(target),
+                Transform::from_xyz(x, 0.0, 999.0),
+                projection(),
+            ));
+        }
+        let boid = |world: &mut World, x: f32, y: f32| {
+            world
+                .spawn((Boid::default(), Transform::from_xyz(x, y, 0.0)))
+                .id()
+        };
+        let in_first = boid(&mut world, 50.0, 50.0);
+        let in_second = boid(&mut world, 950.0, -80.0);
+        let between = boid(&mut world, 500.0, 0.0);
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(mark_offscreen_boids);
+        schedule.run(&mut world);
+
+        assert!(world.get::<Offscreen>(in_first).is_none());
+        assert!(world.get::<Offscreen>(in_second).is_none());
+        assert!(world.get::<Offscreen>(between).is_some());
+
+        // Everyone steers every frame again once throttling is turned off.
+        world
+            .resource_mut::<BoidSettings>()
+            .offscreen_steering_interval = 1;
+        schedule.run(&mut world);
+        assert!(world.get::<Offscreen>(between).is_none());
+    }
+
+    #[test]
+    fn test_rotated_camera_rect_grows_to_fit() {
+        let rect = camera_world_rect(
+            &Transform::from_xyz(10.0, 0.0, 0.0).with_rotation(Quat::from_rotation_z(FRAC_PI_4)),
+            Rect::new(-1.0, -1.0, 1.0, 1.0),
+        );
+        let half_diagonal = 2.0f32.sqrt();
+        assert!(rect
+            .min
+            .abs_diff_eq(Vec2::new(10.0 - half_diagonal, -half_diagonal), 0.001));
+        assert!(rect
+            .max
+            .abs_diff_eq(Vec2::new(10.0 + half_diagonal, half_diagonal), 0.001));
+    }
+
+    #[test]
+    fn test_offscreen_boids_take_turns_steering() {
+        let entity = Entity::from_raw(3);
+        let steering_frames = (0..8).filter(|frame| !skips_frame(entity, *frame, 4));
+        assert_eq!(steering_frames.count(), 2);
+        assert!((0..8).all(|frame| !skips_frame(entity, frame, 1)));
+    }
+}
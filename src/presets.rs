@@ -0,0 +1,74 @@
+use crate::store::{from_ron, to_ron, StoreError};
+use crate::BoidSettings;
+use std::path::PathBuf;
+
+/// The folder boid setting presets are saved to, relative to where the game is run from.
+const PRESETS_DIRECTORY: &str = "presets";
+
+impl BoidSettings {
+    pub fn to_ron(&self) -> Result<String, StoreError> {
+        to_ron(self)
+    }
+
+    /// Any settings missing from the preset, like ones added after it was saved, use the defaults.
+    pub fn from_ron(preset: &str) -> Result<Self, StoreError> {
+        from_ron(preset)
+    }
+
+    pub fn save_preset(&self, name: &str) -> Result<(), StoreError> {
+        let path = preset_path(name)?;
+        std::fs::create_dir_all(PRESETS_DIRECTORY)?;
+        std::fs::write(path, self.to_ron()?)?;
+        Ok(())
+    }
+
+    pub fn load_preset(name: &str) -> Result<Self, StoreError> {
+        Self::from_ron(&std::fs::read_to_string(preset_path(name)?)?)
+    }
+}
+
+fn preset_path(name: &str) -> Result<PathBuf, StoreError> {
+    if name.trim().is_empty() || name.contains(['/', '\\', '.']) {
+        return Err(StoreError::InvalidName(name.to_string()));
+    }
+    Ok(PathBuf::from(PRESETS_DIRECTORY).join(format!("{}.ron", name.trim())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let default = BoidSettings::default();
+        assert_eq!(
+            BoidSettings::from_ron(&default.to_ron().unwrap()).unwrap(),
+            default
+        );
+
+        let custom = BoidSettings {
+            max_speed: 250.0,
+            cohesion_enabled: false,
+            max_captures_evaluated: 12,
+            ..default
+        };
+        assert_eq!(
+            BoidSettings::from_ron(&custom.to_ron().unwrap()).unwrap(),
+            custom
+        );
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let settings = BoidSettings::from_ron("(max_speed: 10.0)").unwrap();
+        assert_eq!(settings.max_speed, 10.0);
+        assert_eq!(settings.min_speed, BoidSettings::default().min_speed);
+    }
+
+    #[test]
+    fn test_invalid_preset_names() {
+        assert!(preset_path("").is_err());
+        assert!(preset_path("../settings").is_err());
+        assert!(preset_path("floaty").is_ok());
+    }
+}
@@ -1,11 +1,19 @@
-use crate::{BoidColor, Bot, PlayerActions};
+use crate::accessibility::AccessibilitySettings;
+use crate::viewports::{PlayerViewports, ViewportLayoutPreference, ViewportRelative};
+use crate::{BoidColor, Bot, PlayerActions, ARENA_PADDING};
 use bevy::prelude::*;
+use bevy_prototype_debug_lines::DebugLines;
 use leafwing_input_manager::buttonlike::MouseMotionDirection;
 use leafwing_input_manager::prelude::*;
 use leafwing_input_manager::user_input::InputKind;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::{PI, TAU};
 use std::fmt::Formatter;
+use turborand::prelude::*;
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Reflect, FromReflect)]
+#[derive(
+    Debug, Copy, Clone, Eq, PartialEq, Default, Reflect, FromReflect, Serialize, Deserialize,
+)]
 pub enum PlayerType {
     #[default]
     AnyDevice,
@@ -16,52 +24,80 @@ pub enum PlayerType {
     Bot(Bot),
 }
 
+/// The back and forward buttons on the side of a mouse. Windows and Linux number them
+/// differently.
+const MOUSE_BACK: [MouseButton; 2] = [MouseButton::Other(1), MouseButton::Other(8)];
+const MOUSE_FORWARD: [MouseButton; 2] = [MouseButton::Other(2), MouseButton::Other(9)];
+
 impl PlayerType {
+    /// The devices that are merged together for `AnyDevice`.
+    const ANY_DEVICE_PARTS: [Self; 4] = [
+        Self::Wasd,
+        Self::ArrowKeys,
+        Self::Mouse,
+        Self::GamePad(None),
+    ];
+
     pub fn is_local(&self) -> bool {
         !matches!(self, Self::Bot(_))
     }
 
-    pub fn human_options() -> [Self; 9] {
+    /// Returns true if both read from the same device, so they can't both be playing.
+    pub fn shares_device(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Bot(_), _) | (_, Self::Bot(_)) => false,
+            (Self::AnyDevice, _) | (_, Self::AnyDevice) => true,
+            // A gamepad player without one picked listens to all of them.
+            (Self::GamePad(a), Self::GamePad(b)) => a.is_none() || b.is_none() || a == b,
+            (a, b) => a == b,
+        }
+    }
+
+    /// The devices a human can play with, including one option for each of the `gamepads` that
+    /// are connected.
+    pub fn human_options(gamepads: impl IntoIterator<Item = Gamepad>) -> Vec<Self> {
+        let mut gamepads: Vec<Gamepad> = gamepads.into_iter().collect();
+        // Gamepads aren't kept in any order, sort them so the menu doesn't shuffle around.
+        gamepads.sort_by_key(|gamepad| gamepad.id);
         [
             Self::AnyDevice,
             Self::Wasd,
             Self::Mouse,
             Self::ArrowKeys,
             Self::GamePad(None),
-            Self::GamePad(Some(Gamepad { id: 0 })),
-            Self::GamePad(Some(Gamepad { id: 1 })),
-            Self::GamePad(Some(Gamepad { id: 2 })),
-            Self::GamePad(Some(Gamepad { id: 3 })),
         ]
+        .into_iter()
+        .chain(
+            gamepads
+                .into_iter()
+                .map(|gamepad| Self::GamePad(Some(gamepad))),
+        )
+        .collect()
     }
 
-    pub fn bot_options() -> [Self; 4] {
+    pub fn bot_options() -> [Self; 5] {
         [
             Self::Bot(Bot::BoneHead),
             Self::Bot(Bot::ScaredyCat),
             Self::Bot(Bot::Speedy),
             Self::Bot(Bot::Hunter),
+            Self::Bot(Bot::Mimic),
         ]
     }
 
-    pub fn human_bot_label(&self) -> &str {
-        match self.is_local() {
-            true => "Human",
-            false => "Bot",
-        }
-    }
-
     pub fn input_map(&self) -> Option<InputMap<PlayerActions>> {
         match self {
-            PlayerType::AnyDevice => Some(
-                PlayerType::Wasd
-                    .input_map()
-                    .unwrap()
-                    .merge(&PlayerType::ArrowKeys.input_map().unwrap())
-                    .merge(&PlayerType::Mouse.input_map().unwrap())
-                    .merge(&PlayerType::GamePad(None).input_map().unwrap())
-                    .build(),
-            ),
+            PlayerType::AnyDevice => Some({
+                let mut map = InputMap::<PlayerActions>::default();
+                // Parts without a map are skipped rather than panicking, they have nothing to add.
+                for part in Self::ANY_DEVICE_PARTS
+                    .iter()
+                    .filter_map(PlayerType::input_map)
+                {
+                    map.merge(&part);
+                }
+                map
+            }),
             PlayerType::Wasd => Some(
                 InputMap::<PlayerActions>::default()
                     .insert(VirtualDPad::wasd(), PlayerActions::Direction)
@@ -85,6 +121,9 @@ impl PlayerType {
                     )
                     .insert(KeyCode::Space, PlayerActions::Boost)
                     .insert(KeyCode::LShift, PlayerActions::Boost)
+                    .insert(KeyCode::LControl, PlayerActions::Brake)
+                    .insert(KeyCode::C, PlayerActions::RecenterCamera)
+                    .insert(KeyCode::Q, PlayerActions::Formation)
                     .build(),
             ),
             PlayerType::ArrowKeys => Some(
@@ -100,6 +139,9 @@ impl PlayerType {
                         PlayerActions::CameraZoom,
                     )
                     .insert(KeyCode::Up, PlayerActions::Boost)
+                    .insert(KeyCode::Down, PlayerActions::Brake)
+                    .insert(KeyCode::Numpad0, PlayerActions::RecenterCamera)
+                    .insert(KeyCode::RControl, PlayerActions::Formation)
                     .build(),
             ),
             PlayerType::Mouse => Some(
@@ -115,6 +157,9 @@ impl PlayerType {
                     )
                     .insert(VirtualDPad::mouse_wheel(), PlayerActions::CameraZoom)
                     .insert(MouseButton::Left, PlayerActions::Boost)
+                    .insert(MouseButton::Middle, PlayerActions::RecenterCamera)
+                    .insert_many_to_one(MOUSE_BACK, PlayerActions::Brake)
+                    .insert_many_to_one(MOUSE_FORWARD, PlayerActions::Formation)
                     .build(),
             ),
             PlayerType::GamePad(gp) => Some({
@@ -123,6 +168,9 @@ impl PlayerType {
                     .insert(VirtualDPad::dpad(), PlayerActions::CameraZoom)
                     .insert(GamepadButtonType::South, PlayerActions::Boost)
                     .insert(GamepadButtonType::RightTrigger, PlayerActions::Boost)
+                    .insert(GamepadButtonType::LeftTrigger, PlayerActions::Brake)
+                    .insert(GamepadButtonType::RightThumb, PlayerActions::RecenterCamera)
+                    .insert(GamepadButtonType::North, PlayerActions::Formation)
                     .build();
                 if let Some(gp) = gp {
                     map.set_gamepad(*gp);
@@ -146,13 +194,13 @@ impl std::fmt::Display for PlayerType {
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Reflect, FromReflect)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Reflect, FromReflect, Serialize, Deserialize)]
 pub struct PlayerSettings {
     pub player_type: PlayerType,
     pub color: BoidColor,
 }
 
-#[derive(Debug, Clone, Default, Eq, PartialEq, Reflect)]
+#[derive(Debug, Clone, Default, Eq, PartialEq, Reflect, Serialize, Deserialize)]
 pub enum MultiplayerMode {
     #[default]
     SplitScreenVertical,
@@ -170,12 +218,252 @@ impl std::fmt::Display for MultiplayerMode {
     }
 }
 
-#[derive(Debug, Clone, Resource, Reflect)]
+/// How tough the bots are in a Quick Play round.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    /// The bots that fill the seven bot slots of a Quick Play round.
+    pub fn bots(&self) -> [Bot; 7] {
+        match self {
+            Difficulty::Easy => [
+                Bot::BoneHead,
+                Bot::BoneHead,
+                Bot::ScaredyCat,
+                Bot::BoneHead,
+                Bot::BoneHead,
+                Bot::BoneHead,
+                Bot::BoneHead,
+            ],
+            Difficulty::Normal => [
+                Bot::BoneHead,
+                Bot::BoneHead,
+                Bot::ScaredyCat,
+                Bot::Speedy,
+                Bot::Hunter,
+                Bot::BoneHead,
+                Bot::BoneHead,
+            ],
+            Difficulty::Hard => [
+                Bot::Hunter,
+                Bot::Speedy,
+                Bot::Mimic,
+                Bot::Hunter,
+                Bot::Speedy,
+                Bot::Hunter,
+                Bot::Mimic,
+            ],
+        }
+    }
+
+    /// The localization key for the name of the difficulty
+    pub fn key(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+}
+
+/// How much room each boid needs to fly around comfortably, in separation distances squared.
+const AREA_PER_BOID: f32 = 16.0;
+/// The arena size the boid settings are tuned for.
+pub const DEFAULT_ARENA_RADIUS: f32 = 1200.0;
+
+/// Returned when there are too many boids to fit in the arena comfortably.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DensityWarning {
+    /// The most boids that fit in the current arena
+    pub max_boids: usize,
+    /// How big the arena would need to be to fit all the boids
+    pub suggested_radius: f32,
+}
+
+/// The circles drawn for the arena, scaled to match the arena radius.
+#[derive(Component, Debug)]
+pub struct ArenaCircle {
+    /// How much bigger than the arena radius this circle is
+    pub padding: f32,
+}
+
+impl ArenaCircle {
+    /// The scale of a unit circle mesh that matches the arena.
+    pub fn scale(&self, round_settings: &RoundSettings) -> Vec3 {
+        let radius = round_settings.arena_radius + self.padding;
+        Vec3::new(radius, radius, 1.0)
+    }
+}
+
+/// Keeps the arena's circles in sync with the radius boids are kept inside when it's changed
+/// while playing, like from the inspector.
+pub fn resize_arena(
+    round_settings: Res<RoundSettings>,
+    mut query: Query<(&ArenaCircle, &mut Transform)>,
+) {
+    for (arena_circle, mut transform) in query.iter_mut() {
+        let scale = arena_circle.scale(&round_settings);
+        if transform.scale != scale {
+            transform.scale = scale;
+        }
+    }
+}
+
+/// How many straight lines the boundary ring is drawn with
+const BOUNDARY_RING_SEGMENTS: usize = 96;
+/// How many times a second the boundary ring pulses
+const BOUNDARY_RING_PULSES_PER_SECOND: f32 = 0.5;
+
+/// Returns the points around a circle of `radius`, the first point is repeated at the end to
+/// close it.
+pub fn ring_points(radius: f32, segments: usize) -> Vec<Vec2> {
+    (0..=segments)
+        .map(|i| {
+            let angle = TAU * i as f32 / segments as f32;
+            Vec2::new(angle.cos(), angle.sin()) * radius
+        })
+        .collect()
+}
+
+/// Draws a red ring inside the arena's edge where boids start turning back, so players can see
+/// how far they can go. It pulses gently unless reduce motion is on.
+pub fn draw_boundary_ring(
+    mut lines: ResMut<DebugLines>,
+    round_settings: Res<RoundSettings>,
+    accessibility: Res<AccessibilitySettings>,
+    time: Res<Time>,
+) {
+    if !accessibility.boundary_ring {
+        return;
+    }
+    let alpha = match accessibility.reduce_motion {
+        true => 0.6,
+        false => {
+            0.45 + 0.25 * (time.elapsed_seconds() * BOUNDARY_RING_PULSES_PER_SECOND * TAU).sin()
+        }
+    };
+    let color = Color::rgba(1.0, 0.15, 0.15, alpha);
+    let points = ring_points(round_settings.turn_back_radius(), BOUNDARY_RING_SEGMENTS);
+    for segment in points.windows(2) {
+        lines.line_colored(segment[0].extend(0.02), segment[1].extend(0.02), 0.0, color);
+    }
+}
+
+/// An action a player can't play without that an input map leaves unbound.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MissingBinding {
+    /// Neither `Direction` nor `Rotate` is bound, so the leader can't be steered.
+    Steering,
+    Boost,
+}
+
+impl std::fmt::Display for MissingBinding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MissingBinding::Steering => write!(f, "Nothing is bound to steer with"),
+            MissingBinding::Boost => write!(f, "Nothing is bound to boost with"),
+        }
+    }
+}
+
+/// Checks that a remapped input map can still steer and boost, returning everything that's missing
+/// so it can be flagged before the map is saved.
+pub fn validate_input_map(input_map: &InputMap<PlayerActions>) -> Result<(), Vec<MissingBinding>> {
+    let bound = |action: PlayerActions| !input_map.get(action).is_empty();
+    let mut missing = Vec::new();
+    if !bound(PlayerActions::Direction) && !bound(PlayerActions::Rotate) {
+        missing.push(MissingBinding::Steering);
+    }
+    if !bound(PlayerActions::Boost) {
+        missing.push(MissingBinding::Boost);
+    }
+    match missing.is_empty() {
+        true => Ok(()),
+        false => Err(missing),
+    }
+}
+
+/// Reasons a round can't be started with the current settings.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RoundSettingsError {
+    NoPlayers,
+}
+
+impl std::fmt::Display for RoundSettingsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RoundSettingsError::NoPlayers => write!(f, "A round needs at least one player"),
+        }
+    }
+}
+
+impl RoundSettingsError {
+    /// The localization id of the hint shown in the menu
+    pub fn localization_id(&self) -> &'static str {
+        match self {
+            RoundSettingsError::NoPlayers => "no_players",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize)]
+#[serde(default)]
 pub struct RoundSettings {
     pub players: Vec<PlayerSettings>,
     pub arena_radius: f32,
     pub boid_count: f32,
     pub multiplayer_mode: MultiplayerMode,
+    /// The closest boids can be to each other when they spawn. 0 places them anywhere.
+    pub spawn_min_distance: f32,
+    /// The closest leaders can be to each other when they spawn, so nobody gets captured before
+    /// they've had a chance to move. 0 places them anywhere.
+    pub min_leader_separation: f32,
+    /// How many recent captures a color needs to go on fire. 0 turns streaks off.
+    pub streak_threshold: f32,
+    /// How much faster a flock that's on fire can go.
+    pub streak_speed_multiplier: f32,
+    /// Starts with only a few boids and keeps adding more in waves. Whoever has the most boids
+    /// when the time runs out wins.
+    pub survival_mode: bool,
+    /// Seconds between each wave of boids in survival mode
+    pub wave_interval: f32,
+    /// How many boids each wave adds in survival mode
+    pub wave_size: usize,
+    /// Waves stop adding boids once there are this many
+    pub max_boids: usize,
+    /// How long a survival round lasts in seconds
+    pub survival_seconds: f32,
+    /// A color also wins by holding on to `control_share` of all the boids for `control_seconds`
+    /// in a row, so growing the flock matters as much as hunting leaders.
+    pub control_mode: bool,
+    /// The fraction of all the boids a color needs to be in control, from 0.0 to 1.0
+    pub control_share: f32,
+    /// How many seconds in a row a color has to stay in control to win
+    pub control_seconds: f32,
+    /// Lets people join a round in progress by taking over a bot. Tab joins with WASD, Enter
+    /// with the arrow keys and West on a gamepad. Press it again to hand control back.
+    pub drop_in: bool,
+    /// How many of its own boids a leader needs within capture range to have its full capture
+    /// range. Leaders with fewer have it shrunk, so they have to bring their flock into fights.
+    /// 0 turns this off.
+    pub leader_escort_size: f32,
+    /// How much each bot's settings are randomly nudged so bots of the same type play a little
+    /// differently, 0.1 is ±10%. 0 makes them identical.
+    pub bot_jitter: f32,
+    /// How many fewer followers, as a fraction of its current target's, another leader needs
+    /// before a Hunter switches to it. 0 switches to any weaker leader straight away.
+    pub hunter_switch_margin: f32,
+    /// Rounds can end without a winner. Off keeps a round going past a tied time limit and stops
+    /// two leaders from capturing each other at once, so there is always a winner unless every
+    /// flock is gone. Quick Play turns this off.
+    pub allow_ties: bool,
 }
 
 impl RoundSettings {
@@ -186,6 +474,23 @@ impl RoundSettings {
             .count()
     }
 
+    /// How far from the center boids can get before they start turning back into the arena.
+    pub fn turn_back_radius(&self) -> f32 {
+        (self.arena_radius - ARENA_PADDING).max(0.0)
+    }
+
+    /// The split screen viewports for the local players, in the order of `player_viewport_id`.
+    pub fn player_viewports(&self, border_thickness: f32) -> PlayerViewports {
+        PlayerViewports::new(
+            self.local_player_count() as u8,
+            match &self.multiplayer_mode {
+                MultiplayerMode::SplitScreenVertical => ViewportLayoutPreference::Vertical,
+                _ => ViewportLayoutPreference::Horizontal,
+            },
+            border_thickness,
+        )
+    }
+
     // Gets the index of the viewport this player was assigned based on how many local players
     // came before this player.
     pub fn player_viewport_id(&self, player_index: usize) -> Option<usize> {
@@ -197,6 +502,92 @@ impl RoundSettings {
             .find(|(_, (i, _))| *i == player_index)
             .map(|(i, (_, _))| i)
     }
+
+    /// The part of the screen a local player watches the game through, the whole screen when the
+    /// local players are sharing it.
+    pub fn player_viewport(
+        &self,
+        player_index: usize,
+        border_thickness: f32,
+    ) -> Option<ViewportRelative> {
+        let viewport_id = self.player_viewport_id(player_index)?;
+        match self.multiplayer_mode {
+            MultiplayerMode::SharedScreen if self.local_player_count() > 1 => {
+                Some(ViewportRelative::fullscreen())
+            }
+            _ => Some(self.player_viewports(border_thickness).get(viewport_id)),
+        }
+    }
+
+    /// Gets the indexes of the players that are set to use a specific gamepad.
+    pub fn players_using_gamepad(&self, gamepad: Gamepad) -> impl Iterator<Item = usize> + '_ {
+        self.players
+            .iter()
+            .enumerate()
+            .filter(move |(_, p)| p.player_type == PlayerType::GamePad(Some(gamepad)))
+            .map(|(i, _)| i)
+    }
+
+    /// Checks the round can be started with these settings.
+    pub fn validate(&self) -> Result<(), RoundSettingsError> {
+        match self.players.is_empty() {
+            true => Err(RoundSettingsError::NoPlayers),
+            false => Ok(()),
+        }
+    }
+
+    /// Checks if the boids would be packed in so tight that they can't keep apart.
+    pub fn check_density(&self, separation_distance: f32) -> Option<DensityWarning> {
+        let area_per_boid = AREA_PER_BOID * separation_distance.powi(2);
+        let play_radius = self.turn_back_radius();
+        let max_boids = (PI * play_radius.powi(2) / area_per_boid) as usize;
+        match self.boid_count as usize > max_boids {
+            true => Some(DensityWarning {
+                max_boids,
+                suggested_radius: (self.boid_count * area_per_boid / PI).sqrt() + ARENA_PADDING,
+            }),
+            false => None,
+        }
+    }
+
+    /// Two players sharing a keyboard against six bots, each with their own half of the screen.
+    pub fn co_op_vs_bots() -> Self {
+        let mut settings = Self::default();
+        settings.players[0].player_type = PlayerType::Wasd;
+        settings.players[1].player_type = PlayerType::ArrowKeys;
+        settings.multiplayer_mode = MultiplayerMode::SplitScreenVertical;
+        settings
+    }
+
+    /// One player against seven bots picked for the difficulty. Rounds keep going until there's
+    /// a winner.
+    pub fn quick_play(difficulty: Difficulty) -> Self {
+        let mut settings = Self::default();
+        for (player, bot) in settings.players.iter_mut().skip(1).zip(difficulty.bots()) {
+            player.player_type = PlayerType::Bot(bot);
+        }
+        settings.allow_ties = false;
+        settings
+    }
+
+    /// One player against a single bot.
+    pub fn one_vs_one() -> Self {
+        let mut settings = Self::default();
+        settings.players.truncate(2);
+        settings
+    }
+
+    /// Assigns a random bot type to every bot slot, leaving human players as they are.
+    pub fn randomize_bots(&mut self, rand: &impl TurboRand) {
+        let options = PlayerType::bot_options();
+        for player in self
+            .players
+            .iter_mut()
+            .filter(|p| !p.player_type.is_local())
+        {
+            player.player_type = options[rand.usize(..options.len())];
+        }
+    }
 }
 
 impl Default for RoundSettings {
@@ -236,9 +627,240 @@ impl Default for RoundSettings {
                     color: BoidColor::Cyan,
                 },
             ],
-            arena_radius: 1200.0,
+            arena_radius: DEFAULT_ARENA_RADIUS,
             boid_count: 400.0,
             multiplayer_mode: MultiplayerMode::default(),
+            spawn_min_distance: 0.0,
+            min_leader_separation: 300.0,
+            streak_threshold: 0.0,
+            streak_speed_multiplier: 1.3,
+            survival_mode: false,
+            wave_interval: 10.0,
+            wave_size: 40,
+            max_boids: 800,
+            survival_seconds: 180.0,
+            control_mode: false,
+            control_share: 0.6,
+            control_seconds: 30.0,
+            drop_in: false,
+            leader_escort_size: 0.0,
+            bot_jitter: 0.1,
+            hunter_switch_margin: 0.25,
+            allow_ties: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boundary_ring_is_at_the_turn_back_radius() {
+        let round_settings = RoundSettings {
+            arena_radius: 800.0,
+            ..default()
+        };
+        assert_eq!(round_settings.turn_back_radius(), 800.0 - ARENA_PADDING);
+        let points = ring_points(round_settings.turn_back_radius(), BOUNDARY_RING_SEGMENTS);
+        assert_eq!(points.len(), BOUNDARY_RING_SEGMENTS + 1);
+        for point in points {
+            assert!((point.length() - round_settings.turn_back_radius()).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_input_map_needs_steering_and_boost() {
+        for player_type in PlayerType::human_options((0..4).map(|id| Gamepad { id })) {
+            assert_eq!(
+                validate_input_map(&player_type.input_map().unwrap()),
+                Ok(())
+            );
+        }
+
+        let input_map = InputMap::<PlayerActions>::default()
+            .insert(KeyCode::Space, PlayerActions::Boost)
+            .insert(KeyCode::C, PlayerActions::RecenterCamera)
+            .build();
+        assert_eq!(
+            validate_input_map(&input_map),
+            Err(vec![MissingBinding::Steering])
+        );
+        assert_eq!(
+            validate_input_map(&InputMap::default()),
+            Err(vec![MissingBinding::Steering, MissingBinding::Boost])
+        );
+    }
+
+    #[test]
+    fn test_any_device_has_every_devices_bindings() {
+        let any_device = PlayerType::AnyDevice.input_map().unwrap();
+        for part in PlayerType::ANY_DEVICE_PARTS {
+            let part_map = part.input_map().unwrap();
+            for action in PlayerActions::variants() {
+                for input in part_map.get(action).iter() {
+                    assert!(
+                        any_device.get(action).iter().any(|i| i == input),
+                        "{part} {action:?} binding missing from AnyDevice"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_mouse_can_brake_and_use_formation() {
+        let mouse = PlayerType::Mouse.input_map().unwrap();
+        for action in [PlayerActions::Brake, PlayerActions::Formation] {
+            let inputs: Vec<UserInput> = mouse.get(action).iter().cloned().collect();
+            assert!(!inputs.is_empty(), "{action:?} has no mouse binding");
+            // Right click is taken by the menu.
+            assert!(!inputs.contains(&MouseButton::Right.into()));
+        }
+    }
+
+    #[test]
+    fn test_human_options_list_connected_gamepads() {
+        let gamepad_options = |ids: &[usize]| {
+            PlayerType::human_options(ids.iter().map(|id| Gamepad { id: *id }))
+                .into_iter()
+                .filter(|option| matches!(option, PlayerType::GamePad(_)))
+                .collect::<Vec<PlayerType>>()
+        };
+        assert_eq!(
+            gamepad_options(&[5, 1]),
+            vec![
+                PlayerType::GamePad(None),
+                PlayerType::GamePad(Some(Gamepad { id: 1 })),
+                PlayerType::GamePad(Some(Gamepad { id: 5 })),
+            ]
+        );
+        assert_eq!(gamepad_options(&[]), vec![PlayerType::GamePad(None)]);
+        assert!(PlayerType::human_options([]).contains(&PlayerType::AnyDevice));
+    }
+
+    #[test]
+    fn test_players_using_gamepad() {
+        let mut round_settings = RoundSettings::default();
+        round_settings.players[0].player_type = PlayerType::GamePad(Some(Gamepad { id: 0 }));
+        round_settings.players[1].player_type = PlayerType::GamePad(Some(Gamepad { id: 1 }));
+        let players: Vec<usize> = round_settings
+            .players_using_gamepad(Gamepad { id: 1 })
+            .collect();
+        assert_eq!(players, vec![1]);
+    }
+
+    #[test]
+    fn test_density_check() {
+        let settings = RoundSettings::default();
+        assert_eq!(settings.check_density(15.0), None);
+
+        let packed = RoundSettings {
+            boid_count: 2000.0,
+            arena_radius: 500.0,
+            ..default()
+        };
+        let warning = packed.check_density(15.0).unwrap();
+        assert!(warning.max_boids < 2000);
+        assert!(warning.suggested_radius > 500.0);
+        let fits = RoundSettings {
+            arena_radius: warning.suggested_radius + 1.0,
+            ..packed
+        };
+        assert_eq!(fits.check_density(15.0), None);
+    }
+
+    #[test]
+    fn test_arena_resizes_with_radius() {
+        let mut world = World::new();
+        world.insert_resource(RoundSettings::default());
+        let border = world
+            .spawn((ArenaCircle { padding: 2.0 }, Transform::default()))
+            .id();
+        let mut schedule = Schedule::new();
+        schedule.add_system(resize_arena);
+        schedule.run(&mut world);
+        let default_radius = RoundSettings::default().arena_radius;
+        assert_eq!(
+            world.get::<Transform>(border).unwrap().scale,
+            Vec3::new(default_radius + 2.0, default_radius + 2.0, 1.0)
+        );
+
+        world.resource_mut::<RoundSettings>().arena_radius = 300.0;
+        schedule.run(&mut world);
+        assert_eq!(
+            world.get::<Transform>(border).unwrap().scale,
+            Vec3::new(302.0, 302.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_round_needs_players() {
+        assert_eq!(RoundSettings::default().validate(), Ok(()));
+        let empty = RoundSettings {
+            players: Vec::new(),
+            ..default()
+        };
+        assert_eq!(empty.validate(), Err(RoundSettingsError::NoPlayers));
+    }
+
+    #[test]
+    fn test_co_op_vs_bots() {
+        let settings = RoundSettings::co_op_vs_bots();
+        assert_eq!(settings.players.len(), 8);
+        assert_eq!(settings.local_player_count(), 2);
+        assert_ne!(settings.multiplayer_mode, MultiplayerMode::SharedScreen);
+    }
+
+    #[test]
+    fn test_quick_play_difficulty_picks_the_bots() {
+        let count = |settings: &RoundSettings, bot: Bot| {
+            settings
+                .players
+                .iter()
+                .filter(|p| p.player_type == PlayerType::Bot(bot))
+                .count()
+        };
+        let easy = RoundSettings::quick_play(Difficulty::Easy);
+        let normal = RoundSettings::quick_play(Difficulty::Normal);
+        let hard = RoundSettings::quick_play(Difficulty::Hard);
+        for settings in [&easy, &normal, &hard] {
+            assert_eq!(settings.players.len(), 8);
+            assert_eq!(settings.local_player_count(), 1);
+            assert!(!settings.allow_ties);
+        }
+        // Normal is the usual mix.
+        assert_eq!(normal.players, RoundSettings::default().players);
+        assert_eq!(count(&easy, Bot::BoneHead), 6);
+        assert_eq!(count(&easy, Bot::Hunter) + count(&easy, Bot::Speedy), 0);
+        assert_eq!(count(&hard, Bot::BoneHead), 0);
+        assert_eq!(count(&hard, Bot::Hunter), 3);
+        assert!(count(&hard, Bot::Hunter) > count(&normal, Bot::Hunter));
+        assert!(count(&hard, Bot::Speedy) > count(&normal, Bot::Speedy));
+    }
+
+    #[test]
+    fn test_one_vs_one() {
+        let settings = RoundSettings::one_vs_one();
+        assert_eq!(settings.players.len(), 2);
+        assert_eq!(settings.local_player_count(), 1);
+        assert!(!settings.players[1].player_type.is_local());
+    }
+
+    #[test]
+    fn test_randomize_bots_keeps_humans() {
+        let mut round_settings = RoundSettings::default();
+        round_settings.players[2].player_type = PlayerType::Wasd;
+        let before = round_settings.players.clone();
+        round_settings.randomize_bots(&Rng::with_seed(7));
+
+        for (before, after) in before.iter().zip(round_settings.players.iter()) {
+            assert_eq!(before.color, after.color);
+            if before.player_type.is_local() {
+                assert_eq!(before.player_type, after.player_type);
+            } else {
+                assert!(PlayerType::bot_options().contains(&after.player_type));
+            }
         }
     }
 }
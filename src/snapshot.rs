@@ -0,0 +1,248 @@
+use crate::store::StoreError;
+use crate::{
+    spawn_boid_facing, Boid, BoidColor, BoidSettings, Leader, RoundSeed, RoundSettings, SceneRoot,
+    Velocity,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The folder match snapshots are saved to, relative to where the game is run from.
+const SNAPSHOTS_DIRECTORY: &str = "snapshots";
+/// Start the game with this followed by the path to a snapshot to play it from where it was saved.
+pub const RESTORE_FLAG: &str = "--restore";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoidSnapshot {
+    pub position: Vec2,
+    /// Radians from facing up
+    pub heading: f32,
+    pub speed: f32,
+    pub color: Option<BoidColor>,
+    pub leader: bool,
+}
+
+impl BoidSnapshot {
+    pub fn new(
+        transform: &Transform,
+        velocity: &Velocity,
+        color: Option<&BoidColor>,
+        leader: bool,
+    ) -> Self {
+        Self {
+            position: transform.translation.truncate(),
+            heading: transform.rotation.to_euler(EulerRot::XYZ).2,
+            speed: velocity.forward,
+            color: color.copied(),
+            leader,
+        }
+    }
+}
+
+/// Everything needed to recreate a match as it was, for attaching to bug reports.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchSnapshot {
+    pub seed: u64,
+    pub round_settings: RoundSettings,
+    pub boid_settings: BoidSettings,
+    pub boids: Vec<BoidSnapshot>,
+}
+
+impl MatchSnapshot {
+    pub fn to_json(&self) -> Result<String, StoreError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(snapshot: &str) -> Result<Self, StoreError> {
+        Ok(serde_json::from_str(snapshot)?)
+    }
+
+    /// Saves the snapshot to a new file named after the current time and returns its path.
+    pub fn save(&self) -> Result<PathBuf, StoreError> {
+        // Milliseconds so saving twice in a row doesn't overwrite the first one.
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let path = PathBuf::from(SNAPSHOTS_DIRECTORY).join(format!("match-{timestamp}.json"));
+        std::fs::create_dir_all(SNAPSHOTS_DIRECTORY)?;
+        std::fs::write(&path, self.to_json()?)?;
+        Ok(path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, StoreError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Replaces the boids of a freshly set up round with the ones in the snapshot.
+    /// Leaders are moved into place so their cameras and controls keep working, leaders that
+    /// were already captured in the snapshot are despawned and everything else is respawned.
+    pub fn restore(
+        &self,
+        commands: &mut Commands,
+        texture: Handle<Image>,
+        scene_root: Entity,
+        leaders: &mut Query<(Entity, &BoidColor, &mut Transform, &mut Velocity), With<Leader>>,
+        boids: &Query<Entity, (With<Boid>, Without<Leader>)>,
+    ) {
+        for entity in boids.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        let mut restored_leaders = Vec::new();
+        for boid in self.boids.iter() {
+            let leader = leaders
+                .iter_mut()
+                .find(|(_, color, ..)| boid.leader && Some(**color) == boid.color);
+            if let Some((entity, _, mut transform, mut velocity)) = leader {
+                transform.translation = boid.position.extend(transform.translation.z);
+                transform.rotation = Quat::from_rotation_z(boid.heading);
+                velocity.forward = boid.speed;
+                restored_leaders.push(entity);
+                continue;
+            }
+            let entity = spawn_boid_facing(commands, texture.clone(), boid.position, boid.heading);
+            commands.entity(entity).insert(Velocity {
+                forward: boid.speed,
+                ..default()
+            });
+            if let Some(color) = boid.color {
+                commands.entity(entity).insert(color);
+            }
+            commands.entity(scene_root).add_child(entity);
+        }
+        for (entity, ..) in leaders.iter() {
+            if !restored_leaders.contains(&entity) {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+/// Set when the game was started with a snapshot to restore once the round is set up.
+#[derive(Resource, Debug)]
+pub struct RestoreSnapshot(pub MatchSnapshot);
+
+/// F9 saves the current match to a file that can be attached to bug reports.
+#[allow(clippy::type_complexity)]
+pub fn dump_match_state(
+    keys: Res<Input<KeyCode>>,
+    seed: Option<Res<RoundSeed>>,
+    round_settings: Res<RoundSettings>,
+    boid_settings: Res<BoidSettings>,
+    query: Query<(&Transform, &Velocity, Option<&BoidColor>, Option<&Leader>), With<Boid>>,
+) {
+    if !keys.just_pressed(KeyCode::F9) {
+        return;
+    }
+    let snapshot = MatchSnapshot {
+        seed: seed.map(|s| s.0).unwrap_or_default(),
+        round_settings: round_settings.clone(),
+        boid_settings: boid_settings.clone(),
+        boids: query
+            .iter()
+            .map(|(transform, velocity, color, leader)| {
+                BoidSnapshot::new(transform, velocity, color, leader.is_some())
+            })
+            .collect(),
+    };
+    match snapshot.save() {
+        Ok(path) => info!("Saved match snapshot to {}", path.display()),
+        Err(e) => error!("Failed to save match snapshot: {e}"),
+    }
+}
+
+pub fn restore_snapshot(
+    mut commands: Commands,
+    snapshot: Option<Res<RestoreSnapshot>>,
+    asset_server: Res<AssetServer>,
+    scene_root: Query<Entity, With<SceneRoot>>,
+    mut leaders: Query<(Entity, &BoidColor, &mut Transform, &mut Velocity), With<Leader>>,
+    boids: Query<Entity, (With<Boid>, Without<Leader>)>,
+) {
+    let (snapshot, scene_root) = match (snapshot, scene_root.get_single()) {
+        (Some(snapshot), Ok(scene_root)) => (snapshot, scene_root),
+        _ => return,
+    };
+    snapshot.0.restore(
+        &mut commands,
+        asset_server.load("bird.png"),
+        scene_root,
+        &mut leaders,
+        &boids,
+    );
+    commands.insert_resource(RoundSeed(snapshot.0.seed));
+    commands.remove_resource::<RestoreSnapshot>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::SystemState;
+
+    #[test]
+    #[allow(clippy::type_complexity)]
+    fn test_snapshot_round_trip() {
+        let boid = |x: f32, heading: f32, color: Option<BoidColor>, leader: bool| BoidSnapshot {
+            position: Vec2::new(x, -x),
+            heading,
+            speed: 80.0,
+            color,
+            leader,
+        };
+        let snapshot = MatchSnapshot {
+            seed: 42,
+            round_settings: RoundSettings::default(),
+            boid_settings: BoidSettings::default(),
+            boids: vec![
+                boid(10.0, 0.5, Some(BoidColor::Red), true),
+                boid(20.0, -1.0, Some(BoidColor::Red), false),
+                boid(30.0, 2.0, None, false),
+            ],
+        };
+        let snapshot = MatchSnapshot::from_json(&snapshot.to_json().unwrap()).unwrap();
+
+        // A freshly set up round with a leader in the wrong place and a boid to replace.
+        let mut world = World::new();
+        let scene_root = world.spawn(SceneRoot).id();
+        world.spawn((
+            Boid::default(),
+            Leader,
+            BoidColor::Red,
+            Transform::default(),
+            Velocity::default(),
+        ));
+        world.spawn((Boid::default(), Transform::default(), Velocity::default()));
+
+        let mut system_state: SystemState<(
+            Commands,
+            Query<(Entity, &BoidColor, &mut Transform, &mut Velocity), With<Leader>>,
+            Query<Entity, (With<Boid>, Without<Leader>)>,
+        )> = SystemState::new(&mut world);
+        let (mut commands, mut leaders, boids) = system_state.get_mut(&mut world);
+        snapshot.restore(
+            &mut commands,
+            Handle::default(),
+            scene_root,
+            &mut leaders,
+            &boids,
+        );
+        system_state.apply(&mut world);
+
+        let mut restored: Vec<BoidSnapshot> = world
+            .query_filtered::<(&Transform, &Velocity, Option<&BoidColor>, Option<&Leader>), With<Boid>>()
+            .iter(&world)
+            .map(|(transform, velocity, color, leader)| {
+                BoidSnapshot::new(transform, velocity, color, leader.is_some())
+            })
+            .collect();
+        restored.sort_by(|a, b| a.position.x.total_cmp(&b.position.x));
+        assert_eq!(restored.len(), snapshot.boids.len());
+        for (restored, saved) in restored.iter().zip(snapshot.boids.iter()) {
+            assert!(restored.position.abs_diff_eq(saved.position, 0.001));
+            assert!((restored.heading - saved.heading).abs() < 0.001);
+            assert_eq!(restored.color, saved.color);
+            assert_eq!(restored.leader, saved.leader);
+        }
+    }
+}
@@ -0,0 +1,419 @@
+use crate::{Boid, BoidColor, Leader, RoundSeed, Velocity};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use itertools::Itertools;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// How many seconds ahead of the flock its momentum arrow points
+const FLOCK_ARROW_SECONDS: f32 = 1.0;
+
+/// Where a flock is and where it's going on average.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct FlockMotion {
+    pub centroid: Vec2,
+    /// The average velocity in units per second
+    pub velocity: Vec2,
+}
+
+impl FlockMotion {
+    /// Where the flock's momentum arrow ends, further ahead the faster it's going.
+    pub fn arrow_tip(&self) -> Vec2 {
+        self.centroid + self.velocity * FLOCK_ARROW_SECONDS
+    }
+}
+
+/// Start the game with this followed by a file path to record every match to a CSV file.
+pub const RECORD_FLAG: &str = "--record";
+/// How often the match recorder adds a row
+const RECORD_INTERVAL_SECONDS: f32 = 1.0;
+
+/// How many boids of each color there are, updated every frame.
+#[derive(Resource, Debug, Default)]
+pub struct BoidCensus {
+    pub counts: HashMap<BoidColor, usize>,
+    /// Boids that haven't joined a flock yet
+    pub uncolored: usize,
+    pub leaders: usize,
+    pub flocks: HashMap<BoidColor, FlockMotion>,
+}
+
+impl BoidCensus {
+    pub fn count(&self, color: &BoidColor) -> usize {
+        self.counts.get(color).copied().unwrap_or_default()
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.values().sum::<usize>() + self.uncolored
+    }
+
+    pub fn flock(&self, color: &BoidColor) -> Option<FlockMotion> {
+        self.flocks.get(color).copied()
+    }
+}
+
+/// Fun facts about the current match shown on the game over screen.
+#[derive(Resource, Debug, Default)]
+pub struct MatchStats {
+    /// How long the match has been played for
+    pub elapsed_seconds: f32,
+    /// The longest time in seconds each color went without losing a boid
+    longest_without_loss: HashMap<BoidColor, f32>,
+    current_without_loss: HashMap<BoidColor, f32>,
+    last_counts: HashMap<BoidColor, usize>,
+}
+
+impl MatchStats {
+    /// Updates the no loss streaks from this frame's census.
+    /// Gaining boids back doesn't make up for losing them, any drop restarts the streak.
+    /// Colors that have been eliminated stop counting.
+    pub fn record(&mut self, census: &BoidCensus, delta_seconds: f32) {
+        self.elapsed_seconds += delta_seconds;
+        let colors: Vec<BoidColor> = census
+            .counts
+            .keys()
+            .chain(self.last_counts.keys())
+            .copied()
+            .unique()
+            .collect();
+        for color in colors {
+            let count = census.count(&color);
+            if count == 0 {
+                self.current_without_loss.remove(&color);
+                self.last_counts.remove(&color);
+                continue;
+            }
+            let lost = count < self.last_counts.get(&color).copied().unwrap_or_default();
+            let current = self.current_without_loss.entry(color).or_default();
+            match lost {
+                true => *current = 0.0,
+                false => *current += delta_seconds,
+            }
+            let longest = self.longest_without_loss.entry(color).or_default();
+            *longest = longest.max(*current);
+            self.last_counts.insert(color, count);
+        }
+    }
+
+    /// The colors that went the longest without losing a boid and how long that was.
+    /// More than one color is returned if they tied.
+    pub fn longest_without_loss(&self) -> Option<(Vec<BoidColor>, f32)> {
+        let longest = self
+            .longest_without_loss
+            .values()
+            .copied()
+            .fold(None, |longest: Option<f32>, x| {
+                Some(longest.unwrap_or(x).max(x))
+            })?;
+        let mut colors: Vec<BoidColor> = self
+            .longest_without_loss
+            .iter()
+            .filter(|(_, seconds)| **seconds == longest)
+            .map(|(color, _)| *color)
+            .collect();
+        colors.sort_by_key(|color| color.index());
+        Some((colors, longest))
+    }
+}
+
+/// Records how many boids each color has and where their leaders are every second, so matches
+/// can be graphed for balancing. Only added when recording was turned on. Every match is added to
+/// the same file, the rows are told apart by the round's seed in the `match_id` column.
+#[derive(Resource, Debug)]
+pub struct MatchRecorder {
+    pub path: PathBuf,
+    timer: Timer,
+    seconds: f32,
+    rows: Vec<String>,
+}
+
+impl MatchRecorder {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            timer: Timer::from_seconds(RECORD_INTERVAL_SECONDS, TimerMode::Repeating),
+            seconds: 0.0,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn header() -> String {
+        ["match_id".to_string(), "seconds".to_string()]
+            .into_iter()
+            .chain(BoidColor::ALL.iter().map(|c| c.name().to_lowercase()))
+            .chain(BoidColor::ALL.iter().flat_map(|c| {
+                let name = c.name().to_lowercase();
+                [format!("{name}_leader_x"), format!("{name}_leader_y")]
+            }))
+            .join(",")
+    }
+
+    /// Adds a row for every interval that passed. Leaders that have been captured are left
+    /// blank.
+    pub fn tick(
+        &mut self,
+        match_id: u64,
+        delta_seconds: f32,
+        census: &BoidCensus,
+        leaders: &HashMap<BoidColor, Vec2>,
+    ) {
+        self.timer
+            .tick(std::time::Duration::from_secs_f32(delta_seconds));
+        self.seconds += delta_seconds;
+        for _ in 0..self.timer.times_finished_this_tick() {
+            let row = [match_id.to_string(), format!("{:.1}", self.seconds)]
+                .into_iter()
+                .chain(BoidColor::ALL.iter().map(|c| census.count(c).to_string()))
+                .chain(BoidColor::ALL.iter().flat_map(|c| match leaders.get(c) {
+                    Some(position) => [format!("{:.1}", position.x), format!("{:.1}", position.y)],
+                    None => [String::new(), String::new()],
+                }))
+                .join(",");
+            self.rows.push(row);
+        }
+    }
+
+    /// Appends the rows recorded so far to the file and starts over for the next match.
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "{}", Self::header())?;
+        }
+        for row in self.rows.drain(..) {
+            writeln!(file, "{row}")?;
+        }
+        self.reset();
+        Ok(())
+    }
+
+    pub fn reset(&mut self) {
+        self.timer.reset();
+        self.seconds = 0.0;
+        self.rows.clear();
+    }
+}
+
+pub fn record_match(
+    recorder: Option<ResMut<MatchRecorder>>,
+    census: Res<BoidCensus>,
+    leaders: Query<(&Transform, &BoidColor), With<Leader>>,
+    round_seed: Res<RoundSeed>,
+    time: Res<Time>,
+) {
+    if let Some(mut recorder) = recorder {
+        let leaders = leaders
+            .iter()
+            .map(|(transform, color)| (*color, transform.translation.truncate()))
+            .collect();
+        recorder.tick(round_seed.0, time.delta_seconds(), &census, &leaders);
+    }
+}
+
+pub fn reset_match_recorder(recorder: Option<ResMut<MatchRecorder>>) {
+    if let Some(mut recorder) = recorder {
+        recorder.reset();
+    }
+}
+
+pub fn flush_match_recorder(recorder: Option<ResMut<MatchRecorder>>) {
+    if let Some(mut recorder) = recorder {
+        match recorder.flush() {
+            Ok(_) => info!("Saved match recording to {}", recorder.path.display()),
+            Err(e) => error!("Failed to save match recording: {e}"),
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn update_boid_census(
+    mut census: ResMut<BoidCensus>,
+    query: Query<
+        (
+            Option<&BoidColor>,
+            Option<&Leader>,
+            Option<&Transform>,
+            Option<&Velocity>,
+        ),
+        With<Boid>,
+    >,
+) {
+    census.counts.clear();
+    census.uncolored = 0;
+    census.leaders = 0;
+    // Sums of the positions and velocities, turned into averages once everyone is counted.
+    let mut flocks: HashMap<BoidColor, FlockMotion> = HashMap::new();
+    for (color, leader, transform, velocity) in query.iter() {
+        match color {
+            Some(color) => *census.counts.entry(*color).or_default() += 1,
+            None => census.uncolored += 1,
+        }
+        if leader.is_some() {
+            census.leaders += 1;
+        }
+        if let (Some(color), Some(transform)) = (color, transform) {
+            let flock = flocks.entry(*color).or_default();
+            flock.centroid += transform.translation.truncate();
+            flock.velocity +=
+                transform.up().truncate() * velocity.map(|v| v.forward).unwrap_or(0.0);
+        }
+    }
+    for (color, flock) in flocks.iter_mut() {
+        let count = census.count(color) as f32;
+        flock.centroid /= count;
+        flock.velocity /= count;
+    }
+    census.flocks = flocks;
+}
+
+pub fn update_match_stats(mut stats: ResMut<MatchStats>, census: Res<BoidCensus>, time: Res<Time>) {
+    stats.record(&census, time.delta_seconds());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn census(counts: &[(BoidColor, usize)]) -> BoidCensus {
+        BoidCensus {
+            counts: counts.iter().copied().collect(),
+            ..default()
+        }
+    }
+
+    #[test]
+    fn test_census_counts_spawned_boids() {
+        let mut world = World::new();
+        world.init_resource::<BoidCensus>();
+        world.spawn((Boid::default(), BoidColor::Red, Leader));
+        world.spawn((Boid::default(), BoidColor::Red));
+        world.spawn((Boid::default(), BoidColor::Blue, Leader));
+        world.spawn(Boid::default());
+        world.spawn(BoidColor::Green);
+        let mut schedule = Schedule::new();
+        schedule.add_system(update_boid_census);
+        schedule.run(&mut world);
+
+        let census = world.resource::<BoidCensus>();
+        assert_eq!(census.total(), 4);
+        assert_eq!(census.count(&BoidColor::Red), 2);
+        assert_eq!(census.count(&BoidColor::Blue), 1);
+        assert_eq!(census.count(&BoidColor::Green), 0);
+        assert_eq!(census.uncolored, 1);
+        assert_eq!(census.leaders, 2);
+    }
+
+    #[test]
+    fn test_flock_moving_right_points_right() {
+        let mut world = World::new();
+        world.init_resource::<BoidCensus>();
+        let facing_right = Quat::from_rotation_z(-std::f32::consts::FRAC_PI_2);
+        for position in [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(10.0, 20.0, 0.0),
+            Vec3::new(20.0, -20.0, 0.0),
+        ] {
+            world.spawn((
+                Boid::default(),
+                BoidColor::Red,
+                Transform::from_translation(position).with_rotation(facing_right),
+                Velocity {
+                    forward: 100.0,
+                    ..default()
+                },
+            ));
+        }
+        let mut schedule = Schedule::new();
+        schedule.add_system(update_boid_census);
+        schedule.run(&mut world);
+
+        let flock = world
+            .resource::<BoidCensus>()
+            .flock(&BoidColor::Red)
+            .unwrap();
+        assert!(flock.centroid.abs_diff_eq(Vec2::new(10.0, 0.0), 0.001));
+        assert!(flock.velocity.abs_diff_eq(Vec2::new(100.0, 0.0), 0.001));
+        let arrow = flock.arrow_tip() - flock.centroid;
+        assert!(arrow.x > 0.0 && arrow.y.abs() < 0.001);
+    }
+
+    #[test]
+    fn test_recorder_adds_a_row_every_second() {
+        let mut recorder = MatchRecorder::new(PathBuf::from("match.csv"));
+        let counts = census(&[(BoidColor::Red, 10), (BoidColor::Blue, 4)]);
+        let leaders: HashMap<BoidColor, Vec2> = [(BoidColor::Red, Vec2::new(12.0, -3.5))]
+            .into_iter()
+            .collect();
+        for _ in 0..12 {
+            recorder.tick(42, 0.25, &counts, &leaders);
+        }
+        assert_eq!(recorder.rows.len(), 3);
+
+        let header = MatchRecorder::header();
+        let header: Vec<&str> = header.split(',').collect();
+        assert_eq!(header.len(), 2 + BoidColor::ALL.len() * 3);
+        for row in recorder.rows.iter() {
+            let row: Vec<&str> = row.split(',').collect();
+            assert_eq!(row.len(), header.len());
+            let column = |name: &str| row[header.iter().position(|h| *h == name).unwrap()];
+            assert_eq!(column("match_id"), "42");
+            assert_eq!(column("red"), "10");
+            assert_eq!(column("blue"), "4");
+            assert_eq!(column("green"), "0");
+            assert_eq!(column("red_leader_x"), "12.0");
+            assert_eq!(column("red_leader_y"), "-3.5");
+            assert_eq!(column("blue_leader_x"), "");
+        }
+    }
+
+    #[test]
+    fn test_longest_without_loss() {
+        let snapshots = [
+            census(&[(BoidColor::Red, 10), (BoidColor::Blue, 10)]),
+            census(&[(BoidColor::Red, 12), (BoidColor::Blue, 8)]),
+            census(&[(BoidColor::Red, 11), (BoidColor::Blue, 9)]),
+            census(&[(BoidColor::Red, 11), (BoidColor::Blue, 9)]),
+            census(&[(BoidColor::Red, 13), (BoidColor::Blue, 7)]),
+            census(&[(BoidColor::Red, 20)]),
+        ];
+        let mut stats = MatchStats::default();
+        for snapshot in snapshots.iter() {
+            stats.record(snapshot, 1.0);
+        }
+        // Red lost one on the third snapshot then held on for the last three.
+        assert_eq!(
+            stats.longest_without_loss(),
+            Some((vec![BoidColor::Red], 3.0))
+        );
+    }
+
+    #[test]
+    fn test_eliminated_color_stops_counting() {
+        let mut stats = MatchStats::default();
+        for (red, blue) in [(10, 5), (9, 5), (8, 0), (7, 0), (6, 0), (5, 0), (4, 0)] {
+            stats.record(
+                &census(&[(BoidColor::Red, red), (BoidColor::Blue, blue)]),
+                1.0,
+            );
+        }
+        assert_eq!(
+            stats.longest_without_loss(),
+            Some((vec![BoidColor::Blue], 2.0))
+        );
+    }
+
+    #[test]
+    fn test_longest_without_loss_tie() {
+        let mut stats = MatchStats::default();
+        assert_eq!(stats.longest_without_loss(), None);
+        for _ in 0..3 {
+            stats.record(&census(&[(BoidColor::Blue, 5), (BoidColor::Red, 5)]), 0.5);
+        }
+        assert_eq!(
+            stats.longest_without_loss(),
+            Some((vec![BoidColor::Red, BoidColor::Blue], 1.5))
+        );
+    }
+}
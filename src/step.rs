@@ -0,0 +1,152 @@
+use crate::GlobalActions;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Freezes the round so the simulation can be stepped through one frame at a time, for watching
+/// captures spread. Works while playing or paused.
+#[derive(Resource, Reflect, Debug, Default)]
+#[reflect(Resource)]
+pub struct StepMode {
+    pub enabled: bool,
+    /// Set to let the simulation run for the next frame
+    pub step_requested: bool,
+}
+
+impl StepMode {
+    /// Lets the simulation run one more frame, does nothing unless step mode is on.
+    pub fn request_step(&mut self) {
+        if self.enabled {
+            self.step_requested = true;
+        }
+    }
+}
+
+/// Run condition for the round's rules and timers, false while step mode is holding the
+/// simulation between steps so nothing can be won or run out while it's frozen.
+pub fn not_frozen(step_mode: Res<StepMode>) -> bool {
+    !step_mode.enabled || step_mode.step_requested
+}
+
+/// Turns step mode on and off and asks for the next step. Runs at the end of the frame so the
+/// step covers the whole of the next one.
+pub fn request_simulation_step(
+    action_state: Query<&ActionState<GlobalActions>>,
+    mut step_mode: ResMut<StepMode>,
+) {
+    let action_state = match action_state.get_single() {
+        Ok(action_state) => action_state,
+        Err(_) => return,
+    };
+    if action_state.just_pressed(GlobalActions::ToggleStepMode) {
+        step_mode.enabled = !step_mode.enabled;
+        step_mode.step_requested = false;
+        match step_mode.enabled {
+            true => info!("Step mode on"),
+            false => info!("Step mode off"),
+        }
+    }
+    if action_state.just_pressed(GlobalActions::StepSimulation) {
+        step_mode.request_step();
+    }
+}
+
+/// Freezes the simulation again after a step has run.
+pub fn finish_simulation_step(mut step_mode: ResMut<StepMode>) {
+    step_mode.step_requested = false;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attract::{simulating, AttractMode};
+    use crate::control::{update_control, ControlTimer};
+    use crate::stats::BoidCensus;
+    use crate::{AppState, BoidColor, GameEnded, GameEvent, RoundSettings};
+    use std::time::Duration;
+
+    #[derive(Resource, Default)]
+    struct Ticks(u32);
+
+    fn tick(mut ticks: ResMut<Ticks>) {
+        ticks.0 += 1;
+    }
+
+    #[test]
+    fn test_step_mode_runs_one_tick_per_step() {
+        let mut world = World::new();
+        world.insert_resource(State(AppState::Playing));
+        world.init_resource::<AttractMode>();
+        world.init_resource::<Ticks>();
+        world.insert_resource(StepMode {
+            enabled: true,
+            ..default()
+        });
+        let mut schedule = Schedule::new();
+        schedule.add_systems((tick.run_if(simulating), finish_simulation_step).chain());
+        let mut ticks_after = |world: &mut World, frames: u32| {
+            for _ in 0..frames {
+                schedule.run(world);
+            }
+            world.resource::<Ticks>().0
+        };
+
+        assert_eq!(ticks_after(&mut world, 3), 0);
+        world.resource_mut::<StepMode>().request_step();
+        assert_eq!(ticks_after(&mut world, 3), 1);
+
+        // Paused rounds can be stepped through too.
+        world.insert_resource(State(AppState::Paused));
+        world.resource_mut::<StepMode>().request_step();
+        world.resource_mut::<StepMode>().request_step();
+        assert_eq!(ticks_after(&mut world, 3), 2);
+
+        // Turning it off lets a playing round run every frame again.
+        world.insert_resource(State(AppState::Playing));
+        world.resource_mut::<StepMode>().enabled = false;
+        assert_eq!(ticks_after(&mut world, 3), 5);
+    }
+
+    #[test]
+    fn test_round_timers_dont_advance_while_frozen() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<GameEvent>()
+            .init_resource::<ControlTimer>()
+            .init_resource::<GameEnded>()
+            .insert_resource(BoidCensus {
+                counts: [(BoidColor::Red, 10)].into_iter().collect(),
+                ..default()
+            })
+            .insert_resource(RoundSettings {
+                control_mode: true,
+                control_seconds: 1000.0,
+                ..default()
+            })
+            .insert_resource(StepMode {
+                enabled: true,
+                ..default()
+            })
+            .add_system(update_control.run_if(not_frozen))
+            .add_system(finish_simulation_step.in_base_set(CoreSet::Last));
+        let tick = |app: &mut App, frames: usize| {
+            for _ in 0..frames {
+                std::thread::sleep(Duration::from_millis(5));
+                app.update();
+            }
+            let timer = app.world.resource::<ControlTimer>();
+            (timer.color, timer.held_seconds)
+        };
+
+        assert_eq!(tick(&mut app, 3), (None, 0.0));
+        app.world.resource_mut::<StepMode>().request_step();
+        assert_eq!(tick(&mut app, 1), (Some(BoidColor::Red), 0.0));
+        app.world.resource_mut::<StepMode>().request_step();
+        let (_, stepped) = tick(&mut app, 1);
+        assert!(stepped > 0.0);
+        assert_eq!(tick(&mut app, 3), (Some(BoidColor::Red), stepped));
+
+        app.world.resource_mut::<StepMode>().enabled = false;
+        let (_, running) = tick(&mut app, 2);
+        assert!(running > stepped);
+    }
+}
@@ -0,0 +1,139 @@
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Formatter;
+
+/// Something went wrong reading or writing one of the files the game keeps between launches.
+#[derive(Debug)]
+pub enum StoreError {
+    /// File names can't be empty or contain anything that would make them a path.
+    InvalidName(String),
+    Io(std::io::Error),
+    Ron(ron::Error),
+    Json(serde_json::Error),
+}
+
+impl std::fmt::Display for StoreError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoreError::InvalidName(name) => write!(f, "Invalid name \"{name}\""),
+            StoreError::Io(e) => write!(f, "{e}"),
+            StoreError::Ron(e) => write!(f, "{e}"),
+            StoreError::Json(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for StoreError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ron::Error> for StoreError {
+    fn from(e: ron::Error) -> Self {
+        Self::Ron(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for StoreError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Self::Ron(e.code)
+    }
+}
+
+impl From<serde_json::Error> for StoreError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+pub fn to_ron(value: &impl Serialize) -> Result<String, StoreError> {
+    Ok(ron::ser::to_string_pretty(
+        value,
+        ron::ser::PrettyConfig::default(),
+    )?)
+}
+
+pub fn from_ron<T: DeserializeOwned>(text: &str) -> Result<T, StoreError> {
+    Ok(ron::from_str(text)?)
+}
+
+/// Settings that are kept in a RON file between launches.
+/// Use `#[serde(default)]` on them so files saved before a setting existed still load.
+pub trait SettingsFile: Serialize + DeserializeOwned + Default {
+    /// The file the settings are saved to, relative to where the game is run from.
+    const FILE: &'static str;
+
+    fn save(&self) -> Result<(), StoreError> {
+        std::fs::write(Self::FILE, to_ron(self)?)?;
+        Ok(())
+    }
+
+    /// Returns the defaults if nothing has been saved yet.
+    fn load() -> Result<Self, StoreError> {
+        match std::fs::read_to_string(Self::FILE) {
+            Ok(text) => from_ron(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Loads the settings into the app, keeping the defaults if they can't be read.
+pub fn insert_settings_file<T: SettingsFile + Resource>(app: &mut App) {
+    match T::load() {
+        Ok(settings) => {
+            app.insert_resource(settings);
+        }
+        Err(e) => error!("Failed to load {}: {e}", T::FILE),
+    }
+}
+
+/// Run condition that's true when the settings were changed after they were loaded, so the file
+/// isn't written back every launch.
+pub fn settings_changed<T: Resource>(settings: Option<Res<T>>) -> bool {
+    settings
+        .map(|settings| settings.is_changed() && !settings.is_added())
+        .unwrap_or_default()
+}
+
+pub fn save_settings_file<T: SettingsFile + Resource>(settings: Res<T>) {
+    if let Err(e) = settings.save() {
+        error!("Failed to save {}: {e}", T::FILE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct Volume(f32);
+
+    #[derive(Resource, Default)]
+    struct Saves(usize);
+
+    fn count_saves(mut saves: ResMut<Saves>) {
+        saves.0 += 1;
+    }
+
+    #[test]
+    fn test_loading_settings_doesnt_save_them() {
+        let mut world = World::new();
+        world.init_resource::<Saves>();
+        world.init_resource::<Volume>();
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(count_saves.run_if(settings_changed::<Volume>));
+        schedule.run(&mut world);
+        assert_eq!(world.resource::<Saves>().0, 0);
+
+        world.resource_mut::<Volume>().0 = 0.5;
+        schedule.run(&mut world);
+        assert_eq!(world.resource::<Saves>().0, 1);
+
+        schedule.run(&mut world);
+        assert_eq!(world.resource::<Saves>().0, 1);
+    }
+}
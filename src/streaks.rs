@@ -0,0 +1,92 @@
+use crate::{BoidColor, RoundSettings};
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+
+/// How long it takes a color's recent capture count to fade to about a third.
+const STREAK_DECAY_SECONDS: f32 = 2.0;
+
+/// Tracks how quickly each color has been capturing boids so a flock that captures a lot in
+/// a short time can go "on fire" and get a speed boost.
+#[derive(Resource, Debug, Default)]
+pub struct CaptureStreaks {
+    /// Captures fade over time, so this is roughly how many captures were made recently.
+    recent_captures: HashMap<BoidColor, f32>,
+    on_fire: HashSet<BoidColor>,
+}
+
+impl CaptureStreaks {
+    pub fn record_capture(&mut self, color: BoidColor) {
+        *self.recent_captures.entry(color).or_default() += 1.0;
+    }
+
+    /// Fades the recent captures and updates which colors are on fire.
+    /// Colors stay on fire until they drop below half the threshold so they don't flicker.
+    pub fn update(&mut self, delta_seconds: f32, threshold: f32) {
+        let decay = (-delta_seconds / STREAK_DECAY_SECONDS).exp();
+        for (color, captures) in self.recent_captures.iter_mut() {
+            *captures *= decay;
+            if threshold > 0.0 && *captures >= threshold {
+                self.on_fire.insert(*color);
+            } else if threshold <= 0.0 || *captures < threshold * 0.5 {
+                self.on_fire.remove(color);
+            }
+        }
+    }
+
+    pub fn is_on_fire(&self, color: &BoidColor) -> bool {
+        self.on_fire.contains(color)
+    }
+
+    /// The max speed multiplier for boids of this color.
+    pub fn speed_multiplier(
+        &self,
+        color: Option<&BoidColor>,
+        round_settings: &RoundSettings,
+    ) -> f32 {
+        match color.map(|c| self.is_on_fire(c)).unwrap_or_default() {
+            true => round_settings.streak_speed_multiplier,
+            false => 1.0,
+        }
+    }
+}
+
+pub fn update_capture_streaks(
+    mut streaks: ResMut<CaptureStreaks>,
+    time: Res<Time>,
+    round_settings: Res<RoundSettings>,
+) {
+    streaks.update(time.delta_seconds(), round_settings.streak_threshold);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_streak_starts_and_ends_with_capture_rate() {
+        let mut streaks = CaptureStreaks::default();
+        for _ in 0..30 {
+            streaks.record_capture(BoidColor::Red);
+        }
+        streaks.record_capture(BoidColor::Blue);
+        streaks.update(1.0 / 60.0, 20.0);
+        assert!(streaks.is_on_fire(&BoidColor::Red));
+        assert!(!streaks.is_on_fire(&BoidColor::Blue));
+
+        // Captures slowed down so the streak fades out.
+        streaks.update(1.0, 20.0);
+        assert!(streaks.is_on_fire(&BoidColor::Red));
+        streaks.update(5.0, 20.0);
+        assert!(!streaks.is_on_fire(&BoidColor::Red));
+    }
+
+    #[test]
+    fn test_streaks_are_off_by_default() {
+        let mut streaks = CaptureStreaks::default();
+        for _ in 0..100 {
+            streaks.record_capture(BoidColor::Red);
+        }
+        streaks.update(1.0 / 60.0, RoundSettings::default().streak_threshold);
+        assert!(!streaks.is_on_fire(&BoidColor::Red));
+    }
+}
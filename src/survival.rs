@@ -0,0 +1,201 @@
+use crate::boids::{spawn_positions, SpawnIn};
+use crate::{
+    spawn_boid, Boid, BoidColor, GameEnded, GameEvent, RoundSettings, SceneRoot, Winner,
+    ARENA_PADDING,
+};
+use bevy::prelude::*;
+use itertools::Itertools;
+use turborand::prelude::*;
+
+/// Keeps track of when to spawn the next wave and when a survival round ends.
+#[derive(Resource, Debug)]
+pub struct SurvivalTimers {
+    pub wave: Timer,
+    pub round: Timer,
+    /// Seeds where the next wave is placed, derived from the round's seed so the whole round can
+    /// be recreated. Moves on after every wave so they don't all land on the same spots.
+    pub seed: u64,
+}
+
+impl SurvivalTimers {
+    pub fn new(round_settings: &RoundSettings, seed: u64) -> Self {
+        Self {
+            wave: Timer::from_seconds(round_settings.wave_interval, TimerMode::Repeating),
+            round: Timer::from_seconds(round_settings.survival_seconds, TimerMode::Once),
+            seed,
+        }
+    }
+}
+
+/// Returns how many boids the next wave should add without going over `max_boids`.
+pub fn wave_spawn_count(current: usize, wave_size: usize, max_boids: usize) -> usize {
+    wave_size.min(max_boids.saturating_sub(current))
+}
+
+/// Returns the color with the most boids, or None if it's a tie.
+pub fn largest_flock(colors: impl Iterator<Item = BoidColor>) -> Option<BoidColor> {
+    let counts = colors.counts();
+    let mut largest = counts.iter().max_set_by_key(|(_, count)| **count);
+    match largest.len() {
+        1 => largest.pop().map(|(color, _)| *color),
+        _ => None,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_waves(
+    mut commands: Commands,
+    timers: Option<ResMut<SurvivalTimers>>,
+    boids: Query<(), With<Boid>>,
+    scene_root: Query<Entity, With<SceneRoot>>,
+    asset_server: Res<AssetServer>,
+    round_settings: Res<RoundSettings>,
+    time: Res<Time>,
+) {
+    let mut timers = match timers {
+        Some(timers) => timers,
+        None => return,
+    };
+    timers.round.tick(time.delta());
+    timers.wave.tick(time.delta());
+    let scene_root = match scene_root.get_single() {
+        Ok(scene_root) => scene_root,
+        Err(_) => return,
+    };
+
+    let rand = Rng::with_seed(timers.seed);
+    let mut current = boids.iter().count();
+    for _ in 0..timers.wave.times_finished_this_tick() {
+        let count = wave_spawn_count(current, round_settings.wave_size, round_settings.max_boids);
+        // New boids don't need to be spaced out from the ones already flying around.
+        for position in spawn_positions(
+            count,
+            round_settings.arena_radius - ARENA_PADDING,
+            0.0,
+            &rand,
+        ) {
+            let entity = spawn_boid(&mut commands, &asset_server, position, &rand);
+            commands.entity(entity).insert(SpawnIn::new(0.0));
+            commands.entity(scene_root).add_child(entity);
+        }
+        current += count;
+        timers.seed = rand.gen_u64();
+    }
+}
+
+/// Ends a survival round once its time runs out, the color with the most boids wins. When ties
+/// aren't allowed a tied round keeps going until one flock pulls ahead.
+pub fn end_survival_round(
+    timers: Option<Res<SurvivalTimers>>,
+    colors: Query<&BoidColor>,
+    mut game_ended: ResMut<GameEnded>,
+    mut event_writer: EventWriter<GameEvent>,
+    round_settings: Res<RoundSettings>,
+) {
+    if game_ended.0 || !timers.map(|t| t.round.finished()).unwrap_or_default() {
+        return;
+    }
+    let winner = largest_flock(colors.iter().copied()).map(|color| Winner { color });
+    if winner.is_none() && !round_settings.allow_ties && !colors.is_empty() {
+        return;
+    }
+    game_ended.0 = true;
+    event_writer.send(GameEvent::GameOver(winner));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_waves_add_boids_up_to_the_cap() {
+        let mut current = 10;
+        let mut counts = Vec::new();
+        for _ in 0..5 {
+            current += wave_spawn_count(current, 20, 65);
+            counts.push(current);
+        }
+        assert_eq!(counts, [30, 50, 65, 65, 65]);
+    }
+
+    #[test]
+    fn test_waves_grow_in_and_follow_the_seed() {
+        use std::time::Duration;
+
+        let wave_positions = |seed: u64| {
+            let round_settings = RoundSettings {
+                survival_mode: true,
+                wave_interval: 0.001,
+                wave_size: 5,
+                max_boids: 5,
+                ..default()
+            };
+            let mut app = App::new();
+            app.add_plugins(MinimalPlugins)
+                .add_plugin(AssetPlugin::default())
+                .insert_resource(SurvivalTimers::new(&round_settings, seed))
+                .insert_resource(round_settings)
+                .add_system(spawn_waves);
+            app.world.spawn((SceneRoot, SpatialBundle::default()));
+            for _ in 0..2 {
+                std::thread::sleep(Duration::from_millis(5));
+                app.update();
+            }
+            assert_eq!(app.world.query::<&Boid>().iter(&app.world).count(), 5);
+            app.world
+                .query_filtered::<&Transform, (With<Boid>, With<SpawnIn>)>()
+                .iter(&app.world)
+                .map(|transform| transform.translation.truncate())
+                .collect::<Vec<Vec2>>()
+        };
+        let positions = wave_positions(7);
+        assert_eq!(positions.len(), 5);
+        assert_eq!(wave_positions(7), positions);
+        assert_ne!(wave_positions(8), positions);
+    }
+
+    #[test]
+    fn test_tied_round_keeps_playing_without_ties() {
+        use crate::attract::AttractMode;
+        use crate::boids::leader_defeated;
+        use crate::AppState;
+        use std::time::Duration;
+
+        let next_state = |allow_ties: bool| {
+            let mut world = World::new();
+            let round_settings = RoundSettings {
+                survival_mode: true,
+                allow_ties,
+                ..default()
+            };
+            let mut timers = SurvivalTimers::new(&round_settings, 0);
+            timers
+                .round
+                .tick(Duration::from_secs_f32(round_settings.survival_seconds));
+            world.insert_resource(timers);
+            world.insert_resource(round_settings);
+            world.insert_resource(State(AppState::Playing));
+            world.init_resource::<NextState<AppState>>();
+            world.init_resource::<AttractMode>();
+            world.init_resource::<GameEnded>();
+            world.init_resource::<Events<GameEvent>>();
+            world.spawn(BoidColor::Red);
+            world.spawn(BoidColor::Blue);
+
+            let mut schedule = Schedule::new();
+            schedule.add_systems((end_survival_round, leader_defeated).chain());
+            schedule.run(&mut world);
+            world.resource::<NextState<AppState>>().0.clone()
+        };
+        assert_eq!(next_state(true), Some(AppState::GameOver));
+        assert_eq!(next_state(false), None);
+    }
+
+    #[test]
+    fn test_largest_flock() {
+        use BoidColor::*;
+        assert_eq!(largest_flock([Red, Blue, Red].into_iter()), Some(Red));
+        assert_eq!(largest_flock([Red, Blue].into_iter()), None);
+        assert_eq!(largest_flock(std::iter::empty()), None);
+    }
+}
@@ -0,0 +1,96 @@
+use crate::boids::is_neighbor_update_frame;
+use crate::math::convex_hull;
+use crate::{Boid, BoidColor, BoidSettings, SceneRoot};
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::sprite::Mesh2dHandle;
+use bevy::utils::HashMap;
+
+/// Drawn between the arena and the boids
+const TERRITORY_Z: f32 = 1.0;
+const TERRITORY_ALPHA: f32 = 0.15;
+
+/// A translucent shape drawn around all the boids of a color to show how much of the arena
+/// they cover.
+#[derive(Component, Debug)]
+pub struct Territory(pub BoidColor);
+
+/// Builds a filled mesh from a convex polygon.
+pub fn territory_mesh(hull: &[Vec2]) -> Mesh {
+    let positions: Vec<[f32; 3]> = hull.iter().map(|p| [p.x, p.y, 0.0]).collect();
+    let normals = vec![[0.0, 0.0, 1.0]; hull.len()];
+    let uvs = vec![[0.0, 0.0]; hull.len()];
+    // The hull is convex so a fan of triangles from the first point covers it.
+    let indices = (1..hull.len().saturating_sub(1) as u32)
+        .flat_map(|i| [0, i, i + 1])
+        .collect();
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+/// Run condition for rebuilding the territories every `territory_update_interval` frames.
+pub fn territory_update_due(mut frame: Local<u32>, boid_settings: Res<BoidSettings>) -> bool {
+    let due = is_neighbor_update_frame(*frame, boid_settings.territory_update_interval);
+    *frame = frame.wrapping_add(1);
+    due
+}
+
+pub fn update_territories(
+    mut commands: Commands,
+    boids: Query<(&Transform, &BoidColor), With<Boid>>,
+    mut territories: Query<(&Territory, &Mesh2dHandle, &mut Visibility)>,
+    scene_root: Query<Entity, With<SceneRoot>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    boid_settings: Res<BoidSettings>,
+) {
+    if !boid_settings.territory_enabled {
+        for (_, _, mut visibility) in territories.iter_mut() {
+            *visibility = Visibility::Hidden;
+        }
+        return;
+    }
+
+    let mut flocks: HashMap<BoidColor, Vec<Vec2>> = HashMap::new();
+    for (transform, color) in boids.iter() {
+        flocks
+            .entry(*color)
+            .or_default()
+            .push(transform.translation.truncate());
+    }
+
+    for (territory, mesh_handle, mut visibility) in territories.iter_mut() {
+        let hull = flocks
+            .remove(&territory.0)
+            .map(|points| convex_hull(&points))
+            .unwrap_or_default();
+        if let Some(mesh) = meshes.get_mut(&mesh_handle.0) {
+            *mesh = territory_mesh(&hull);
+        }
+        *visibility = Visibility::Inherited;
+    }
+
+    // Any colors left over don't have a territory yet.
+    let scene_root = match scene_root.get_single() {
+        Ok(scene_root) => scene_root,
+        Err(_) => return,
+    };
+    for (color, points) in flocks {
+        let territory = commands
+            .spawn(ColorMesh2dBundle {
+                mesh: meshes.add(territory_mesh(&convex_hull(&points))).into(),
+                material: materials.add(ColorMaterial::from(*color.color().set_a(TERRITORY_ALPHA))),
+                transform: Transform::from_xyz(0.0, 0.0, TERRITORY_Z),
+                ..default()
+            })
+            .insert(Territory(color))
+            .insert(Name::new(format!("{color} Territory")))
+            .id();
+        commands.entity(scene_root).add_child(territory);
+    }
+}
@@ -0,0 +1,98 @@
+use crate::ui::systems::ComboBoxEnum;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Self; 2] = [Self::English, Self::Spanish];
+
+    /// The translations bundled into the game for this language.
+    fn bundled_strings(&self) -> &'static str {
+        match self {
+            Language::English => include_str!("../../assets/lang/en.ron"),
+            Language::Spanish => include_str!("../../assets/lang/es.ron"),
+        }
+    }
+}
+
+impl ComboBoxEnum for Language {
+    fn combo_box_label() -> &'static str {
+        "Language"
+    }
+
+    fn values(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(Self::ALL.iter().copied())
+    }
+
+    fn value_label(&self, _localization: &Localization) -> String {
+        // Always shown in the language itself so players can find theirs.
+        match self {
+            Language::English => "English",
+            Language::Spanish => "Español",
+        }
+        .to_string()
+    }
+}
+
+/// Looks up the text shown in the UI for the currently selected language.
+#[derive(Debug, Resource)]
+pub struct Localization {
+    pub language: Language,
+    strings: HashMap<Language, HashMap<String, String>>,
+}
+
+impl Default for Localization {
+    fn default() -> Self {
+        let strings = Language::ALL
+            .iter()
+            .map(|language| {
+                let strings = ron::from_str(language.bundled_strings()).unwrap_or_else(|e| {
+                    error!("Failed to load {language:?} translations: {e}");
+                    HashMap::default()
+                });
+                (*language, strings)
+            })
+            .collect();
+        Self {
+            language: Language::default(),
+            strings,
+        }
+    }
+}
+
+impl Localization {
+    /// Returns the translation of `id` in the current language.
+    /// Falls back to the id itself if there isn't one so missing strings are easy to spot.
+    pub fn t<'a>(&'a self, id: &'a str) -> &'a str {
+        self.strings
+            .get(&self.language)
+            .and_then(|strings| strings.get(id))
+            .map(String::as_str)
+            .unwrap_or(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_switching_language() {
+        let mut localization = Localization::default();
+        assert_eq!(localization.t("quick_play"), "Quick Play");
+        localization.language = Language::Spanish;
+        assert_eq!(localization.t("quick_play"), "Partida rápida");
+    }
+
+    #[test]
+    fn test_missing_key_falls_back_to_id() {
+        let localization = Localization::default();
+        assert_eq!(localization.t("not_a_real_key"), "not_a_real_key");
+    }
+}
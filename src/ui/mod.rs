@@ -1,11 +1,14 @@
 mod components;
+mod localization;
 mod style;
 mod systems;
 
 pub use components::*;
 
+use crate::camera::winner_sweep_done;
 use crate::AppState;
 use bevy::prelude::*;
+use localization::Localization;
 use systems::*;
 
 pub struct UiAppPlugin;
@@ -17,6 +20,7 @@ pub enum UiState {
     CustomGameMenu,
     PauseMenu,
     SettingsMenu,
+    History,
     Hidden,
 }
 
@@ -25,13 +29,58 @@ impl Plugin for UiAppPlugin {
         app.add_event::<UiEvent>()
             .add_state::<UiState>()
             .add_startup_system(set_ui_theme)
-            .add_startup_system(lock_mouse);
+            .add_startup_system(lock_mouse)
+            .add_startup_system(restore_display_settings)
+            .add_startup_system(restore_accessibility_settings);
         // Settings does not need to lock/unlock mouse since it will be opened from another menu
-        app.add_system(draw_settings.in_set(OnUpdate(UiState::SettingsMenu)));
-        app.add_system(draw_pause_menu.in_set(OnUpdate(UiState::PauseMenu)));
+        app.add_system(
+            draw_settings
+                .run_if(hud_visible)
+                .in_set(OnUpdate(UiState::SettingsMenu)),
+        );
+        app.add_system(
+            draw_pause_menu
+                .run_if(hud_visible)
+                .in_set(OnUpdate(UiState::PauseMenu)),
+        );
         app.add_system(unlock_mouse.in_schedule(OnEnter(UiState::PauseMenu)));
         app.add_system(lock_mouse.in_schedule(OnEnter(UiState::Hidden)));
-        app.add_system(draw_game_over.in_set(OnUpdate(AppState::GameOver)));
+        app.add_system(
+            draw_game_over
+                .run_if(hud_visible)
+                .run_if(winner_sweep_done)
+                .in_set(OnUpdate(AppState::GameOver)),
+        );
+        app.add_system(
+            draw_on_fire_indicators
+                .run_if(hud_visible)
+                .in_set(OnUpdate(AppState::Playing)),
+        );
+        app.add_system(
+            draw_edge_indicators
+                .run_if(hud_visible)
+                .in_set(OnUpdate(AppState::Playing)),
+        );
+        app.add_system(
+            draw_flock_arrows
+                .run_if(hud_visible)
+                .in_set(OnUpdate(AppState::Playing)),
+        );
+        app.add_system(
+            draw_capture_flashes
+                .run_if(hud_visible)
+                .in_set(OnUpdate(AppState::Playing)),
+        );
+        app.add_system(
+            draw_population_bar
+                .run_if(hud_visible)
+                .in_set(OnUpdate(AppState::Playing)),
+        );
+        app.add_system(
+            draw_spectator_label
+                .run_if(hud_visible)
+                .in_set(OnUpdate(AppState::Playing)),
+        );
         app.add_system(unlock_mouse.in_schedule(OnEnter(AppState::GameOver)));
         app.add_system(lock_mouse.in_schedule(OnExit(AppState::GameOver)));
         app.add_system(draw_title.in_set(OnUpdate(AppState::Title)));
@@ -40,15 +89,20 @@ impl Plugin for UiAppPlugin {
         app.add_system(on_title_enter.in_schedule(OnEnter(AppState::Title)));
         app.add_system(on_title_exit.in_schedule(OnExit(AppState::Title)));
         app.add_system(draw_round_settings.in_set(OnUpdate(UiState::CustomGameMenu)));
+        app.add_system(draw_history.in_set(OnUpdate(UiState::History)));
         app.add_system(unlock_mouse.in_schedule(OnEnter(UiState::CustomGameMenu)));
+        app.add_system(on_custom_game_enter.in_schedule(OnEnter(UiState::CustomGameMenu)));
         app.add_system(lock_mouse.in_schedule(OnExit(UiState::CustomGameMenu)));
         app.add_system(toggle_pause_hotkey);
         app.add_system(on_focused);
         app.add_system(on_click);
         app.add_system(toggle_fullscreen);
+        app.add_system(toggle_ui_visibility);
         app.add_system(handle_ui_events.in_base_set(CoreSet::PostUpdate));
         app.add_system(hide_ui.in_schedule(OnEnter(AppState::Playing)));
         app.add_system(show_pause_menu.in_schedule(OnEnter(AppState::Paused)));
         app.insert_resource(UiData::default());
+        app.insert_resource(HudHidden::default());
+        app.insert_resource(Localization::default());
     }
 }
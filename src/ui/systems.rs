@@ -1,41 +1,141 @@
-use crate::round::PlayerSettings;
+use crate::accessibility::AccessibilitySettings;
+use crate::camera::{
+    edge_indicator, Camera2dFollow, Camera2dFollowMany, CameraFollowTarget, CameraSettings,
+    CaptureFlashes, Spectating,
+};
+use crate::display::DisplaySettings;
+use crate::history::MatchHistory;
+use crate::round::{Difficulty, PlayerSettings};
+use crate::stats::{BoidCensus, MatchStats};
+use crate::streaks::CaptureStreaks;
+use crate::ui::localization::{Language, Localization};
 use crate::ui::style::get_style;
 use crate::ui::{Logo, UiState};
+use crate::viewports::ViewportRelative;
 use crate::{
-    AppState, BoidColor, Bot, GlobalActions, MultiplayerMode, PlayerType, RoundSettings, Winner,
+    AppState, BoidColor, BoidSettings, Bot, GlobalActions, Leader, MultiplayerMode, PlayerActions,
+    PlayerType, RoundSettings, Winner,
 };
 use bevy::input::mouse::MouseButtonInput;
 use bevy::prelude::*;
-use bevy::window::{CursorGrabMode, PrimaryWindow, WindowFocused, WindowMode, WindowResolution};
+use bevy::window::{CursorGrabMode, PrimaryWindow, WindowFocused, WindowMode};
 use bevy_egui::egui::{Align, Align2, InnerResponse, Response, Ui};
 use bevy_egui::{egui, EguiContexts};
 use bevy_egui_kbgp::KbgpEguiResponseExt;
 use egui::vec2;
 use leafwing_input_manager::prelude::*;
+use std::collections::HashMap;
 use std::fmt::Debug;
+use turborand::prelude::*;
 
 #[derive(Debug, Reflect, Resource)]
 #[reflect(Resource)]
 pub struct UiData {
     pub round_settings: RoundSettings,
+    /// The flock physics being set up in the custom game menu. Copied from the live settings when
+    /// the menu opens so anything tuned in the inspector carries over.
+    pub boid_settings: BoidSettings,
     #[reflect(ignore)]
     pub window_mode: WindowMode,
     pub window_width: f32,
     pub window_height: f32,
+    #[reflect(ignore)]
+    pub language: Language,
+    /// The name of the boid settings preset to save or load
+    pub preset_name: String,
+    pub reduce_motion: bool,
+    pub path_preview: bool,
+    pub boundary_ring: bool,
+    /// How much the camera looks ahead of the player's leader, see `CameraSettings::lead_blend`
+    pub camera_lead: f32,
+    /// The bots Quick Play is played against
+    #[reflect(ignore)]
+    pub difficulty: Difficulty,
+    /// The menu to go back to when the settings menu is closed
+    #[reflect(ignore)]
+    pub settings_return_to: UiState,
+}
+
+/// Hides all of the UI so players can take clean screenshots.
+#[derive(Debug, Default, Resource)]
+pub struct HudHidden {
+    pub hidden: bool,
+    /// If debug lines were turned on before hiding so they can be turned back on.
+    debug_lines: bool,
 }
 
+impl HudHidden {
+    pub fn hide(&mut self, boid_settings: &mut BoidSettings) {
+        if !self.hidden {
+            self.hidden = true;
+            self.debug_lines = boid_settings.debug_lines;
+            boid_settings.debug_lines = false;
+        }
+    }
+
+    pub fn show(&mut self, boid_settings: &mut BoidSettings) {
+        if self.hidden {
+            self.hidden = false;
+            boid_settings.debug_lines = self.debug_lines;
+        }
+    }
+}
+
+/// Run condition for systems that draw UI
+pub fn hud_visible(hud_hidden: Res<HudHidden>) -> bool {
+    !hud_hidden.hidden
+}
+
+/// How long a player's row stays highlighted after pressing a button on their gamepad
+const IDENTIFY_GAMEPAD_SECONDS: f32 = 1.0;
+
 #[derive(Debug)]
 pub enum UiEvent {
     SettingsSaved,
+    /// Hand all the human players' flocks over to bots and keep watching.
+    SpectateBots,
+    SaveBoidPreset,
+    LoadBoidPreset,
+}
+
+impl UiData {
+    /// Opens the settings menu, remembering which menu to go back to when it's closed.
+    pub fn open_settings(&mut self, from: UiState, next_ui_state: &mut NextState<UiState>) {
+        self.settings_return_to = from;
+        next_ui_state.set(UiState::SettingsMenu);
+    }
+
+    pub fn close_settings(&self, next_ui_state: &mut NextState<UiState>) {
+        next_ui_state.set(self.settings_return_to.clone());
+    }
+
+    /// Uses the settings picked in the custom game menu for the next round.
+    pub fn apply_custom_game(
+        &self,
+        round_settings: &mut RoundSettings,
+        boid_settings: &mut BoidSettings,
+    ) {
+        *round_settings = self.round_settings.clone();
+        *boid_settings = self.boid_settings.clone();
+    }
 }
 
 impl Default for UiData {
     fn default() -> Self {
         Self {
             round_settings: Default::default(),
+            boid_settings: Default::default(),
             window_mode: WindowMode::Windowed,
             window_width: 1280.0,
             window_height: 800.0,
+            language: Language::default(),
+            preset_name: String::new(),
+            reduce_motion: false,
+            path_preview: false,
+            boundary_ring: true,
+            camera_lead: 0.0,
+            difficulty: Difficulty::default(),
+            settings_return_to: UiState::Title,
         }
     }
 }
@@ -60,6 +160,9 @@ pub fn draw_pause_menu(
     mut next_app_state: ResMut<NextState<AppState>>,
     mut next_ui_state: ResMut<NextState<UiState>>,
     app_state: Res<State<AppState>>,
+    localization: Res<Localization>,
+    mut ui_event_writer: EventWriter<UiEvent>,
+    mut ui_data: ResMut<UiData>,
 ) {
     egui::Window::new("Game Paused")
         .anchor(Align2::CENTER_CENTER, vec2(0.0, 120.0))
@@ -67,13 +170,13 @@ pub fn draw_pause_menu(
         .collapsible(false)
         .title_bar(false)
         .show(egui_context.ctx_mut(), |ui| {
-            ui.vertical_centered(|ui| ui.heading("Game Paused"));
+            ui.vertical_centered(|ui| ui.heading(localization.t("game_paused")));
             ui.separator();
             ui.set_width(220.0);
             ui.vertical_centered_justified(|ui| {
                 if app_state.0 != AppState::Playing
                     && ui
-                        .button("Resume")
+                        .button(localization.t("resume"))
                         .kbgp_navigation()
                         .kbgp_initial_focus()
                         .clicked()
@@ -81,15 +184,36 @@ pub fn draw_pause_menu(
                     next_app_state.set(AppState::Playing);
                 }
 
-                if ui.button("Restart").kbgp_navigation().clicked() {
+                if ui
+                    .button(localization.t("restart"))
+                    .kbgp_navigation()
+                    .clicked()
+                {
                     next_app_state.set(AppState::LoadRound);
                 }
 
-                if ui.button("Settings").kbgp_navigation().clicked() {
-                    next_ui_state.set(UiState::SettingsMenu);
+                if ui
+                    .button(localization.t("spectate_bots"))
+                    .kbgp_navigation()
+                    .clicked()
+                {
+                    ui_event_writer.send(UiEvent::SpectateBots);
+                    next_app_state.set(AppState::Playing);
+                }
+
+                if ui
+                    .button(localization.t("settings"))
+                    .kbgp_navigation()
+                    .clicked()
+                {
+                    ui_data.open_settings(UiState::PauseMenu, &mut next_ui_state);
                 }
 
-                if ui.button("Return to Title").kbgp_navigation().clicked() {
+                if ui
+                    .button(localization.t("return_to_title"))
+                    .kbgp_navigation()
+                    .clicked()
+                {
                     next_app_state.set(AppState::Title);
                     next_ui_state.set(UiState::Title);
                 }
@@ -102,6 +226,9 @@ pub fn draw_title(
     #[cfg(not(target_arch = "wasm32"))] mut exit: EventWriter<bevy::app::AppExit>,
     mut app_state: ResMut<NextState<AppState>>,
     mut ui_state: ResMut<NextState<UiState>>,
+    mut round_settings: ResMut<RoundSettings>,
+    mut ui_data: ResMut<UiData>,
+    localization: Res<Localization>,
 ) {
     egui::Window::new("Flock Fusion")
         .title_bar(false)
@@ -112,38 +239,80 @@ pub fn draw_title(
             ui.set_width(200.0);
             ui.vertical_centered_justified(|ui| {
                 if ui
-                    .button("Quick Play")
+                    .button(localization.t("quick_play"))
                     .kbgp_navigation()
                     .kbgp_initial_focus()
                     .clicked()
                 {
+                    *round_settings = RoundSettings::quick_play(ui_data.difficulty);
                     app_state.set(AppState::LoadRound);
                 }
+                ui.horizontal(|ui| {
+                    for difficulty in Difficulty::ALL {
+                        ui.selectable_value(
+                            &mut ui_data.difficulty,
+                            difficulty,
+                            localization.t(difficulty.key()),
+                        )
+                        .kbgp_navigation();
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .button(localization.t("co_op_vs_bots"))
+                        .kbgp_navigation()
+                        .clicked()
+                    {
+                        *round_settings = RoundSettings::co_op_vs_bots();
+                        app_state.set(AppState::LoadRound);
+                    }
+                    if ui
+                        .button(localization.t("one_vs_one"))
+                        .kbgp_navigation()
+                        .clicked()
+                    {
+                        *round_settings = RoundSettings::one_vs_one();
+                        app_state.set(AppState::LoadRound);
+                    }
+                });
 
                 if ui
-                    .button("Custom Game")
+                    .button(localization.t("custom_game"))
                     .kbgp_navigation()
                     .kbgp_initial_focus()
                     .clicked()
                 {
                     ui_state.set(UiState::CustomGameMenu);
                 }
-                ui.small("^ Play custom with friends! ^");
+                ui.small(localization.t("custom_game_hint"));
 
                 if ui
-                    .button("Settings")
+                    .button(localization.t("settings"))
                     .kbgp_navigation()
                     .kbgp_initial_focus()
                     .clicked()
                 {
-                    ui_state.set(UiState::SettingsMenu);
+                    ui_data.open_settings(UiState::Title, &mut ui_state);
+                }
+
+                if ui
+                    .button(localization.t("history"))
+                    .kbgp_navigation()
+                    .clicked()
+                {
+                    ui_state.set(UiState::History);
                 }
 
                 #[cfg(not(target_arch = "wasm32"))]
                 {
                     ui.separator();
 
-                    if ui.button("Exit Game").kbgp_navigation().clicked() {
+                    if ui
+                        .button(localization.t("exit_game"))
+                        .kbgp_navigation()
+                        .clicked()
+                    {
                         exit.send(bevy::app::AppExit);
                     };
                 }
@@ -151,20 +320,46 @@ pub fn draw_title(
         });
 }
 
-pub fn on_title_enter(mut query: Query<&mut Visibility, With<Logo>>) {
-    *query.single_mut() = Visibility::Visible;
+pub fn on_title_enter(
+    mut query: Query<&mut Visibility, With<Logo>>,
+    mut hud_hidden: ResMut<HudHidden>,
+    mut boid_settings: ResMut<BoidSettings>,
+) {
+    if let Ok(mut visibility) = query.get_single_mut() {
+        *visibility = Visibility::Visible;
+    }
+    hud_hidden.show(&mut boid_settings);
 }
 
 pub fn on_title_exit(mut query: Query<&mut Visibility, With<Logo>>) {
-    *query.single_mut() = Visibility::Hidden;
+    if let Ok(mut visibility) = query.get_single_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+pub fn on_custom_game_enter(mut ui_data: ResMut<UiData>, boid_settings: Res<BoidSettings>) {
+    ui_data.boid_settings = boid_settings.clone();
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn draw_round_settings(
     mut egui_context: EguiContexts,
     mut app_state: ResMut<NextState<AppState>>,
     mut ui_data: ResMut<UiData>,
     mut round_settings: ResMut<RoundSettings>,
+    localization: Res<Localization>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    time: Res<Time>,
+    mut boid_settings: ResMut<BoidSettings>,
+    gamepads: Res<Gamepads>,
+    // When each player's gamepad last had a button pressed, keyed by player index.
+    mut identified_at: Local<HashMap<usize, f32>>,
 ) {
+    for button in gamepad_buttons.get_just_pressed() {
+        for i in ui_data.round_settings.players_using_gamepad(button.gamepad) {
+            identified_at.insert(i, time.elapsed_seconds());
+        }
+    }
     egui::Window::new("Round Settings")
         .title_bar(false)
         .anchor(Align2::CENTER_CENTER, vec2(0.0, 0.0))
@@ -177,27 +372,41 @@ pub fn draw_round_settings(
                 .min_row_height(40.0)
                 .num_columns(4)
                 .show(ui, |ui| {
-                    ui.label("Player");
-                    ui.label("Type");
+                    ui.label(localization.t("player"));
+                    ui.label(localization.t("type"));
                     ui.end_row();
                     let mut remove_indexes = Vec::new();
                     for (i, player_setting) in ui_data.round_settings.players.iter_mut().enumerate()
                     {
-                        ui.label(format!("Player {}", i + 1));
+                        let label = localization
+                            .t("player_number")
+                            .replace("{number}", &(i + 1).to_string());
+                        match identified_at.get(&i) {
+                            Some(pressed_at)
+                                if time.elapsed_seconds() - pressed_at
+                                    < IDENTIFY_GAMEPAD_SECONDS =>
+                            {
+                                ui.colored_label(egui::Color32::from_rgb(255, 80, 80), label)
+                            }
+                            _ => ui.label(label),
+                        };
                         egui::ComboBox::from_id_source(format!("player_settings_type{i}"))
-                            .selected_text(player_setting.player_type.human_bot_label())
+                            .selected_text(match player_setting.player_type.is_local() {
+                                true => localization.t("human"),
+                                false => localization.t("bot"),
+                            })
                             .show_ui(ui, |ui| {
                                 ui.set_width(200.0);
                                 ui.selectable_value(
                                     &mut player_setting.player_type,
                                     PlayerType::AnyDevice,
-                                    "Human",
+                                    localization.t("human"),
                                 )
                                 .kbgp_navigation();
                                 ui.selectable_value(
                                     &mut player_setting.player_type,
                                     PlayerType::Bot(Bot::BoneHead),
-                                    "Bot",
+                                    localization.t("bot"),
                                 )
                                 .kbgp_navigation();
                             })
@@ -208,7 +417,7 @@ pub fn draw_round_settings(
                             .show_ui(ui, |ui| {
                                 ui.set_width(200.0);
                                 if player_setting.player_type.is_local() {
-                                    for option in PlayerType::human_options() {
+                                    for option in PlayerType::human_options(gamepads.iter()) {
                                         ui.selectable_value(
                                             &mut player_setting.player_type,
                                             option,
@@ -238,9 +447,18 @@ pub fn draw_round_settings(
                     for index in remove_indexes {
                         ui_data.round_settings.players.remove(index);
                     }
-                    let new_id = ui_data.round_settings.players.len();
-                    if let Some(new_color) = BoidColor::from_index(new_id) {
-                        if ui.button("Add Player").kbgp_navigation().clicked() {
+                    let used_colors: Vec<BoidColor> = ui_data
+                        .round_settings
+                        .players
+                        .iter()
+                        .map(|p| p.color)
+                        .collect();
+                    if let Some(new_color) = BoidColor::next_distinct(&used_colors) {
+                        if ui
+                            .button(localization.t("add_player"))
+                            .kbgp_navigation()
+                            .clicked()
+                        {
                             ui_data.round_settings.players.push(PlayerSettings {
                                 player_type: default(),
                                 color: new_color,
@@ -251,8 +469,23 @@ pub fn draw_round_settings(
                 });
 
             ui.vertical_centered_justified(|ui| {
+                if ui
+                    .button(localization.t("randomize_bots"))
+                    .kbgp_navigation()
+                    .clicked()
+                {
+                    ui_data.round_settings.randomize_bots(&Rng::new());
+                }
+                if ui_data
+                    .round_settings
+                    .players
+                    .iter()
+                    .any(|p| matches!(p.player_type, PlayerType::GamePad(Some(_))))
+                {
+                    ui.small(localization.t("identify_gamepad_hint"));
+                }
                 if ui_data.round_settings.local_player_count() > 1 {
-                    ui.label("Local Multiplayer Mode: ");
+                    ui.label(localization.t("local_multiplayer_mode"));
                     egui::ComboBox::from_id_source("local_screen_type")
                         .width(ui.available_width())
                         .selected_text(ui_data.round_settings.multiplayer_mode.to_string())
@@ -280,18 +513,111 @@ pub fn draw_round_settings(
                         })
                         .response
                         .kbgp_navigation();
+                    draw_viewport_preview(ui, &ui_data.round_settings);
+                }
+                ui.add(
+                    egui::DragValue::new(&mut ui_data.round_settings.boid_count)
+                        .speed(1.0)
+                        .clamp_range(10.0..=5000.0)
+                        .prefix(localization.t("boid_count")),
+                );
+                let separation_distance = ui_data.boid_settings.separation_distance;
+                if let Some(warning) = ui_data.round_settings.check_density(separation_distance) {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        localization
+                            .t("too_many_boids")
+                            .replace("{max}", &warning.max_boids.to_string()),
+                    );
+                    if ui
+                        .button(localization.t("use_max_boids"))
+                        .kbgp_navigation()
+                        .clicked()
+                    {
+                        ui_data.round_settings.boid_count = warning.max_boids as f32;
+                    }
+                    let suggested_radius = warning.suggested_radius.ceil();
+                    if ui
+                        .button(
+                            localization
+                                .t("use_suggested_radius")
+                                .replace("{radius}", &suggested_radius.to_string()),
+                        )
+                        .kbgp_navigation()
+                        .clicked()
+                    {
+                        ui_data.round_settings.arena_radius = suggested_radius;
+                    }
+                }
+                ui.checkbox(
+                    &mut ui_data.round_settings.drop_in,
+                    localization.t("drop_in"),
+                )
+                .kbgp_navigation();
+                ui.checkbox(
+                    &mut ui_data.round_settings.survival_mode,
+                    localization.t("survival_mode"),
+                )
+                .kbgp_navigation();
+                if ui_data.round_settings.survival_mode {
+                    ui.add(
+                        egui::DragValue::new(&mut ui_data.round_settings.wave_interval)
+                            .speed(0.5)
+                            .clamp_range(1.0..=600.0)
+                            .prefix(localization.t("wave_interval")),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut ui_data.round_settings.wave_size)
+                            .speed(1.0)
+                            .clamp_range(1..=500)
+                            .prefix(localization.t("wave_size")),
+                    );
+                }
+                ui.checkbox(
+                    &mut ui_data.round_settings.control_mode,
+                    localization.t("control_mode"),
+                )
+                .kbgp_navigation();
+                if ui_data.round_settings.control_mode {
+                    ui.add(
+                        egui::Slider::new(&mut ui_data.round_settings.control_share, 0.5..=1.0)
+                            .text(localization.t("control_share")),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut ui_data.round_settings.control_seconds)
+                            .speed(0.5)
+                            .clamp_range(1.0..=600.0)
+                            .prefix(localization.t("control_seconds")),
+                    );
+                }
+                egui::CollapsingHeader::new(localization.t("flock_physics")).show(ui, |ui| {
+                    draw_flock_physics(ui, &mut ui_data.boid_settings, &localization)
+                });
+                egui::CollapsingHeader::new(localization.t("round_rules")).show(ui, |ui| {
+                    draw_round_rules(ui, &mut ui_data.round_settings, &localization)
+                });
+                let validation = ui_data.round_settings.validate();
+                if let Err(e) = &validation {
+                    ui.colored_label(egui::Color32::YELLOW, localization.t(e.localization_id()));
                 }
                 horizontal_right_to_left_top(ui, |ui| {
                     if ui
-                        .button("Start Game")
+                        .add_enabled(
+                            validation.is_ok(),
+                            egui::Button::new(localization.t("start_game")),
+                        )
                         .kbgp_navigation()
                         .kbgp_initial_focus()
                         .clicked()
                     {
-                        *round_settings = ui_data.round_settings.clone();
+                        ui_data.apply_custom_game(&mut round_settings, &mut boid_settings);
                         app_state.set(AppState::LoadRound);
                     }
-                    if ui.button("Back").kbgp_navigation().clicked() {
+                    if ui
+                        .button(localization.t("back"))
+                        .kbgp_navigation()
+                        .clicked()
+                    {
                         *round_settings = ui_data.round_settings.clone();
                         app_state.set(AppState::Title);
                     }
@@ -300,14 +626,162 @@ pub fn draw_round_settings(
         });
 }
 
+/// Edits the boid settings that change how the game plays, the rest are left to the inspector.
+fn draw_flock_physics(ui: &mut Ui, boid_settings: &mut BoidSettings, localization: &Localization) {
+    let mut drag_value = |value: &mut f32, max: f32, label: &str| {
+        ui.add(
+            egui::DragValue::new(value)
+                .speed(1.0)
+                .clamp_range(0.0..=max)
+                .prefix(localization.t(label)),
+        )
+        .kbgp_navigation();
+    };
+    drag_value(&mut boid_settings.max_speed, 9999.0, "max_speed");
+    drag_value(&mut boid_settings.min_speed, 9999.0, "min_speed");
+    drag_value(&mut boid_settings.acceleration, 9999.0, "acceleration");
+    drag_value(
+        &mut boid_settings.max_turn_rate_per_second,
+        3600.0,
+        "turn_rate",
+    );
+    drag_value(
+        &mut boid_settings.separation_distance,
+        1000.0,
+        "separation_distance",
+    );
+    drag_value(&mut boid_settings.capture_range, 1000.0, "capture_range");
+}
+
+/// Edits the round settings that tweak the rules, 0 turns most of them off.
+fn draw_round_rules(ui: &mut Ui, round_settings: &mut RoundSettings, localization: &Localization) {
+    let mut drag_value = |value: &mut f32, speed: f32, max: f32, label: &str| {
+        ui.add(
+            egui::DragValue::new(value)
+                .speed(speed)
+                .clamp_range(0.0..=max)
+                .prefix(localization.t(label)),
+        )
+        .kbgp_navigation();
+    };
+    drag_value(
+        &mut round_settings.leader_escort_size,
+        1.0,
+        100.0,
+        "leader_escort_size",
+    );
+    drag_value(&mut round_settings.bot_jitter, 0.01, 0.5, "bot_jitter");
+    drag_value(
+        &mut round_settings.hunter_switch_margin,
+        0.01,
+        1.0,
+        "hunter_switch_margin",
+    );
+    drag_value(
+        &mut round_settings.min_leader_separation,
+        10.0,
+        5000.0,
+        "min_leader_separation",
+    );
+    drag_value(
+        &mut round_settings.spawn_min_distance,
+        0.5,
+        100.0,
+        "spawn_min_distance",
+    );
+    drag_value(
+        &mut round_settings.streak_threshold,
+        1.0,
+        200.0,
+        "streak_threshold",
+    );
+    drag_value(
+        &mut round_settings.streak_speed_multiplier,
+        0.01,
+        3.0,
+        "streak_speed_multiplier",
+    );
+}
+
+/// Returns where each local player's view will be in a preview of `size`, along with the indexes
+/// of the players shown in it.
+pub fn viewport_preview(
+    round_settings: &RoundSettings,
+    size: egui::Vec2,
+) -> Vec<(egui::Rect, Vec<usize>)> {
+    let local_players: Vec<usize> = round_settings
+        .players
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.player_type.is_local())
+        .map(|(i, _)| i)
+        .collect();
+    let to_rect = |viewport: ViewportRelative| {
+        egui::Rect::from_min_size(
+            egui::pos2(viewport.x * size.x, viewport.y * size.y),
+            vec2(viewport.width * size.x, viewport.height * size.y),
+        )
+    };
+    match round_settings.multiplayer_mode {
+        MultiplayerMode::SharedScreen if local_players.len() > 1 => {
+            vec![(to_rect(ViewportRelative::fullscreen()), local_players)]
+        }
+        _ => {
+            let viewports = round_settings.player_viewports(0.0);
+            local_players
+                .into_iter()
+                .enumerate()
+                .map(|(viewport_id, player)| (to_rect(viewports.get(viewport_id)), vec![player]))
+                .collect()
+        }
+    }
+}
+
+/// Draws a small screen split up the way the round's viewports will be, so players can see the
+/// layout before starting.
+fn draw_viewport_preview(ui: &mut Ui, round_settings: &RoundSettings) {
+    let size = vec2(ui.available_width(), ui.available_width() * 9.0 / 16.0);
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    for (viewport, players) in viewport_preview(round_settings, size) {
+        let viewport = viewport.translate(rect.min.to_vec2()).shrink(1.0);
+        ui.painter()
+            .rect_filled(viewport, 2.0, egui::Color32::from_gray(40));
+        let spacing = viewport.width() / (players.len() + 1) as f32;
+        for (i, player) in players.iter().enumerate() {
+            let [r, g, b, _] = round_settings.players[*player]
+                .color
+                .color()
+                .as_rgba_u32()
+                .to_le_bytes();
+            ui.painter().text(
+                egui::pos2(
+                    viewport.left() + spacing * (i + 1) as f32,
+                    viewport.center().y,
+                ),
+                Align2::CENTER_CENTER,
+                format!("P{}", player + 1),
+                egui::FontId::proportional(14.0),
+                egui::Color32::from_rgb(r, g, b),
+            );
+        }
+    }
+}
+
 pub fn draw_game_over(
     mut egui_context: EguiContexts,
     mut app_state: ResMut<NextState<AppState>>,
     winner: Option<Res<Winner>>,
+    localization: Res<Localization>,
+    match_stats: Res<MatchStats>,
 ) {
     let title = match winner {
-        None => "Tie!".to_string(),
-        Some(winner) => format!("{:?} Won!", winner.color),
+        None => localization.t("tie").to_string(),
+        Some(winner) => {
+            let color_id = winner.color.name().to_lowercase();
+            localization
+                .t("winner_title")
+                .replace("{color}", localization.t(&color_id))
+        }
     };
     egui::Window::new("Winner")
         .title_bar(false)
@@ -316,12 +790,27 @@ pub fn draw_game_over(
         .collapsible(false)
         .show(egui_context.ctx_mut(), |ui| {
             ui.vertical_centered(|ui| ui.heading(title));
+            if let Some((colors, seconds)) = match_stats.longest_without_loss() {
+                let colors = colors
+                    .iter()
+                    .map(|color| localization.t(&color.name().to_lowercase()).to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                ui.vertical_centered(|ui| {
+                    ui.label(
+                        localization
+                            .t("longest_without_loss")
+                            .replace("{colors}", &colors)
+                            .replace("{seconds}", &format!("{seconds:.1}")),
+                    )
+                });
+            }
             ui.separator();
             ui.set_width(220.0);
             ui.set_width(200.0);
             ui.vertical_centered_justified(|ui| {
                 if ui
-                    .button("Restart")
+                    .button(localization.t("restart"))
                     .kbgp_navigation()
                     .kbgp_initial_focus()
                     .clicked()
@@ -329,18 +818,431 @@ pub fn draw_game_over(
                     app_state.set(AppState::LoadRound);
                 }
 
-                if ui.button("Return to Title").kbgp_navigation().clicked() {
+                if ui
+                    .button(localization.t("return_to_title"))
+                    .kbgp_navigation()
+                    .clicked()
+                {
                     app_state.set(AppState::Title);
                 }
             });
         });
 }
 
+/// Describes how long ago a match ended, like "5m" which the history shows as "5m ago".
+fn time_ago(seconds: u64, localization: &Localization) -> String {
+    let (count, unit) = match seconds {
+        x if x < 60 => (x, "seconds_short"),
+        x if x < 60 * 60 => (x / 60, "minutes_short"),
+        x if x < 60 * 60 * 24 => (x / (60 * 60), "hours_short"),
+        x => (x / (60 * 60 * 24), "days_short"),
+    };
+    localization.t(unit).replace("{count}", &count.to_string())
+}
+
+/// The name of a player's controls in the current language. Bots keep their names.
+fn player_type_label(player_type: &PlayerType, localization: &Localization) -> String {
+    match player_type {
+        PlayerType::AnyDevice => localization.t("any_device").to_string(),
+        PlayerType::Wasd => localization.t("wasd").to_string(),
+        PlayerType::ArrowKeys => localization.t("arrow_keys").to_string(),
+        PlayerType::Mouse => localization.t("mouse").to_string(),
+        PlayerType::GamePad(Some(gamepad)) => localization
+            .t("gamepad_number")
+            .replace("{number}", &(gamepad.id + 1).to_string()),
+        PlayerType::GamePad(None) => localization.t("any_gamepad").to_string(),
+        PlayerType::Bot(bot) => bot.to_string(),
+    }
+}
+
+pub fn draw_history(
+    mut egui_context: EguiContexts,
+    mut ui_state: ResMut<NextState<UiState>>,
+    history: Res<MatchHistory>,
+    localization: Res<Localization>,
+) {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    egui::Window::new("History")
+        .anchor(Align2::CENTER_CENTER, vec2(0.0, 0.0))
+        .resizable(false)
+        .collapsible(false)
+        .title_bar(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.set_width(320.0);
+            ui.vertical_centered(|ui| ui.heading(localization.t("history")));
+            ui.separator();
+            if history.entries.is_empty() {
+                ui.vertical_centered(|ui| ui.label(localization.t("no_matches_yet")));
+            }
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    for entry in history.entries.iter() {
+                        ui.horizontal(|ui| {
+                            let swatch = match entry.winner {
+                                Some(color) => {
+                                    let [r, g, b, _] = color.color().as_rgba_u32().to_le_bytes();
+                                    egui::Color32::from_rgb(r, g, b)
+                                }
+                                None => egui::Color32::GRAY,
+                            };
+                            let (rect, _) =
+                                ui.allocate_exact_size(vec2(16.0, 16.0), egui::Sense::hover());
+                            ui.painter().rect_filled(rect, 3.0, swatch);
+                            ui.vertical(|ui| {
+                                ui.label(match entry.winner {
+                                    Some(color) => localization.t("winner_title").replace(
+                                        "{color}",
+                                        localization.t(&color.name().to_lowercase()),
+                                    ),
+                                    None => localization.t("tie").to_string(),
+                                });
+                                let players = entry
+                                    .players
+                                    .iter()
+                                    .map(|player| {
+                                        player_type_label(&player.player_type, &localization)
+                                    })
+                                    .collect::<Vec<String>>()
+                                    .join(", ");
+                                ui.small(
+                                    localization
+                                        .t("history_details")
+                                        .replace("{players}", &players)
+                                        .replace(
+                                            "{seconds}",
+                                            &format!("{:.0}", entry.duration_seconds),
+                                        )
+                                        .replace(
+                                            "{ago}",
+                                            &time_ago(
+                                                now.saturating_sub(entry.timestamp),
+                                                &localization,
+                                            ),
+                                        ),
+                                );
+                            });
+                        });
+                    }
+                });
+            ui.separator();
+            ui.vertical_centered_justified(|ui| {
+                if ui
+                    .button(localization.t("back"))
+                    .kbgp_navigation()
+                    .kbgp_initial_focus()
+                    .clicked()
+                {
+                    ui_state.set(UiState::Title);
+                }
+            });
+        });
+}
+
+/// How big the arrows pointing at players off the edge of the screen are
+const EDGE_INDICATOR_SIZE: f32 = 24.0;
+
+/// Returns the area of the window a camera draws to, in egui's coordinates.
+fn egui_viewport(camera: &Camera, window: &Window) -> Option<egui::Rect> {
+    let size = camera.logical_viewport_size()?;
+    let origin = camera
+        .viewport
+        .as_ref()
+        .map(|v| v.physical_position.as_vec2() / window.scale_factor() as f32)
+        .unwrap_or_default();
+    Some(egui::Rect::from_min_size(
+        egui::pos2(origin.x, origin.y),
+        vec2(size.x, size.y),
+    ))
+}
+
+/// Converts a world position to where it is drawn in the window for a camera, in egui's
+/// coordinates.
+fn world_to_egui(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    window: &Window,
+    world_position: Vec3,
+) -> Option<egui::Pos2> {
+    // Viewport positions start at the bottom left of the viewport, egui's at the top left.
+    let position = camera.world_to_viewport(camera_transform, world_position)?;
+    let viewport = egui_viewport(camera, window)?;
+    Some(egui::pos2(
+        viewport.min.x + position.x,
+        viewport.max.y - position.y,
+    ))
+}
+
+/// Draws an arrow at the edge of the shared screen pointing at each player that's out of view.
+pub fn draw_edge_indicators(
+    mut egui_context: EguiContexts,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera2dFollowMany>>,
+    targets: Query<(&GlobalTransform, &BoidColor), With<CameraFollowTarget>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+) {
+    let window = match windows.get_single() {
+        Ok(window) => window,
+        Err(_) => return,
+    };
+    let painter = egui_context.ctx_mut().layer_painter(egui::LayerId::new(
+        egui::Order::Background,
+        egui::Id::new("edge_indicators"),
+    ));
+    for (camera, camera_transform) in cameras.iter() {
+        let viewport = match egui_viewport(camera, window) {
+            Some(viewport) => viewport,
+            None => continue,
+        };
+        for (transform, color) in targets.iter() {
+            let target =
+                match world_to_egui(camera, camera_transform, window, transform.translation()) {
+                    Some(target) => Vec2::new(target.x, target.y),
+                    None => continue,
+                };
+            if let Some(indicator) = edge_indicator(
+                Vec2::new(viewport.min.x, viewport.min.y),
+                Vec2::new(viewport.max.x, viewport.max.y),
+                target,
+                EDGE_INDICATOR_SIZE,
+            ) {
+                let forward = (target - indicator).normalize_or_zero() * EDGE_INDICATOR_SIZE / 2.0;
+                let side = forward.perp();
+                let points = [
+                    indicator + forward,
+                    indicator - forward + side,
+                    indicator - forward - side,
+                ]
+                .into_iter()
+                .map(|p| egui::pos2(p.x, p.y))
+                .collect();
+                let [r, g, b, _] = color.color().as_rgba_u32().to_le_bytes();
+                painter.add(egui::Shape::convex_polygon(
+                    points,
+                    egui::Color32::from_rgb(r, g, b),
+                    egui::Stroke::NONE,
+                ));
+            }
+        }
+    }
+}
+
+/// Draws an arrow at the middle of each flock on the shared screen pointing the way it's heading,
+/// longer the faster it's going.
+pub fn draw_flock_arrows(
+    mut egui_context: EguiContexts,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera2dFollowMany>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    census: Res<BoidCensus>,
+    camera_settings: Res<CameraSettings>,
+) {
+    if !camera_settings.flock_arrows {
+        return;
+    }
+    let window = match windows.get_single() {
+        Ok(window) => window,
+        Err(_) => return,
+    };
+    let painter = egui_context.ctx_mut().layer_painter(egui::LayerId::new(
+        egui::Order::Background,
+        egui::Id::new("flock_arrows"),
+    ));
+    for (camera, camera_transform) in cameras.iter() {
+        for (color, flock) in census.flocks.iter() {
+            let to_egui = |position: Vec2| {
+                world_to_egui(camera, camera_transform, window, position.extend(0.0))
+            };
+            if let (Some(start), Some(end)) = (to_egui(flock.centroid), to_egui(flock.arrow_tip()))
+            {
+                let [r, g, b, _] = color.color().as_rgba_u32().to_le_bytes();
+                painter.arrow(
+                    start,
+                    end - start,
+                    egui::Stroke::new(3.0, egui::Color32::from_rgb(r, g, b)),
+                );
+            }
+        }
+    }
+}
+
+/// Draws the fading borders around the views of players that just captured a leader.
+pub fn draw_capture_flashes(
+    mut egui_context: EguiContexts,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    flashes: Res<CaptureFlashes>,
+) {
+    let window = match windows.get_single() {
+        Ok(window) => window,
+        Err(_) => return,
+    };
+    let painter = egui_context.ctx_mut().layer_painter(egui::LayerId::new(
+        egui::Order::Background,
+        egui::Id::new("capture_flashes"),
+    ));
+    let width = 12.0;
+    for flash in flashes.0.iter() {
+        let viewport = flash.viewport;
+        let rect = egui::Rect::from_min_size(
+            egui::pos2(viewport.x * window.width(), viewport.y * window.height()),
+            vec2(
+                viewport.width * window.width(),
+                viewport.height * window.height(),
+            ),
+        );
+        let [r, g, b, _] = flash.color.color().as_rgba_u32().to_le_bytes();
+        painter.with_clip_rect(rect).rect_stroke(
+            rect.shrink(width / 2.0),
+            0.0,
+            egui::Stroke::new(
+                width,
+                egui::Color32::from_rgba_unmultiplied(r, g, b, (flash.alpha() * 200.0) as u8),
+            ),
+        );
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn draw_on_fire_indicators(
+    mut egui_context: EguiContexts,
+    cameras: Query<
+        (&Camera, &GlobalTransform),
+        Or<(With<Camera2dFollow>, With<Camera2dFollowMany>)>,
+    >,
+    leaders: Query<(&GlobalTransform, &BoidColor), With<Leader>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    streaks: Res<CaptureStreaks>,
+    localization: Res<Localization>,
+) {
+    let window = match windows.get_single() {
+        Ok(window) => window,
+        Err(_) => return,
+    };
+    for (camera_index, (camera, camera_transform)) in cameras.iter().enumerate() {
+        let viewport = match egui_viewport(camera, window) {
+            Some(viewport) => viewport,
+            None => continue,
+        };
+        for (leader_transform, color) in leaders.iter() {
+            if !streaks.is_on_fire(color) {
+                continue;
+            }
+            let above_leader = leader_transform.translation() + Vec3::Y * 20.0;
+            if let Some(position) = world_to_egui(camera, camera_transform, window, above_leader) {
+                egui::Area::new(egui::Id::new(("on_fire", camera_index, *color)))
+                    .fixed_pos(position)
+                    .pivot(Align2::CENTER_BOTTOM)
+                    .interactable(false)
+                    .show(egui_context.ctx_mut(), |ui| {
+                        // Don't spill over into the other players' views in split screen.
+                        ui.set_clip_rect(viewport);
+                        let [r, g, b, _] = color.color().as_rgba_u32().to_le_bytes();
+                        ui.colored_label(
+                            egui::Color32::from_rgb(r, g, b),
+                            localization.t("on_fire"),
+                        );
+                    });
+            }
+        }
+    }
+}
+
+/// How wide the population bar at the top of the screen is
+const POPULATION_BAR_WIDTH: f32 = 320.0;
+const POPULATION_BAR_HEIGHT: f32 = 10.0;
+
+/// Splits `width` between the colors still in the game by how many boids they have.
+pub fn population_segments(census: &BoidCensus, width: f32) -> Vec<(BoidColor, f32)> {
+    let total: usize = BoidColor::ALL.iter().map(|color| census.count(color)).sum();
+    if total == 0 {
+        return Vec::new();
+    }
+    BoidColor::ALL
+        .iter()
+        .filter(|color| census.count(color) > 0)
+        .map(|color| (*color, width * census.count(color) as f32 / total as f32))
+        .collect()
+}
+
+/// Draws a bar across the top of the screen showing each color's share of the boids.
+pub fn draw_population_bar(mut egui_context: EguiContexts, census: Res<BoidCensus>) {
+    let segments = population_segments(&census, POPULATION_BAR_WIDTH);
+    if segments.is_empty() {
+        return;
+    }
+    egui::Area::new("population_bar")
+        .anchor(Align2::CENTER_TOP, vec2(0.0, 8.0))
+        .interactable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            let (rect, _) = ui.allocate_exact_size(
+                vec2(POPULATION_BAR_WIDTH, POPULATION_BAR_HEIGHT),
+                egui::Sense::hover(),
+            );
+            let mut left = rect.left();
+            for (color, width) in segments {
+                let [r, g, b, _] = color.color().as_rgba_u32().to_le_bytes();
+                ui.painter().rect_filled(
+                    egui::Rect::from_min_size(
+                        egui::pos2(left, rect.top()),
+                        vec2(width, rect.height()),
+                    ),
+                    0.0,
+                    egui::Color32::from_rgb(r, g, b),
+                );
+                left += width;
+            }
+        });
+}
+
+/// Shows which team each spectating camera is following, below the population bar.
+#[allow(clippy::type_complexity)]
+pub fn draw_spectator_label(
+    mut egui_context: EguiContexts,
+    cameras: Query<(Entity, &Camera, &Camera2dFollow, Option<&Spectating>)>,
+    colors: Query<&BoidColor, With<Leader>>,
+    humans: Query<(), (With<Leader>, With<InputMap<PlayerActions>>)>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    localization: Res<Localization>,
+) {
+    let everyone_spectating = humans.is_empty();
+    let window = match windows.get_single() {
+        Ok(window) => window,
+        Err(_) => return,
+    };
+    for (entity, camera, camera_follow, spectating) in cameras.iter() {
+        if !everyone_spectating && spectating.is_none() {
+            continue;
+        }
+        let (color, viewport) = match (
+            colors.get(camera_follow.target),
+            egui_viewport(camera, window),
+        ) {
+            (Ok(color), Some(viewport)) => (color, viewport),
+            _ => continue,
+        };
+        egui::Area::new(egui::Id::new(("spectating", entity)))
+            .fixed_pos(viewport.center_top() + vec2(0.0, 28.0))
+            .pivot(Align2::CENTER_TOP)
+            .interactable(false)
+            .show(egui_context.ctx_mut(), |ui| {
+                let [r, g, b, _] = color.color().as_rgba_u32().to_le_bytes();
+                ui.colored_label(
+                    egui::Color32::from_rgb(r, g, b),
+                    localization
+                        .t("spectating")
+                        .replace("{color}", localization.t(&color.name().to_lowercase())),
+                );
+            });
+    }
+}
+
 pub fn draw_settings(
     mut egui_context: EguiContexts,
     mut ui_state: ResMut<NextState<UiState>>,
     mut ui_data: ResMut<UiData>,
     mut ui_event_writer: EventWriter<UiEvent>,
+    localization: Res<Localization>,
 ) {
     egui::Window::new("Settings")
         .anchor(Align2::CENTER_CENTER, vec2(0.0, 0.0))
@@ -349,10 +1251,12 @@ pub fn draw_settings(
         .title_bar(false)
         .show(egui_context.ctx_mut(), |ui| {
             ui.set_width(240.0);
-            ui.vertical_centered(|ui| ui.heading("Settings"));
+            ui.vertical_centered(|ui| ui.heading(localization.t("settings")));
             ui.separator();
             ui.vertical_centered_justified(|ui| {
-                ui_data.window_mode.draw_as_combo_box(ui, 210.0);
+                ui_data
+                    .window_mode
+                    .draw_as_combo_box(ui, 210.0, &localization);
                 if ui_data.window_mode == WindowMode::SizedFullscreen {
                     ui.add(
                         egui::DragValue::new(&mut ui_data.window_width)
@@ -367,19 +1271,59 @@ pub fn draw_settings(
                             .prefix("H: "),
                     );
                 }
+                ui_data.language.draw_as_combo_box(ui, 210.0, &localization);
+                ui.checkbox(&mut ui_data.reduce_motion, localization.t("reduce_motion"))
+                    .kbgp_navigation();
+                ui.checkbox(&mut ui_data.path_preview, localization.t("path_preview"))
+                    .kbgp_navigation();
+                ui.checkbox(&mut ui_data.boundary_ring, localization.t("boundary_ring"))
+                    .kbgp_navigation();
+                ui.add(
+                    egui::Slider::new(&mut ui_data.camera_lead, 0.0..=1.0)
+                        .text(localization.t("camera_lead")),
+                )
+                .kbgp_navigation();
+                // There's no file system to save presets to on the web.
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    ui.separator();
+                    ui.label(localization.t("boid_preset"));
+                    ui.text_edit_singleline(&mut ui_data.preset_name);
+                    horizontal_right_to_left_top(ui, |ui| {
+                        if ui
+                            .button(localization.t("load_preset"))
+                            .kbgp_navigation()
+                            .clicked()
+                        {
+                            ui_event_writer.send(UiEvent::LoadBoidPreset);
+                        }
+                        if ui
+                            .button(localization.t("save_preset"))
+                            .kbgp_navigation()
+                            .clicked()
+                        {
+                            ui_event_writer.send(UiEvent::SaveBoidPreset);
+                        }
+                    });
+                    ui.separator();
+                }
                 horizontal_right_to_left_top(ui, |ui| {
                     if ui
-                        .button("Save")
+                        .button(localization.t("save"))
                         .kbgp_navigation()
                         .kbgp_initial_focus()
                         .clicked()
                     {
-                        ui_state.set(UiState::Title);
+                        ui_data.close_settings(&mut ui_state);
                         ui_event_writer.send(UiEvent::SettingsSaved);
                     }
 
-                    if ui.button("Back").kbgp_navigation().clicked() {
-                        ui_state.set(UiState::Title);
+                    if ui
+                        .button(localization.t("back"))
+                        .kbgp_navigation()
+                        .clicked()
+                    {
+                        ui_data.close_settings(&mut ui_state);
                     }
                 });
             });
@@ -400,24 +1344,69 @@ pub fn horizontal_right_to_left_top<R>(
     })
 }
 
+/// Hands every human's flock to a bot and turns their cameras into spectator cameras. The
+/// cameras keep following the same flocks until the player switches to another one.
+pub fn spectate_bots(
+    commands: &mut Commands,
+    human_leaders: &Query<Entity, (With<Leader>, With<InputMap<PlayerActions>>)>,
+    player_cameras: &Query<Entity, With<Camera2dFollow>>,
+) {
+    for entity in human_leaders.iter() {
+        let mut entity_commands = commands.entity(entity);
+        entity_commands
+            .remove::<InputMap<PlayerActions>>()
+            // Clear any buttons that were held so they don't get stuck on.
+            .insert(ActionState::<PlayerActions>::default());
+        Bot::default().insert(&mut entity_commands);
+    }
+    for camera in player_cameras.iter() {
+        commands.entity(camera).insert(Spectating);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn handle_ui_events(
+    mut commands: Commands,
     mut events: EventReader<UiEvent>,
     mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut localization: ResMut<Localization>,
     ui_data: Res<UiData>,
+    human_leaders: Query<Entity, (With<Leader>, With<InputMap<PlayerActions>>)>,
+    player_cameras: Query<Entity, With<Camera2dFollow>>,
+    mut boid_settings: ResMut<BoidSettings>,
+    mut accessibility: ResMut<AccessibilitySettings>,
+    mut camera_settings: ResMut<CameraSettings>,
+    mut display_settings: ResMut<DisplaySettings>,
 ) {
     for event in events.iter() {
         info!("{event:?}");
         match event {
             UiEvent::SettingsSaved => {
-                let mut window = windows.single_mut();
-                if window.mode != ui_data.window_mode {
-                    window.mode = ui_data.window_mode;
+                *display_settings = DisplaySettings {
+                    window_mode: ui_data.window_mode.into(),
+                    window_width: ui_data.window_width,
+                    window_height: ui_data.window_height,
+                    camera_lead: ui_data.camera_lead,
+                };
+                if let Ok(mut window) = windows.get_single_mut() {
+                    display_settings.apply_to_window(&mut window);
                 }
-                if window.mode == WindowMode::SizedFullscreen {
-                    window.resolution =
-                        WindowResolution::new(ui_data.window_width, ui_data.window_height);
+                localization.language = ui_data.language;
+                accessibility.reduce_motion = ui_data.reduce_motion;
+                accessibility.path_preview = ui_data.path_preview;
+                accessibility.boundary_ring = ui_data.boundary_ring;
+                camera_settings.lead_blend = ui_data.camera_lead;
+            }
+            UiEvent::SpectateBots => spectate_bots(&mut commands, &human_leaders, &player_cameras),
+            UiEvent::SaveBoidPreset => {
+                if let Err(e) = boid_settings.save_preset(&ui_data.preset_name) {
+                    error!("Failed to save boid settings preset: {e}");
                 }
             }
+            UiEvent::LoadBoidPreset => match BoidSettings::load_preset(&ui_data.preset_name) {
+                Ok(preset) => *boid_settings = preset,
+                Err(e) => error!("Failed to load boid settings preset: {e}"),
+            },
         }
     }
 }
@@ -443,8 +1432,68 @@ pub fn toggle_pause_hotkey(
     }
 }
 
+/// Hides or shows all the UI while in a round
+pub fn toggle_ui_visibility(
+    action_state: Query<&ActionState<GlobalActions>>,
+    app_state: Res<State<AppState>>,
+    mut next_ui_state: ResMut<NextState<UiState>>,
+    mut hud_hidden: ResMut<HudHidden>,
+    mut boid_settings: ResMut<BoidSettings>,
+) {
+    let action_state = action_state.single();
+    if !action_state.just_pressed(GlobalActions::ToggleUiVisibility)
+        || !matches!(
+            app_state.0,
+            AppState::Playing | AppState::Paused | AppState::GameOver
+        )
+    {
+        return;
+    }
+    if hud_hidden.hidden {
+        hud_hidden.show(&mut boid_settings);
+        if app_state.0 == AppState::Paused {
+            next_ui_state.set(UiState::PauseMenu);
+        }
+    } else {
+        hud_hidden.hide(&mut boid_settings);
+        next_ui_state.set(UiState::Hidden);
+    }
+}
+
+/// Puts the window and camera back how the player left them last time and shows that in the
+/// settings menu.
+pub fn restore_display_settings(
+    display_settings: Res<DisplaySettings>,
+    mut ui_data: ResMut<UiData>,
+    mut camera_settings: ResMut<CameraSettings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    ui_data.window_mode = display_settings.window_mode();
+    ui_data.window_width = display_settings.window_width;
+    ui_data.window_height = display_settings.window_height;
+    ui_data.camera_lead = display_settings.camera_lead;
+    camera_settings.lead_blend = display_settings.camera_lead;
+    if let Ok(mut window) = windows.get_single_mut() {
+        display_settings.apply_to_window(&mut window);
+    }
+}
+
+/// Shows the accessibility settings the player saved last time in the settings menu.
+pub fn restore_accessibility_settings(
+    accessibility: Res<AccessibilitySettings>,
+    mut ui_data: ResMut<UiData>,
+) {
+    ui_data.reduce_motion = accessibility.reduce_motion;
+    ui_data.path_preview = accessibility.path_preview;
+    ui_data.boundary_ring = accessibility.boundary_ring;
+}
+
 pub fn lock_mouse(mut windows: Query<&mut Window, With<PrimaryWindow>>) {
-    let mut window = windows.single_mut();
+    // The window can be gone while the game shuts down.
+    let mut window = match windows.get_single_mut() {
+        Ok(window) => window,
+        Err(_) => return,
+    };
     window.cursor.grab_mode = CursorGrabMode::Locked;
     window.cursor.visible = false;
 }
@@ -480,7 +1529,10 @@ pub fn on_click(
 }
 
 pub fn unlock_mouse(mut windows: Query<&mut Window, With<PrimaryWindow>>) {
-    let mut window = windows.single_mut();
+    let mut window = match windows.get_single_mut() {
+        Ok(window) => window,
+        Err(_) => return,
+    };
     window.cursor.grab_mode = CursorGrabMode::None;
     window.cursor.visible = true;
 }
@@ -507,23 +1559,24 @@ pub trait ComboBoxEnum {
 
     fn values(&self) -> Box<dyn Iterator<Item = Self>>;
 
-    fn value_label(&self) -> String;
+    fn value_label(&self, localization: &Localization) -> String;
 
     fn draw_as_combo_box(
         &mut self,
         ui: &mut Ui,
         width: f32,
+        localization: &Localization,
     ) -> InnerResponse<Option<Option<Response>>>
     where
         Self: Eq + Copy,
     {
         let mut inner_response = egui::ComboBox::from_id_source(Self::combo_box_label())
-            .selected_text(self.value_label())
+            .selected_text(self.value_label(localization))
             .width(width)
             .show_ui(ui, |ui| {
                 self.values()
                     .map(|value| {
-                        ui.selectable_value(self, value, value.value_label())
+                        ui.selectable_value(self, value, value.value_label(localization))
                             .kbgp_navigation()
                     })
                     .fold(None, |a, b| Some(a.map_or(b.clone(), |a| a | b.clone())))
@@ -552,17 +1605,18 @@ impl ComboBoxEnum for WindowMode {
         )
     }
 
-    fn value_label(&self) -> String {
-        match self {
-            WindowMode::Windowed => "Windowed",
-            #[cfg(not(target_arch = "wasm32"))]
-            WindowMode::BorderlessFullscreen => "Borderless Fullscreen",
-            #[cfg(target_arch = "wasm32")]
-            WindowMode::BorderlessFullscreen => "Fullscreen",
-            WindowMode::SizedFullscreen => "Fullscreen Custom",
-            WindowMode::Fullscreen => "Fullscreen",
-        }
-        .to_string()
+    fn value_label(&self, localization: &Localization) -> String {
+        localization
+            .t(match self {
+                WindowMode::Windowed => "window_mode_windowed",
+                #[cfg(not(target_arch = "wasm32"))]
+                WindowMode::BorderlessFullscreen => "window_mode_borderless",
+                #[cfg(target_arch = "wasm32")]
+                WindowMode::BorderlessFullscreen => "window_mode_fullscreen",
+                WindowMode::SizedFullscreen => "window_mode_custom",
+                WindowMode::Fullscreen => "window_mode_fullscreen",
+            })
+            .to_string()
     }
 }
 
@@ -573,3 +1627,263 @@ pub fn hide_ui(mut next_ui_state: ResMut<NextState<UiState>>) {
 pub fn show_pause_menu(mut next_ui_state: ResMut<NextState<UiState>>) {
     next_ui_state.set(UiState::PauseMenu);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::SavedWindowMode;
+    use crate::viewports::set_camera_viewports;
+    use bevy::ecs::system::SystemState;
+    use bevy::window::WindowResized;
+
+    #[test]
+    fn test_settings_returns_to_where_it_was_opened() {
+        let mut ui_data = UiData::default();
+        let mut next_ui_state = NextState::<UiState>::default();
+        ui_data.open_settings(UiState::PauseMenu, &mut next_ui_state);
+        assert_eq!(next_ui_state.0, Some(UiState::SettingsMenu));
+        ui_data.close_settings(&mut next_ui_state);
+        assert_eq!(next_ui_state.0, Some(UiState::PauseMenu));
+
+        ui_data.open_settings(UiState::Title, &mut next_ui_state);
+        ui_data.close_settings(&mut next_ui_state);
+        assert_eq!(next_ui_state.0, Some(UiState::Title));
+    }
+
+    #[test]
+    fn test_tuned_boid_settings_survive_starting_a_round() {
+        let tuned = BoidSettings {
+            max_speed: 123.0,
+            capture_range: 45.0,
+            ..default()
+        };
+        let mut app = App::new();
+        app.add_state::<AppState>()
+            .add_state::<UiState>()
+            .insert_resource(UiData::default())
+            .insert_resource(RoundSettings::default())
+            .insert_resource(tuned.clone())
+            .add_system(on_custom_game_enter.in_schedule(OnEnter(UiState::CustomGameMenu)))
+            .add_system(crate::despawn_game.in_schedule(OnEnter(AppState::Title)))
+            .add_system(crate::despawn_game.in_schedule(OnEnter(AppState::LoadRound)));
+        app.update();
+        app.world
+            .resource_mut::<NextState<UiState>>()
+            .set(UiState::CustomGameMenu);
+        app.update();
+        assert_eq!(app.world.resource::<UiData>().boid_settings, tuned);
+
+        app.world.resource_scope(|world, ui_data: Mut<UiData>| {
+            world.resource_scope(|world, mut boid_settings: Mut<BoidSettings>| {
+                ui_data.apply_custom_game(
+                    &mut world.resource_mut::<RoundSettings>(),
+                    &mut boid_settings,
+                );
+            });
+        });
+        for state in [AppState::LoadRound, AppState::Playing] {
+            app.world.resource_mut::<NextState<AppState>>().set(state);
+            app.update();
+        }
+        assert_eq!(app.world.resource::<State<AppState>>().0, AppState::Playing);
+        assert_eq!(*app.world.resource::<BoidSettings>(), tuned);
+    }
+
+    #[test]
+    fn test_viewport_preview_matches_player_viewports() {
+        let mut round_settings = RoundSettings::default();
+        for player in round_settings.players.iter_mut().take(3) {
+            player.player_type = PlayerType::AnyDevice;
+        }
+        round_settings.multiplayer_mode = MultiplayerMode::SplitScreenVertical;
+        let size = vec2(160.0, 90.0);
+        let preview = viewport_preview(&round_settings, size);
+
+        let viewports = round_settings.player_viewports(0.0);
+        assert_eq!(preview.len(), round_settings.local_player_count());
+        for (viewport_id, (rect, players)) in preview.iter().enumerate() {
+            let viewport = viewports.get(viewport_id);
+            assert_eq!(rect.min, egui::pos2(viewport.x * 160.0, viewport.y * 90.0));
+            assert_eq!(
+                rect.size(),
+                vec2(viewport.width * 160.0, viewport.height * 90.0)
+            );
+            assert_eq!(
+                round_settings.player_viewport_id(players[0]),
+                Some(viewport_id)
+            );
+        }
+
+        round_settings.multiplayer_mode = MultiplayerMode::SharedScreen;
+        let preview = viewport_preview(&round_settings, size);
+        assert_eq!(preview.len(), 1);
+        assert_eq!(preview[0].1.len(), round_settings.local_player_count());
+    }
+
+    #[test]
+    fn test_population_segments_are_proportional() {
+        let mut census = BoidCensus::default();
+        census.counts.insert(BoidColor::Red, 30);
+        census.counts.insert(BoidColor::Blue, 10);
+        census.counts.insert(BoidColor::Green, 0);
+        census.uncolored = 25;
+        let segments = population_segments(&census, 200.0);
+
+        assert_eq!(segments.len(), 2);
+        let width_of = |color: BoidColor| {
+            segments
+                .iter()
+                .find(|(c, _)| *c == color)
+                .map(|(_, width)| *width)
+                .unwrap()
+        };
+        assert_eq!(width_of(BoidColor::Red), 150.0);
+        assert_eq!(width_of(BoidColor::Blue), 50.0);
+        assert_eq!(segments.iter().map(|(_, width)| width).sum::<f32>(), 200.0);
+        assert!(population_segments(&BoidCensus::default(), 200.0).is_empty());
+    }
+
+    #[test]
+    #[allow(clippy::type_complexity)]
+    fn test_spectate_bots_hands_flocks_to_bots_and_spectates() {
+        let mut world = World::new();
+        let leader = world
+            .spawn((Leader, InputMap::<PlayerActions>::default()))
+            .id();
+        let camera = world.spawn(Camera2dFollow::new(leader)).id();
+
+        let mut system_state: SystemState<(
+            Commands,
+            Query<Entity, (With<Leader>, With<InputMap<PlayerActions>>)>,
+            Query<Entity, With<Camera2dFollow>>,
+        )> = SystemState::new(&mut world);
+        let (mut commands, human_leaders, player_cameras) = system_state.get_mut(&mut world);
+        spectate_bots(&mut commands, &human_leaders, &player_cameras);
+        system_state.apply(&mut world);
+
+        assert!(world.get::<InputMap<PlayerActions>>(leader).is_none());
+        assert_eq!(world.get::<Bot>(leader), Some(&Bot::default()));
+        assert!(world.get::<Spectating>(camera).is_some());
+        assert_eq!(world.get::<Camera2dFollow>(camera).unwrap().target, leader);
+    }
+
+    #[test]
+    fn test_hiding_the_hud_restores_debug_lines() {
+        let mut world = World::new();
+        world.insert_resource(State(AppState::Playing));
+        world.init_resource::<NextState<UiState>>();
+        world.init_resource::<HudHidden>();
+        world.insert_resource(BoidSettings {
+            debug_lines: true,
+            ..default()
+        });
+        let actions = world.spawn_empty().id();
+        let mut schedule = Schedule::new();
+        schedule.add_system(toggle_ui_visibility);
+        let mut toggle = |world: &mut World| {
+            let mut action_state = ActionState::<GlobalActions>::default();
+            action_state.press(GlobalActions::ToggleUiVisibility);
+            world.entity_mut(actions).insert(action_state);
+            schedule.run(world);
+        };
+
+        toggle(&mut world);
+        assert!(world.resource::<HudHidden>().hidden);
+        assert!(!world.resource::<BoidSettings>().debug_lines);
+        toggle(&mut world);
+        assert!(!world.resource::<HudHidden>().hidden);
+        assert!(world.resource::<BoidSettings>().debug_lines);
+
+        // Lines that were off before hiding stay off.
+        world.resource_mut::<BoidSettings>().debug_lines = false;
+        toggle(&mut world);
+        toggle(&mut world);
+        assert!(!world.resource::<BoidSettings>().debug_lines);
+    }
+
+    #[test]
+    fn test_history_labels_are_translated() {
+        let mut localization = Localization::default();
+        assert_eq!(time_ago(90, &localization), "1m");
+        assert_eq!(
+            player_type_label(&PlayerType::GamePad(Some(Gamepad::new(1))), &localization),
+            "Gamepad 2"
+        );
+        localization.language = Language::Spanish;
+        assert_eq!(time_ago(90, &localization), "1 min");
+        assert_eq!(
+            player_type_label(&PlayerType::ArrowKeys, &localization),
+            "Flechas"
+        );
+    }
+
+    #[test]
+    fn test_window_modes_are_translated() {
+        let mut localization = Localization::default();
+        assert_eq!(WindowMode::Windowed.value_label(&localization), "Windowed");
+        localization.language = Language::Spanish;
+        assert_eq!(WindowMode::Windowed.value_label(&localization), "Ventana");
+        // Language names stay in their own language so players can find theirs.
+        assert_eq!(Language::English.value_label(&localization), "English");
+    }
+
+    #[test]
+    fn test_saved_display_settings_are_restored_at_startup() {
+        let mut world = World::new();
+        world.init_resource::<UiData>();
+        world.init_resource::<CameraSettings>();
+        world.insert_resource(DisplaySettings {
+            window_mode: SavedWindowMode::BorderlessFullscreen,
+            camera_lead: 0.75,
+            ..default()
+        });
+        let window = world.spawn((Window::default(), PrimaryWindow)).id();
+        let mut schedule = Schedule::new();
+        schedule.add_system(restore_display_settings);
+        schedule.run(&mut world);
+        assert_eq!(
+            world.resource::<UiData>().window_mode,
+            WindowMode::BorderlessFullscreen
+        );
+        assert_eq!(
+            world.get::<Window>(window).unwrap().mode,
+            WindowMode::BorderlessFullscreen
+        );
+        assert_eq!(world.resource::<UiData>().camera_lead, 0.75);
+        assert_eq!(world.resource::<CameraSettings>().lead_blend, 0.75);
+    }
+
+    #[test]
+    fn test_saved_accessibility_settings_show_in_the_menu() {
+        let mut world = World::new();
+        world.init_resource::<UiData>();
+        world.insert_resource(AccessibilitySettings {
+            reduce_motion: true,
+            boundary_ring: false,
+            ..default()
+        });
+        let mut schedule = Schedule::new();
+        schedule.add_system(restore_accessibility_settings);
+        schedule.run(&mut world);
+        let ui_data = world.resource::<UiData>();
+        assert!(ui_data.reduce_motion);
+        assert!(!ui_data.boundary_ring);
+    }
+
+    #[test]
+    fn test_window_systems_dont_need_a_window() {
+        let mut world = World::new();
+        world.init_resource::<HudHidden>();
+        world.init_resource::<BoidSettings>();
+        world.init_resource::<Events<WindowResized>>();
+        let mut schedule = Schedule::new();
+        schedule.add_systems((
+            lock_mouse,
+            unlock_mouse,
+            on_title_enter,
+            on_title_exit,
+            set_camera_viewports,
+        ));
+        schedule.run(&mut world);
+    }
+}
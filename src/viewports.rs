@@ -104,6 +104,17 @@ impl ViewportRelative {
         self.border = border;
         self
     }
+
+    /// Returns the position and size of the viewport in pixels for a window of `window_size`.
+    /// The edges are rounded rather than the size so viewports next to each other share an edge
+    /// instead of leaving a gap.
+    pub fn physical_rect(&self, window_size: UVec2) -> (UVec2, UVec2) {
+        let window_size = window_size.as_vec2();
+        let min = (Vec2::new(self.x, self.y) * window_size).round() + self.border;
+        let max = (Vec2::new(self.x + self.width, self.y + self.height) * window_size).round()
+            - self.border;
+        (min.as_uvec2(), (max - min).max(Vec2::ZERO).as_uvec2())
+    }
 }
 
 pub fn set_camera_viewports(
@@ -117,22 +128,17 @@ pub fn set_camera_viewports(
     // so then each camera always takes up half the screen.
     // A resize_event is sent when the window is first created, allowing us to reuse this system for initial setup.
 
-    let window = windows.single();
+    let window = match windows.get_single() {
+        Ok(window) => window,
+        Err(_) => return,
+    };
     if resize_events.iter().count() != 0 || !added_query.is_empty() {
+        let window_size = UVec2::new(window.physical_width(), window.physical_height());
         for (mut camera, relative_viewport) in query.iter_mut() {
+            let (physical_position, physical_size) = relative_viewport.physical_rect(window_size);
             camera.viewport = Some(Viewport {
-                physical_position: UVec2::new(
-                    ((window.physical_width() as f32 * relative_viewport.x)
-                        + relative_viewport.border) as u32,
-                    ((window.physical_height() as f32 * relative_viewport.y)
-                        + relative_viewport.border) as u32,
-                ),
-                physical_size: UVec2::new(
-                    ((window.physical_width() as f32 * relative_viewport.width)
-                        - (relative_viewport.border * 2.0)) as u32,
-                    ((window.physical_height() as f32 * relative_viewport.height)
-                        - (relative_viewport.border * 2.0)) as u32,
-                ),
+                physical_position,
+                physical_size,
                 ..default()
             });
         }
@@ -156,7 +162,8 @@ impl PlayerViewports {
         border_thickness: f32,
     ) -> Self {
         let viewports = match player_count {
-            1 => vec![ViewportRelative::fullscreen()],
+            // Rounds without local players still get a view to spectate from.
+            0 | 1 => vec![ViewportRelative::fullscreen()],
             2 => match layout_preference {
                 ViewportLayoutPreference::Horizontal => {
                     vec![ViewportRelative::top(), ViewportRelative::bottom()]
@@ -211,7 +218,7 @@ impl PlayerViewports {
             },
             _ => unimplemented!(),
         };
-        debug_assert_eq!(viewports.len() as u8, player_count);
+        debug_assert_eq!(viewports.len() as u8, player_count.max(1));
         Self {
             viewports,
             border_thickness,
@@ -222,3 +229,45 @@ impl PlayerViewports {
         self.viewports[id].with_border(self.border_thickness)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_players_gets_a_spectator_viewport() {
+        let viewports = PlayerViewports::new(0, ViewportLayoutPreference::Horizontal, 2.0);
+        assert_eq!(viewports.viewports.len(), 1);
+        let viewport = viewports.get(0);
+        assert_eq!(
+            (viewport.x, viewport.y, viewport.width, viewport.height),
+            (0.0, 0.0, 1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_viewports_tile_odd_window_sizes() {
+        let window_size = UVec2::new(101, 67);
+        for player_count in 1..=8 {
+            for layout in [
+                ViewportLayoutPreference::Horizontal,
+                ViewportLayoutPreference::Vertical,
+            ] {
+                let viewports = PlayerViewports::new(player_count, layout, 0.0);
+                let mut covered = vec![0; (window_size.x * window_size.y) as usize];
+                for id in 0..player_count as usize {
+                    let (position, size) = viewports.get(id).physical_rect(window_size);
+                    for y in position.y..position.y + size.y {
+                        for x in position.x..position.x + size.x {
+                            covered[(y * window_size.x + x) as usize] += 1;
+                        }
+                    }
+                }
+                assert!(
+                    covered.iter().all(|c| *c == 1),
+                    "{player_count} viewports don't tile the window"
+                );
+            }
+        }
+    }
+}